@@ -8,19 +8,28 @@ use anchor_lang::prelude::*;
 use anchor_lang::solana_program::program_option::COption;
 use anchor_lang::solana_program::pubkey::Pubkey;
 use anchor_lang::system_program; // so system_program::ID is in scope
+use anchor_spl::associated_token::AssociatedToken;
 use anchor_spl::metadata::mpl_token_metadata::{
     accounts::Metadata as MetadataAccount, ID as TOKEN_METADATA_ID,
 };
 use anchor_spl::token::{
-    self, spl_token::instruction::AuthorityType, Burn, Mint, MintTo, SetAuthority, Token,
-    TokenAccount, Transfer,
+    self, spl_token::instruction::AuthorityType, spl_token::native_mint, Burn, CloseAccount,
+    InitializeAccount3, Mint, MintTo, SetAuthority, SyncNative, Token, TokenAccount, Transfer,
+};
+use anchor_spl::token_interface::{
+    self, Mint as InterfaceMint, TokenAccount as InterfaceTokenAccount, TokenInterface,
+    TransferChecked,
 };
 
 // ---------- Admin & Parameters ----------
 //
 // Parameters in this block are configurable for other projects.
-declare_id!("EGxd8LCM8Y1uMyXrWWapEMh9tH2whZaNBYhaV29Mq9fb");
+declare_id!("D7WCXt3tWc9z4H52KDpm97rwsh2fcA8bXbT2mjWmsJSz");
+#[cfg(not(feature = "localnet"))]
 pub const ADMIN: Pubkey = pubkey!("7kj6VagrS2AdohX3nsSazdsdgj6d1Sqh1fJqZHLMi3sK");
+// Local test admin (`tests/fixtures/admin.json`); never deploy a `localnet` build
+#[cfg(feature = "localnet")]
+pub const ADMIN: Pubkey = pubkey!("Av8yYbmsyjAKPpdaxaq8WuGJb2rmAAzvrCoY5j98M3aW");
 
 // Pricing rule (fixed):
 //  - 1 lamport == 1 base unit
@@ -32,8 +41,24 @@ const DECIMALS: u8 = 9;
 const MIN_SUPPLY_TOKENS: u64 = 100_000; // After initial sale, net circulating + vault >= this (in tokens)
 const MIN_WINDOW: i64 = 45; // initial sale minimum days
 const MAX_WINDOW: i64 = 90; // initial sale maximum days
-                            // End configurable block
-                            // ----------------------------------------------------------------
+const MAX_VAULT_SHARDS: u8 = 8; // solvency check passes every shard as remaining accounts
+const MAX_CREATOR_ALLOCATION_BPS: u16 = 1_000; // creator allocation cap: 10% of the sale supply
+const MAX_REFERRAL_REWARD_BPS: u16 = 500; // referral reward cap: 5% of the referred purchase
+const MIN_BUY_ORDER_LAMPORTS: u64 = 10_000_000; // smallest queued buy order: 0.01 SOL
+const MAX_OPEN_BUY_ORDERS: u64 = 256; // open buy orders the queue holds at once
+const DISTRIBUTION_INTERVAL_DAYS: i64 = 30; // minimum time between surplus snapshots
+const DISTRIBUTION_CLAIM_DAYS: i64 = 90; // claim window before unclaimed surplus returns to the vault
+const DISTRIBUTION_CHALLENGE_SECS: i64 = 60 * 60 * 24 * 3; // published root is challengeable, claims wait
+const DISTRIBUTION_CHALLENGE_BPS: u64 = 10; // stake to challenge a root: 0.1% of the snapshot circulating
+const DISTRIBUTION_CHALLENGE_BOND: u64 = 1_000_000_000; // escrowed per challenge: 1 SOL, lost if the root is republished
+
+// Audited v1 deployment that `migrate_from_legacy` redeems against
+const LEGACY_PROGRAM_ID: Pubkey = pubkey!("EGxd8LCM8Y1uMyXrWWapEMh9tH2whZaNBYhaV29Mq9fb");
+// End configurable block
+// ----------------------------------------------------------------
+
+/// Vault index of the primary SOL vault / token vault account (shards are `0..shard_count`).
+pub const PRIMARY_VAULT: u8 = u8::MAX;
 
 #[program]
 pub mod fair_token {
@@ -46,168 +71,206 @@ pub mod fair_token {
     /// - Establishes SOL vault (System-owned PDA) and token vault (program-owned PDA)
     /// - Sets fixed price rule: 1 lamport == 1 base unit (see DECIMALS comment)
     /// - Ensures metadata has been locked
-    pub fn initialize(ctx: Context<Initialize>, sale_end: i64) -> Result<()> {
-        // ---- time window checks ----
-        let now = Clock::get()?.unix_timestamp;
-        let max_sale_end = now + 60 * 60 * 24 * MAX_WINDOW;
-        let min_sale_end = now + 60 * 60 * 24 * MIN_WINDOW;
-        require!(sale_end <= max_sale_end, ErrorCode::SaleEndNotInRange);
-        require!(sale_end >= min_sale_end, ErrorCode::SaleEndNotInRange);
-
-        // ---- constants / overflow guard ----
-        let base_units_per_token: u64 = 10u64.pow(DECIMALS as u32);
-        require!(
-            MIN_SUPPLY_TOKENS <= u64::MAX / base_units_per_token,
-            ErrorCode::MinSupplyTooLarge
-        );
-
-        // ---- one-time init guard ----
-        let config = &mut ctx.accounts.config;
-        require!(!config.initialized, ErrorCode::AlreadyInitialized);
-
-        // ───────────────────────────────────────────────────────────────
-        // SPL Mint authority handoff (admin -> PDA)
-        // (Preconditions are enforced above via account constraints)
-        // ───────────────────────────────────────────────────────────────
-        let mint = &mut ctx.accounts.mint;
-        let admin = &ctx.accounts.admin;
-        let pda = ctx.accounts.mint_authority.key();
-
-        // Handoff: admin → PDA
-        let cpi_ctx = CpiContext::new(
-            ctx.accounts.token_program.to_account_info(),
-            anchor_spl::token::SetAuthority {
-                account_or_mint: mint.to_account_info(),
-                current_authority: admin.to_account_info(),
-            },
-        );
-        token::set_authority(cpi_ctx, AuthorityType::MintTokens, Some(pda))?;
-
-        // Confirm authority actually changed
-        mint.reload()?;
-        require!(
-            matches!(mint.mint_authority, COption::Some(x) if x == pda),
-            ErrorCode::WrongMintAuthority
-        );
-
-        // ── Metaplex metadata immutability check (off-chain lock enforced here) ──
-        let (expected_meta_pda, _) = Pubkey::find_program_address(
-            &[
-                b"metadata",
-                TOKEN_METADATA_ID.as_ref(),
-                mint.key().as_ref(), // ✅ use existing mutable reference
-            ],
-            &TOKEN_METADATA_ID,
-        );
-        require_keys_eq!(
-            expected_meta_pda,
-            ctx.accounts.metadata.key(),
-            ErrorCode::BadMetadataPda
-        );
-        // Redundant with #[account(owner = TOKEN_METADATA_ID)], but explicit for auditors:
-        require_keys_eq!(
-            ctx.accounts.metadata.owner.key(),
-            TOKEN_METADATA_ID,
-            ErrorCode::BadMetadataOwner
-        );
-
-        let data = ctx.accounts.metadata.try_borrow_data()?;
-        let meta = MetadataAccount::deserialize(&mut data.as_ref())
-            .map_err(|_| ErrorCode::BadMetadataData)?;
-        // Enforce that the off-chain script already revoked update authority
-        require!(
-            meta.update_authority == system_program::ID,
-            ErrorCode::MetadataStillMutable
-        );
-
+    /// - Optional `vault_ceiling`: post-finalization inventory cap of each vault token
+    ///   account (primary and each shard); redemptions that would exceed it burn the
+    ///   excess (supply contraction). `None` = no cap.
+    /// - Optional `creator_allocation`: up to `MAX_CREATOR_ALLOCATION_BPS` of the sale
+    ///   supply, minted by `finalize` into a vesting escrow (cliff + linear release; see
+    ///   `open_creator_vesting`). Escrowed tokens are not redeemable; each release must
+    ///   be backed 1:1 in SOL. Native SOL configs only. `None` = no developer allocation.
+    /// - Optional `referral_rewards`: reward rate (<= `MAX_REFERRAL_REWARD_BPS`) and
+    ///   per-referrer cap for referred buys, paid from the `["referral_pool"]` PDA,
+    ///   never from backing. `None` = referrals are attributed but not rewarded.
+    /// - Optional `distribution_crank`: the only key that may publish surplus
+    ///   distribution roots (`publish_distribution_root`). Native SOL configs only.
+    ///   `None` = surplus distributions disabled.
+    pub fn initialize(
+        ctx: Context<Initialize>,
+        sale_end: i64,
+        vault_ceiling: Option<u64>,
+        creator_allocation: Option<CreatorAllocation>,
+        referral_rewards: Option<ReferralRewards>,
+        distribution_crank: Option<Pubkey>,
+    ) -> Result<()> {
         // ---- SOL vault sanity (defense-in-depth) ----
         let ai = &ctx.accounts.sol_vault;
         require_keys_eq!(*ai.owner, system_program::ID, ErrorCode::InvalidOwner);
         require!(ai.data_len() == 0, ErrorCode::NonZeroData);
 
-        // ---- persist config ----
-        config.initialized = true;
-        config.finalized = false;
-        config.mint = mint.key();
-        config.sol_vault = ctx.accounts.sol_vault.key();
-        config.token_vault = ctx.accounts.token_vault.key();
-        config.token_vault_account = ctx.accounts.token_vault_account.key();
-        config.sale_end = sale_end;
-        config.total_burned = 0;
-        config.min_supply_base_units = MIN_SUPPLY_TOKENS * base_units_per_token;
-
-        emit!(InitializedEvent {
-            admin: ctx.accounts.admin.key(),
-            mint: mint.key(),
+        let kind = VaultKind::SystemSol {
+            sol_vault: ai.key(),
+            bump: ctx.bumps.sol_vault,
+        };
+        init_sale(
+            SaleInit {
+                admin: &ctx.accounts.admin,
+                mint: &mut ctx.accounts.mint,
+                mint_authority: ctx.accounts.mint_authority.key(),
+                mint_authority_bump: ctx.bumps.mint_authority,
+                config: &ctx.accounts.config,
+                config_bump: ctx.bumps.config,
+                token_vault: ctx.accounts.token_vault.key(),
+                token_vault_bump: ctx.bumps.token_vault,
+                token_vault_account: ctx.accounts.token_vault_account.key(),
+                metadata: &ctx.accounts.metadata,
+                token_program: ctx.accounts.token_program.to_account_info(),
+                event_stream: &mut ctx.accounts.event_stream,
+                event_stream_bump: ctx.bumps.event_stream,
+                ledger: &mut ctx.accounts.ledger,
+                ledger_bump: ctx.bumps.ledger,
+                events: EventSink::new(
+                    ctx.accounts.event_authority.to_account_info(),
+                    ctx.bumps.event_authority,
+                ),
+            },
+            kind,
+            sale_end,
+            vault_ceiling,
+            creator_allocation,
+            referral_rewards,
+            distribution_crank,
+        )
+    }
+
+    /// Same as `initialize`, but the SOL vault `["sol_vault"]` is owned by this program
+    /// and holds a `SolVault` header instead of being a bare system account:
+    /// - `backing_lamports` records the backing exactly, separate from the header's rent
+    /// - Redemptions debit the vault's lamports directly (no System Program CPI, no
+    ///   signer seeds, no rent-exempt edge case when the vault is drained)
+    /// - Buys still pay by System transfer (crediting needs no ownership) and add to the header
+    pub fn initialize_program_vault(
+        ctx: Context<InitializeProgramVault>,
+        sale_end: i64,
+        vault_ceiling: Option<u64>,
+        creator_allocation: Option<CreatorAllocation>,
+        referral_rewards: Option<ReferralRewards>,
+        distribution_crank: Option<Pubkey>,
+    ) -> Result<()> {
+        let kind = VaultKind::ProgramSol {
             sol_vault: ctx.accounts.sol_vault.key(),
-            token_vault: ctx.accounts.token_vault.key(),
+            bump: ctx.bumps.sol_vault,
+        };
+        init_sale(
+            SaleInit {
+                admin: &ctx.accounts.admin,
+                mint: &mut ctx.accounts.mint,
+                mint_authority: ctx.accounts.mint_authority.key(),
+                mint_authority_bump: ctx.bumps.mint_authority,
+                config: &ctx.accounts.config,
+                config_bump: ctx.bumps.config,
+                token_vault: ctx.accounts.token_vault.key(),
+                token_vault_bump: ctx.bumps.token_vault,
+                token_vault_account: ctx.accounts.token_vault_account.key(),
+                metadata: &ctx.accounts.metadata,
+                token_program: ctx.accounts.token_program.to_account_info(),
+                event_stream: &mut ctx.accounts.event_stream,
+                event_stream_bump: ctx.bumps.event_stream,
+                ledger: &mut ctx.accounts.ledger,
+                ledger_bump: ctx.bumps.ledger,
+                events: EventSink::new(
+                    ctx.accounts.event_authority.to_account_info(),
+                    ctx.bumps.event_authority,
+                ),
+            },
+            kind,
             sale_end,
-        });
+            vault_ceiling,
+            creator_allocation,
+            referral_rewards,
+            distribution_crank,
+        )
+    }
 
-        Ok(())
+    /// Same as `initialize`, but the fair token is backed by an SPL token (e.g. USDC)
+    /// instead of native SOL:
+    /// - Records `backing_mint` in config and creates the `backing_vault` PDA token account
+    ///   (authority = `token_vault` PDA)
+    /// - Price rule: 1 backing token == 1 fair token; base units are scaled by
+    ///   10^(DECIMALS - backing decimals), so the decimals may differ
+    /// - No SOL vault is created; `config.sol_vault` stays default so the SOL paths reject
+    pub fn initialize_token_backed(
+        ctx: Context<InitializeTokenBacked>,
+        sale_end: i64,
+        vault_ceiling: Option<u64>,
+        creator_allocation: Option<CreatorAllocation>,
+        referral_rewards: Option<ReferralRewards>,
+        distribution_crank: Option<Pubkey>,
+    ) -> Result<()> {
+        let kind = VaultKind::Token {
+            backing_mint: ctx.accounts.backing_mint.key(),
+            backing_vault: ctx.accounts.backing_vault.key(),
+            bump: ctx.bumps.backing_vault,
+            decimals: ctx.accounts.backing_mint.decimals,
+        };
+        init_sale(
+            SaleInit {
+                admin: &ctx.accounts.admin,
+                mint: &mut ctx.accounts.mint,
+                mint_authority: ctx.accounts.mint_authority.key(),
+                mint_authority_bump: ctx.bumps.mint_authority,
+                config: &ctx.accounts.config,
+                config_bump: ctx.bumps.config,
+                token_vault: ctx.accounts.token_vault.key(),
+                token_vault_bump: ctx.bumps.token_vault,
+                token_vault_account: ctx.accounts.token_vault_account.key(),
+                metadata: &ctx.accounts.metadata,
+                token_program: ctx.accounts.token_program.to_account_info(),
+                event_stream: &mut ctx.accounts.event_stream,
+                event_stream_bump: ctx.bumps.event_stream,
+                ledger: &mut ctx.accounts.ledger,
+                ledger_bump: ctx.bumps.ledger,
+                events: EventSink::new(
+                    ctx.accounts.event_authority.to_account_info(),
+                    ctx.bumps.event_authority,
+                ),
+            },
+            kind,
+            sale_end,
+            vault_ceiling,
+            creator_allocation,
+            referral_rewards,
+            distribution_crank,
+        )
     }
 
     /// Buy during initial sale (pre-finalization) or from vault (post-finalization).
     ///  - Pre-finalization: mint `lamports_sent` base units to user (PDA mint authority).
     ///  - Post-finalization: transfer `lamports_sent` base units from vault to user.
     /// In both cases, SOL moves buyer → SOL vault, same amount as base units minted/transferred.
-    pub fn buy_fair_token(ctx: Context<BuyFairToken>, lamports_sent: u64) -> Result<()> {
-        require!(lamports_sent > 0, ErrorCode::NoSOLSent);
-        require!(ctx.accounts.config.initialized, ErrorCode::NotInitialized);
+    /// Returns `BuyResult` as instruction return data (for CPI callers).
+    pub fn buy_fair_token(ctx: Context<BuyFairToken>, lamports_sent: u64) -> Result<BuyResult> {
+        let events = EventSink::new(
+            ctx.accounts.event_authority.to_account_info(),
+            ctx.bumps.event_authority,
+        );
 
-        let sale_end = ctx.accounts.config.sale_end;
-        let finalized = ctx.accounts.config.finalized;
+        let config = ctx.accounts.config.load()?;
+        require!(lamports_sent > 0, ErrorCode::NoSOLSent);
+        require!(config.is_initialized(), ErrorCode::NotInitialized);
 
-        if !finalized {
-            // Optional clarity check: mint authority is still the PDA
-            require!(
-                matches!(ctx.accounts.mint.mint_authority, COption::Some(x) if x == ctx.accounts.mint_authority.key()),
-                ErrorCode::WrongMintAuthority
-            );
+        check_finalize_not_pending(&config)?;
+        let finalized = config.is_finalized();
+        let referral = accrue_referral(
+            &config,
+            ctx.accounts.referral.as_mut(),
+            ctx.accounts.buyer.key(),
+            lamports_sent,
+        )?;
 
-            // pre-finalization: mint (1 lamport == 1 base unit)
-            let seeds: &[&[u8]] = &[b"mint_authority", &[ctx.bumps.mint_authority]];
-            let signer: &[&[&[u8]]] = &[seeds];
-            let cpi_ctx = CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                MintTo {
-                    mint: ctx.accounts.mint.to_account_info(),
-                    to: ctx.accounts.user_token_account.to_account_info(),
-                    authority: ctx.accounts.mint_authority.to_account_info(),
-                },
-                signer,
-            );
-            token::mint_to(cpi_ctx, lamports_sent)?;
-            emit!(BuyMintEvent {
-                buyer: ctx.accounts.buyer.key(),
-                amount: lamports_sent,
-                lamports: lamports_sent, // amount == lamports (1:1 base units)
-            });
-        } else {
-            // post-finalization: transfer from vault (1 lamport == 1 base unit)
-            require!(
-                ctx.accounts.token_vault_account.amount >= lamports_sent,
-                ErrorCode::VaultInsufficient
-            );
-            let seeds: &[&[u8]] = &[b"token_vault", &[ctx.bumps.token_vault]];
-            let signer: &[&[&[u8]]] = &[seeds];
-            let cpi_ctx = CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                Transfer {
-                    from: ctx.accounts.token_vault_account.to_account_info(),
-                    to: ctx.accounts.user_token_account.to_account_info(),
-                    authority: ctx.accounts.token_vault.to_account_info(),
-                },
-                signer,
-            );
-            token::transfer(cpi_ctx, lamports_sent)?;
-            emit!(BuyVaultEvent {
-                buyer: ctx.accounts.buyer.key(),
-                amount: lamports_sent,
-                lamports: lamports_sent, // amount == lamports (1:1 base units)
-                finalized,
-            });
-        }
+        // 1 lamport == 1 base unit
+        mint_or_release(
+            finalized,
+            &mut ctx.accounts.ledger,
+            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.mint.to_account_info(),
+            ctx.accounts.mint_authority.to_account_info(),
+            config.mint_authority_bump,
+            ctx.accounts.token_vault.to_account_info(),
+            config.token_vault_bump,
+            &ctx.accounts.token_vault_account,
+            ctx.accounts.user_token_account.to_account_info(),
+            lamports_sent,
+        )?;
 
         // buyer -> sol_vault (SOL)
         anchor_lang::solana_program::program::invoke(
@@ -221,29 +284,173 @@ pub mod fair_token {
                 ctx.accounts.sol_vault.to_account_info(),
             ],
         )?;
+        add_checked(&mut ctx.accounts.ledger.lamports_in, lamports_sent)?;
+        record_sol_vault_deposit(&config, &ctx.accounts.sol_vault, lamports_sent)?;
 
-        // Auto-finalize trigger: only if not already finalized and now > sale_end
-        let now = Clock::get()?.unix_timestamp;
-        if !finalized && now > sale_end {
-            finalize_sale(
-                &mut ctx.accounts.config,
-                &ctx.accounts.mint,
-                ctx.accounts.mint_authority.to_account_info(),
-                ctx.accounts.token_vault_account.to_account_info(),
-                ctx.accounts.token_program.to_account_info(),
-                ctx.bumps.mint_authority,
-                lamports_sent,
-                0,
-            )?;
-        }
-        Ok(())
+        // Event carries post-transaction state
+        let vault_lamports = sol_vault_backing(&config, &ctx.accounts.sol_vault)?;
+        let state = post_state(
+            &mut ctx.accounts.ledger,
+            vault_lamports,
+            ctx.accounts.mint.as_ref(),
+            ctx.accounts.token_vault_account.as_ref(),
+            Some(ctx.accounts.user_token_account.as_ref()),
+        )?;
+        emit_buy_event(
+            &events,
+            ctx.accounts.buyer.key(),
+            ctx.accounts.buyer.key(),
+            lamports_sent,
+            lamports_sent,
+            finalized,
+            referral,
+            state,
+        )?;
+        Ok(BuyResult {
+            path: if finalized {
+                BuyPath::Vault
+            } else {
+                BuyPath::Mint
+            },
+            amount: lamports_sent,
+            lamports: lamports_sent,
+            state,
+        })
+    }
+
+    /// Fill-mode buy. Pre-finalization this is identical to `buy_fair_token` (mint always
+    /// fills). Post-finalization it fills `min(lamports_requested, vault balance)` and
+    /// charges only that many lamports; fails if the fill would be below `min_fill`
+    /// (`min_fill == lamports_requested` is all-or-nothing). Returns `BuyResult`.
+    pub fn buy_fair_token_fill(
+        ctx: Context<BuyFairToken>,
+        lamports_requested: u64,
+        min_fill: u64,
+    ) -> Result<BuyResult> {
+        let events = EventSink::new(
+            ctx.accounts.event_authority.to_account_info(),
+            ctx.bumps.event_authority,
+        );
+
+        let config = ctx.accounts.config.load()?;
+        require!(lamports_requested > 0, ErrorCode::NoSOLSent);
+        require!(config.is_initialized(), ErrorCode::NotInitialized);
+        require!(min_fill <= lamports_requested, ErrorCode::InvalidMinFill);
+
+        check_finalize_not_pending(&config)?;
+        let finalized = config.is_finalized();
+
+        let filled = if finalized {
+            lamports_requested.min(ctx.accounts.token_vault_account.amount)
+        } else {
+            lamports_requested
+        };
+        require!(
+            filled > 0 && filled >= min_fill,
+            ErrorCode::FillBelowMinimum
+        );
+        let referral = accrue_referral(
+            &config,
+            ctx.accounts.referral.as_mut(),
+            ctx.accounts.buyer.key(),
+            filled,
+        )?;
+
+        mint_or_release(
+            finalized,
+            &mut ctx.accounts.ledger,
+            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.mint.to_account_info(),
+            ctx.accounts.mint_authority.to_account_info(),
+            config.mint_authority_bump,
+            ctx.accounts.token_vault.to_account_info(),
+            config.token_vault_bump,
+            &ctx.accounts.token_vault_account,
+            ctx.accounts.user_token_account.to_account_info(),
+            filled,
+        )?;
+
+        // buyer -> sol_vault (SOL), only for what was filled
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.buyer.to_account_info(),
+                to: ctx.accounts.sol_vault.to_account_info(),
+            },
+        );
+        system_program::transfer(cpi_ctx, filled)?;
+        add_checked(&mut ctx.accounts.ledger.lamports_in, filled)?;
+        record_sol_vault_deposit(&config, &ctx.accounts.sol_vault, filled)?;
+
+        // Event carries post-transaction state
+        let vault_lamports = sol_vault_backing(&config, &ctx.accounts.sol_vault)?;
+        let state = post_state(
+            &mut ctx.accounts.ledger,
+            vault_lamports,
+            ctx.accounts.mint.as_ref(),
+            ctx.accounts.token_vault_account.as_ref(),
+            Some(ctx.accounts.user_token_account.as_ref()),
+        )?;
+        emit_buy_event(
+            &events,
+            ctx.accounts.buyer.key(),
+            ctx.accounts.buyer.key(),
+            filled,
+            lamports_requested,
+            finalized,
+            referral,
+            state,
+        )?;
+        Ok(BuyResult {
+            path: if finalized {
+                BuyPath::Vault
+            } else {
+                BuyPath::Mint
+            },
+            amount: filled,
+            lamports: filled,
+            state,
+        })
     }
 
     /// Redeem during initial sale (pre-finalization) or after (post-finalization).
     ///  - Pre-finalization: burn `amount_to_redeem` base units from user; pay same SOL from vault to redeemer.
     ///  - Post-finalization: transfer `amount_to_redeem` base units from user to vault; pay same SOL from vault.
-    pub fn redeem_fair_token(ctx: Context<RedeemFairToken>, amount_to_redeem: u64) -> Result<()> {
-        require!(ctx.accounts.config.initialized, ErrorCode::NotInitialized);
+    ///  - Post-finalization, waiting buy orders passed as remaining accounts are filled
+    ///    first (see `fill_buy_orders`); the rest goes to the vault. Every restocking
+    ///    redeem (`redeem_to`, `redeem_delegated`, `redeem_fair_token_wsol`,
+    ///    `redeem_sharded`) does the same; with no orders passed it just restocks.
+    /// Returns `RedeemResult` as instruction return data (for CPI callers).
+    pub fn redeem_fair_token<'info>(
+        ctx: Context<'_, '_, '_, 'info, RedeemFairToken<'info>>,
+        amount_to_redeem: u64,
+    ) -> Result<RedeemResult> {
+        redeem_primary(ctx, amount_to_redeem, false)
+    }
+
+    /// Same as `redeem_fair_token`, and if the redemption empties `user_token_account`,
+    /// closes it and returns its rent to the redeemer (`RedeemResult.closed`).
+    pub fn redeem_fair_token_and_close<'info>(
+        ctx: Context<'_, '_, '_, 'info, RedeemFairToken<'info>>,
+        amount_to_redeem: u64,
+    ) -> Result<RedeemResult> {
+        redeem_primary(ctx, amount_to_redeem, true)
+    }
+
+    /// Same as `redeem_fair_token`, but the SOL is paid to `recipient` (cold wallet,
+    /// escrow program, smart-wallet PDA) instead of the redeemer. Waiting buy orders
+    /// passed as remaining accounts are filled first, as in `redeem_fair_token`.
+    pub fn redeem_to<'info>(
+        ctx: Context<'_, '_, '_, 'info, RedeemTo<'info>>,
+        amount_to_redeem: u64,
+    ) -> Result<()> {
+        let events = EventSink::new(
+            ctx.accounts.event_authority.to_account_info(),
+            ctx.bumps.event_authority,
+        );
+
+        let config = ctx.accounts.config.load()?;
+        require!(config.is_initialized(), ErrorCode::NotInitialized);
         require!(amount_to_redeem > 0, ErrorCode::ZeroTokenRedeem);
         require!(
             ctx.accounts.user_token_account.amount >= amount_to_redeem,
@@ -254,346 +461,5068 @@ pub mod fair_token {
             ErrorCode::InvalidMint
         );
         require!(
-            ctx.accounts.sol_vault.key() == ctx.accounts.config.sol_vault,
+            ctx.accounts.sol_vault.key() == config.sol_vault,
             ErrorCode::InvalidVault
         );
         require!(
-            amount_to_redeem <= ctx.accounts.sol_vault.lamports(),
+            amount_to_redeem <= sol_vault_backing(&config, &ctx.accounts.sol_vault)?,
             ErrorCode::VaultSOLInsufficient
         );
 
-        let sale_end = ctx.accounts.config.sale_end;
-        let finalized = ctx.accounts.config.finalized;
-
-        if !finalized {
-            // burn redeemed tokens (1 lamport == 1 base unit)
-            let cpi_ctx = CpiContext::new(
+        let finalized = config.is_finalized();
+        // Post-finalization: serve queued buy orders first (orders in remaining accounts)
+        let queued_fill = if finalized {
+            fill_buy_orders(
+                &ctx.accounts.buy_queue,
+                ctx.remaining_accounts,
+                &mut ctx.accounts.ledger,
                 ctx.accounts.token_program.to_account_info(),
-                Burn {
-                    mint: ctx.accounts.mint.to_account_info(),
-                    from: ctx.accounts.user_token_account.to_account_info(),
-                    authority: ctx.accounts.redeemer.to_account_info(),
-                },
-            );
-            token::burn(cpi_ctx, amount_to_redeem)?;
-            ctx.accounts.config.total_burned = ctx
-                .accounts
-                .config
-                .total_burned
-                .saturating_add(amount_to_redeem);
-
-            emit!(RedeemBurnEvent {
-                redeemer: ctx.accounts.redeemer.key(),
-                amount: amount_to_redeem,
-                lamports: amount_to_redeem, // amount == lamports (1:1 base units)
-            });
+                ctx.accounts.user_token_account.to_account_info(),
+                ctx.accounts.redeemer.to_account_info(),
+                &[],
+                ctx.accounts.sol_vault.to_account_info(),
+                ctx.accounts.redeemer.key(),
+                amount_to_redeem,
+                &events,
+            )?
         } else {
-            // after finalization, transfer redeemed tokens back to vault (1:1 base units)
-            let cpi_ctx = CpiContext::new(
+            0
+        };
+        add_checked(&mut ctx.accounts.ledger.total_queue_filled, queued_fill)?;
+
+        record_sol_vault_deposit(&config, &ctx.accounts.sol_vault, queued_fill)?;
+
+        // burn, or transfer back to vault after finalization (1 lamport == 1 base unit)
+        let to_vault = amount_to_redeem - queued_fill;
+        if to_vault > 0 {
+            burn_or_restock(
+                finalized,
+                config.vault_ceiling,
+                &mut ctx.accounts.ledger,
                 ctx.accounts.token_program.to_account_info(),
-                Transfer {
-                    from: ctx.accounts.user_token_account.to_account_info(),
-                    to: ctx.accounts.token_vault_account.to_account_info(),
-                    authority: ctx.accounts.redeemer.to_account_info(),
-                },
-            );
-            token::transfer(cpi_ctx, amount_to_redeem)?;
-            emit!(RedeemVaultEvent {
-                redeemer: ctx.accounts.redeemer.key(),
-                amount: amount_to_redeem,
-                lamports: amount_to_redeem, // amount == lamports (1:1 base units)
-            });
+                ctx.accounts.mint.to_account_info(),
+                ctx.accounts.user_token_account.to_account_info(),
+                ctx.accounts.redeemer.to_account_info(),
+                ctx.accounts.token_vault.to_account_info(),
+                config.token_vault_bump,
+                &ctx.accounts.token_vault_account,
+                to_vault,
+                &events,
+            )?;
         }
 
-        // SOL: transfer from SOL vault PDA → redeemer, authorized by PDA seeds
-        {
-            let vault_ai = ctx.accounts.sol_vault.to_account_info();
-            let redeemer_ai = ctx.accounts.redeemer.to_account_info();
+        // SOL: transfer from SOL vault PDA → recipient, authorized by PDA seeds
+        pay_from_sol_vault(
+            &config,
+            ctx.accounts.system_program.to_account_info(),
+            ctx.accounts.sol_vault.to_account_info(),
+            ctx.accounts.recipient.to_account_info(),
+            amount_to_redeem,
+        )?;
+        add_checked(&mut ctx.accounts.ledger.lamports_out, amount_to_redeem)?;
+
+        // Event carries post-transaction state
+        let vault_lamports = sol_vault_backing(&config, &ctx.accounts.sol_vault)?;
+        let state = post_state(
+            &mut ctx.accounts.ledger,
+            vault_lamports,
+            ctx.accounts.mint.as_ref(),
+            ctx.accounts.token_vault_account.as_ref(),
+            Some(ctx.accounts.user_token_account.as_ref()),
+        )?;
+        emit_redeem_event(
+            &events,
+            ctx.accounts.redeemer.key(),
+            ctx.accounts.recipient.key(),
+            amount_to_redeem,
+            finalized,
+            false,
+            state,
+        )?;
+        Ok(())
+    }
+
+    /// Records where SOL from delegated redemptions of the owner's tokens is paid
+    /// (see `redeem_delegated`). Without this record, SOL goes to the token owner.
+    pub fn set_redeem_destination(
+        ctx: Context<SetRedeemDestination>,
+        destination: Pubkey,
+    ) -> Result<()> {
+        let record = &mut ctx.accounts.redeem_destination;
+        record.owner = ctx.accounts.owner.key();
+        record.destination = destination;
+        Ok(())
+    }
 
-            let bump = ctx.bumps.sol_vault;
-            let seeds_arr: [&[u8]; 2] = [b"sol_vault", &[bump]];
-            let signer_seeds: &[&[u8]] = &seeds_arr;
-            let signers: &[&[&[u8]]] = &[signer_seeds];
+    /// Redeem by an SPL delegate (approved via `approve`), up to its `delegated_amount`.
+    /// Burn / vault-transfer is signed by the delegate, so SPL Token decrements the
+    /// allowance. SOL goes to the token owner, or to the owner's recorded destination.
+    pub fn redeem_delegated<'info>(
+        ctx: Context<'_, '_, '_, 'info, RedeemDelegated<'info>>,
+        amount_to_redeem: u64,
+    ) -> Result<()> {
+        let events = EventSink::new(
+            ctx.accounts.event_authority.to_account_info(),
+            ctx.bumps.event_authority,
+        );
 
-            let cpi_ctx = CpiContext::new_with_signer(
-                ctx.accounts.system_program.to_account_info(),
-                system_program::Transfer {
-                    from: vault_ai,
-                    to: redeemer_ai,
-                },
-                signers,
-            );
-            system_program::transfer(cpi_ctx, amount_to_redeem)?;
-        }
+        let config = ctx.accounts.config.load()?;
+        require!(config.is_initialized(), ErrorCode::NotInitialized);
+        require!(amount_to_redeem > 0, ErrorCode::ZeroTokenRedeem);
+        require!(
+            ctx.accounts.user_token_account.amount >= amount_to_redeem,
+            ErrorCode::InsufficientTokens
+        );
+        require!(
+            ctx.accounts.user_token_account.delegated_amount >= amount_to_redeem,
+            ErrorCode::DelegateAllowanceExceeded
+        );
+        require!(
+            amount_to_redeem <= sol_vault_backing(&config, &ctx.accounts.sol_vault)?,
+            ErrorCode::VaultSOLInsufficient
+        );
 
-        // Auto-finalize if crossed sale_end
-        let now = Clock::get()?.unix_timestamp;
-        if !finalized && now > sale_end {
-            finalize_sale(
-                &mut ctx.accounts.config,
-                &ctx.accounts.mint,
-                ctx.accounts.mint_authority.to_account_info(),
-                ctx.accounts.token_vault_account.to_account_info(),
+        // SOL destination: owner's recorded choice, else the owner itself
+        let record_ai = &ctx.accounts.redeem_destination;
+        let expected_destination = if record_ai.owner == &crate::ID && !record_ai.data_is_empty() {
+            let data = record_ai.try_borrow_data()?;
+            RedeemDestination::try_deserialize(&mut data.as_ref())?.destination
+        } else {
+            ctx.accounts.user_token_account.owner
+        };
+        require_keys_eq!(
+            ctx.accounts.destination.key(),
+            expected_destination,
+            ErrorCode::WrongRedeemDestination
+        );
+
+        let finalized = config.is_finalized();
+        // Post-finalization: serve queued buy orders first (orders in remaining accounts)
+        let queued_fill = if finalized {
+            fill_buy_orders(
+                &ctx.accounts.buy_queue,
+                ctx.remaining_accounts,
+                &mut ctx.accounts.ledger,
                 ctx.accounts.token_program.to_account_info(),
-                ctx.bumps.mint_authority,
-                0,
+                ctx.accounts.user_token_account.to_account_info(),
+                ctx.accounts.delegate.to_account_info(),
+                &[],
+                ctx.accounts.sol_vault.to_account_info(),
+                ctx.accounts.delegate.key(),
                 amount_to_redeem,
+                &events,
+            )?
+        } else {
+            0
+        };
+        add_checked(&mut ctx.accounts.ledger.total_queue_filled, queued_fill)?;
+
+        record_sol_vault_deposit(&config, &ctx.accounts.sol_vault, queued_fill)?;
+
+        // burn, or transfer back to vault after finalization (1 lamport == 1 base unit)
+        let to_vault = amount_to_redeem - queued_fill;
+        if to_vault > 0 {
+            burn_or_restock(
+                finalized,
+                config.vault_ceiling,
+                &mut ctx.accounts.ledger,
+                ctx.accounts.token_program.to_account_info(),
+                ctx.accounts.mint.to_account_info(),
+                ctx.accounts.user_token_account.to_account_info(),
+                ctx.accounts.delegate.to_account_info(),
+                ctx.accounts.token_vault.to_account_info(),
+                config.token_vault_bump,
+                &ctx.accounts.token_vault_account,
+                to_vault,
+                &events,
             )?;
         }
+
+        pay_from_sol_vault(
+            &config,
+            ctx.accounts.system_program.to_account_info(),
+            ctx.accounts.sol_vault.to_account_info(),
+            ctx.accounts.destination.to_account_info(),
+            amount_to_redeem,
+        )?;
+        add_checked(&mut ctx.accounts.ledger.lamports_out, amount_to_redeem)?;
+
+        // Event carries post-transaction state
+        let vault_lamports = sol_vault_backing(&config, &ctx.accounts.sol_vault)?;
+        let state = post_state(
+            &mut ctx.accounts.ledger,
+            vault_lamports,
+            ctx.accounts.mint.as_ref(),
+            ctx.accounts.token_vault_account.as_ref(),
+            Some(ctx.accounts.user_token_account.as_ref()),
+        )?;
+        emit_redeem_event(
+            &events,
+            ctx.accounts.delegate.key(),
+            ctx.accounts.destination.key(),
+            amount_to_redeem,
+            finalized,
+            false,
+            state,
+        )?;
         Ok(())
     }
-}
 
-fn finalize_sale<'info>(
-    // Finalize the initial sale period. This function may be called inside a buy/redeem
-    // that happens after sale_end. Since `mint.supply` doesn't include the current tx's
-    // mint/burn effects yet, we pass `bought_this_transaction` and `redeemed_this_transaction`
-    // to compute the shortfall properly.
-    config: &mut Account<'info, Config>,
-    mint: &Account<'info, Mint>,
-    mint_authority: AccountInfo<'info>,
-    token_vault_account: AccountInfo<'info>,
-    token_program: AccountInfo<'info>,
-    bump: u8,
-    bought_this_transaction: u64,
-    redeemed_this_transaction: u64,
-) -> Result<()> {
-    if config.finalized {
-        return Ok(());
-    }
+    /// Post-finalization buy order: escrows `lamports` (at least `MIN_BUY_ORDER_LAMPORTS`)
+    /// in a new `BuyOrder` PDA at the tail of the FIFO `BuyQueue`, which holds at most
+    /// `MAX_OPEN_BUY_ORDERS` open orders. Orders are filled by later restocking redeems
+    /// that pass them (instead of restocking the vault) or from vault inventory by
+    /// `fill_buy_orders_from_vault`, and can be cancelled at any time.
+    pub fn place_buy_order(ctx: Context<PlaceBuyOrder>, lamports: u64) -> Result<()> {
+        let events = EventSink::new(
+            ctx.accounts.event_authority.to_account_info(),
+            ctx.bumps.event_authority,
+        );
 
-    // Calculate shortfall to meet min_supply, correcting for this tx’s net effect.
-    let net_minted: i128 = (config.min_supply_base_units as i128)
-        - (mint.supply as i128)
-        - (bought_this_transaction as i128)
-        + (redeemed_this_transaction as i128);
+        require!(lamports > 0, ErrorCode::NoSOLSent);
+        require!(
+            lamports >= MIN_BUY_ORDER_LAMPORTS,
+            ErrorCode::BuyOrderTooSmall
+        );
+        require!(
+            ctx.accounts.config.load()?.is_finalized(),
+            ErrorCode::SaleNotFinalized
+        );
 
-    let to_be_minted: u64 = if net_minted > 0 { net_minted as u64 } else { 0 };
+        let queue = &mut ctx.accounts.buy_queue;
+        require!(
+            queue.open_orders < MAX_OPEN_BUY_ORDERS,
+            ErrorCode::BuyQueueFull
+        );
+        let order = &mut ctx.accounts.buy_order;
+        order.id = queue.tail;
+        order.buyer = ctx.accounts.buyer.key();
+        order.recipient_token_account = ctx.accounts.user_token_account.key();
+        order.remaining = lamports;
+        order.bump = ctx.bumps.buy_order;
+        queue.tail = queue.tail.checked_add(1).ok_or(ErrorCode::MathOverflow)?;
+        queue.open_orders += 1;
+        queue.escrowed_lamports = queue
+            .escrowed_lamports
+            .checked_add(lamports)
+            .ok_or(ErrorCode::MathOverflow)?;
 
-    // PDA signer seeds
-    let signer_seeds: &[&[u8]] = &[b"mint_authority", &[bump]];
-    let signer: &[&[&[u8]]] = &[signer_seeds];
+        // buyer -> order PDA (escrow, on top of the order's rent)
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.buyer.to_account_info(),
+                to: ctx.accounts.buy_order.to_account_info(),
+            },
+        );
+        system_program::transfer(cpi_ctx, lamports)?;
 
-    if to_be_minted > 0 {
-        // Mint shortfall into the vault (authority is the PDA)
-        let mint_to_accounts = MintTo {
-            mint: mint.to_account_info(),
-            to: token_vault_account.clone(),
-            authority: mint_authority.clone(),
-        };
-        let cpi = CpiContext::new_with_signer(token_program.clone(), mint_to_accounts, signer);
-        token::mint_to(cpi, to_be_minted)?;
+        events.emit(BuyOrderPlacedEvent {
+            seq: next_seq(&mut ctx.accounts.buy_queue.event_seq)?,
+            id: ctx.accounts.buy_order.id,
+            buyer: ctx.accounts.buyer.key(),
+            lamports,
+        })?;
+        Ok(())
+    }
+
+    /// Cancels (or, once filled, closes) a buy order: unfilled escrow and rent go back
+    /// to the buyer.
+    pub fn cancel_buy_order(ctx: Context<CancelBuyOrder>) -> Result<()> {
+        let events = EventSink::new(
+            ctx.accounts.event_authority.to_account_info(),
+            ctx.bumps.event_authority,
+        );
+
+        let order = &ctx.accounts.buy_order;
+        let queue = &mut ctx.accounts.buy_queue;
+        queue.escrowed_lamports = queue.escrowed_lamports.saturating_sub(order.remaining);
+        if order.remaining > 0 {
+            queue.open_orders = queue.open_orders.saturating_sub(1);
+        }
 
-        emit!(MinimumEnforcedEvent {
-            forced_minted: to_be_minted
-        });
+        events.emit(BuyOrderCancelledEvent {
+            seq: next_seq(&mut ctx.accounts.buy_queue.event_seq)?,
+            id: order.id,
+            buyer: order.buyer,
+            refunded: order.remaining,
+        })?;
+        Ok(())
     }
 
-    // Revoke MintTokens authority: PDA -> None (finalize)
-    let set_auth_accounts = SetAuthority {
-        account_or_mint: mint.to_account_info(),
-        current_authority: mint_authority.clone(),
-    };
-    let cpi = CpiContext::new_with_signer(token_program, set_auth_accounts, signer);
-    token::set_authority(cpi, AuthorityType::MintTokens, None)?;
+    /// Permissionless crank, post-finalization: fills the orders at the head of the
+    /// `BuyQueue` from the primary token vault, up to its balance. Remaining accounts are
+    /// the order triples of `fill_buy_orders`, from `BuyQueue.head`. Each fill is a vault
+    /// buy at the 1:1 price (vault tokens → order recipient, escrow → primary SOL vault),
+    /// so anyone can serve queued orders from inventory before a later `buy_*` takes it.
+    pub fn fill_buy_orders_from_vault<'info>(
+        ctx: Context<'_, '_, '_, 'info, FillBuyOrdersFromVault<'info>>,
+    ) -> Result<()> {
+        let events = EventSink::new(
+            ctx.accounts.event_authority.to_account_info(),
+            ctx.bumps.event_authority,
+        );
 
-    // Mark finalized and emit supply after including the shortfall minted just now.
-    // (No reload needed; we intentionally use pre-mint supply + to_be_minted.)
-    config.finalized = true;
-    emit!(SaleFinalizedEvent {
-        total_supply: mint.supply.saturating_add(to_be_minted)
-    });
+        let config = ctx.accounts.config.load()?;
+        require!(config.is_finalized(), ErrorCode::SaleNotFinalized);
+        require!(
+            !ctx.remaining_accounts.is_empty(),
+            ErrorCode::BadQueueAccounts
+        );
 
-    Ok(())
-}
+        let seeds: &[&[u8]] = &[b"token_vault", &[config.token_vault_bump]];
+        let filled = fill_buy_orders(
+            &ctx.accounts.buy_queue.to_account_info(),
+            ctx.remaining_accounts,
+            &mut ctx.accounts.ledger,
+            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.token_vault_account.to_account_info(),
+            ctx.accounts.token_vault.to_account_info(),
+            &[seeds],
+            ctx.accounts.sol_vault.to_account_info(),
+            ctx.accounts.token_vault.key(),
+            ctx.accounts.token_vault_account.amount,
+            &events,
+        )?;
+        require!(filled > 0, ErrorCode::VaultInsufficient);
+        add_checked(&mut ctx.accounts.ledger.total_bought_from_vault, filled)?;
+        record_sol_vault_deposit(&config, &ctx.accounts.sol_vault, filled)?;
+        Ok(())
+    }
 
-// ------------------------- Accounts -------------------------
+    /// Same as `buy_fair_token`, but the tokens go to `recipient` (gift, payroll, custodial
+    /// payout). The recipient's ATA is created if needed, paid by the buyer.
+    pub fn buy_for(ctx: Context<BuyFor>, lamports_sent: u64) -> Result<()> {
+        let events = EventSink::new(
+            ctx.accounts.event_authority.to_account_info(),
+            ctx.bumps.event_authority,
+        );
 
-#[derive(Accounts)]
-pub struct Initialize<'info> {
-    /// Admin signer (hard-gated; remove `address = ADMIN` if you don't want gating)
-    #[account(mut, address = ADMIN)]
-    pub admin: Signer<'info>,
+        let config = ctx.accounts.config.load()?;
+        require!(lamports_sent > 0, ErrorCode::NoSOLSent);
+        require!(config.is_initialized(), ErrorCode::NotInitialized);
 
-    /// Pre-created SPL mint (vanity). Must be: decimals=DECIMALS, freeze=None, supply=0, mint_authority=admin.
-    /// We will handoff authority to the PDA inside this instruction.
-    #[account(
-        mut,
-        constraint = mint.decimals == DECIMALS                       @ ErrorCode::WrongDecimals,
-        constraint = mint.freeze_authority.is_none()                 @ ErrorCode::FreezeNotRevoked,
-        constraint = mint.supply == 0                                @ ErrorCode::NonZeroInitialSupply,
-        constraint = mint.mint_authority == Some(admin.key()).into() @ ErrorCode::MintAuthorityMustBeAdmin,
-    )]
-    pub mint: Account<'info, Mint>,
+        check_finalize_not_pending(&config)?;
+        let finalized = config.is_finalized();
 
-    /// CHECK: PDA that will become the new mint authority (no data needed).
-    /// Only the key is used; seeds prove the PDA.
-    #[account(
-        seeds = [b"mint_authority"],
-        bump
-    )]
-    pub mint_authority: AccountInfo<'info>,
+        mint_or_release(
+            finalized,
+            &mut ctx.accounts.ledger,
+            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.mint.to_account_info(),
+            ctx.accounts.mint_authority.to_account_info(),
+            config.mint_authority_bump,
+            ctx.accounts.token_vault.to_account_info(),
+            config.token_vault_bump,
+            &ctx.accounts.token_vault_account,
+            ctx.accounts.recipient_token_account.to_account_info(),
+            lamports_sent,
+        )?;
 
-    /// Global singleton config PDA
-    #[account(
-        init,
+        // buyer -> sol_vault (SOL)
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.buyer.to_account_info(),
+                to: ctx.accounts.sol_vault.to_account_info(),
+            },
+        );
+        system_program::transfer(cpi_ctx, lamports_sent)?;
+        add_checked(&mut ctx.accounts.ledger.lamports_in, lamports_sent)?;
+        record_sol_vault_deposit(&config, &ctx.accounts.sol_vault, lamports_sent)?;
+
+        // Event carries post-transaction state
+        let vault_lamports = sol_vault_backing(&config, &ctx.accounts.sol_vault)?;
+        let state = post_state(
+            &mut ctx.accounts.ledger,
+            vault_lamports,
+            ctx.accounts.mint.as_ref(),
+            ctx.accounts.token_vault_account.as_ref(),
+            Some(ctx.accounts.recipient_token_account.as_ref()),
+        )?;
+        emit_buy_event(
+            &events,
+            ctx.accounts.buyer.key(),
+            ctx.accounts.recipient.key(),
+            lamports_sent,
+            lamports_sent,
+            finalized,
+            ReferralAttribution::NONE,
+            state,
+        )?;
+        Ok(())
+    }
+
+    /// Buys for several recipients at once (airdrops, contributor payouts): `amounts[i]`
+    /// lamports buy `amounts[i]` base units for the token account passed as remaining
+    /// account `i` (writable, this mint, any owner). The buyer pays the sum in one
+    /// transfer; each recipient gets its own `BuyMintEvent` / `BuyVaultEvent`, whose
+    /// state counts the legs up to and including its own.
+    pub fn batch_buy<'info>(
+        ctx: Context<'_, '_, '_, 'info, BatchBuy<'info>>,
+        amounts: Vec<u64>,
+    ) -> Result<()> {
+        let events = EventSink::new(
+            ctx.accounts.event_authority.to_account_info(),
+            ctx.bumps.event_authority,
+        );
+
+        let config = ctx.accounts.config.load()?;
+        require!(config.is_initialized(), ErrorCode::NotInitialized);
+        require!(
+            !amounts.is_empty() && amounts.len() == ctx.remaining_accounts.len(),
+            ErrorCode::BadBatchAccounts
+        );
+        check_finalize_not_pending(&config)?;
+        let finalized = config.is_finalized();
+
+        let mut total = 0u64;
+        for amount in &amounts {
+            require!(*amount > 0, ErrorCode::NoSOLSent);
+            add_checked(&mut total, *amount)?;
+        }
+        if finalized {
+            require!(
+                ctx.accounts.token_vault_account.amount >= total,
+                ErrorCode::VaultInsufficient
+            );
+        }
+
+        // buyer -> sol_vault (SOL), once for the whole batch; each event reports the vault
+        // as if the legs had been paid one by one
+        let mut vault_lamports = sol_vault_backing(&config, &ctx.accounts.sol_vault)?;
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.buyer.to_account_info(),
+                to: ctx.accounts.sol_vault.to_account_info(),
+            },
+        );
+        system_program::transfer(cpi_ctx, total)?;
+        add_checked(&mut ctx.accounts.ledger.lamports_in, total)?;
+        record_sol_vault_deposit(&config, &ctx.accounts.sol_vault, total)?;
+
+        for (recipient_token_account, amount) in ctx.remaining_accounts.iter().zip(amounts) {
+            require!(
+                recipient_token_account.is_writable,
+                ErrorCode::BadBatchAccounts
+            );
+            let recipient = {
+                let data = recipient_token_account.try_borrow_data()?;
+                let account = TokenAccount::try_deserialize(&mut &data[..])?;
+                require_keys_eq!(account.mint, config.mint, ErrorCode::WrongMint);
+                account.owner
+            };
+            require_keys_eq!(
+                *recipient_token_account.owner,
+                token::ID,
+                ErrorCode::InvalidOwner
+            );
+
+            mint_or_release(
+                finalized,
+                &mut ctx.accounts.ledger,
+                ctx.accounts.token_program.to_account_info(),
+                ctx.accounts.mint.to_account_info(),
+                ctx.accounts.mint_authority.to_account_info(),
+                config.mint_authority_bump,
+                ctx.accounts.token_vault.to_account_info(),
+                config.token_vault_bump,
+                &ctx.accounts.token_vault_account,
+                recipient_token_account.clone(),
+                amount,
+            )?;
+
+            // Each event carries the state right after its recipient's leg
+            add_checked(&mut vault_lamports, amount)?;
+            let state = post_state(
+                &mut ctx.accounts.ledger,
+                vault_lamports,
+                ctx.accounts.mint.as_ref(),
+                ctx.accounts.token_vault_account.as_ref(),
+                Some(recipient_token_account),
+            )?;
+            emit_buy_event(
+                &events,
+                ctx.accounts.buyer.key(),
+                recipient,
+                amount,
+                amount,
+                finalized,
+                ReferralAttribution::NONE,
+                state,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Same as `buy_fair_token`, but paid from the buyer's wSOL token account.
+    /// The wSOL is unwrapped inside the instruction so the SOL vault keeps holding
+    /// native lamports only:
+    ///  - per-buyer scratch wSOL account (PDA) created with its rent advanced by the SOL vault
+    ///  - wSOL buyer → scratch (`lamports_sent`)
+    ///  - scratch account closed → SOL vault (`lamports_sent` plus the advanced rent, native)
+    /// The buyer spends exactly `lamports_sent` wSOL and no native lamports (fees aside).
+    /// The vault must hold at least one token-account rent to advance (`VaultSOLInsufficient`).
+    pub fn buy_fair_token_wsol(ctx: Context<BuyFairTokenWsol>, lamports_sent: u64) -> Result<()> {
+        let events = EventSink::new(
+            ctx.accounts.event_authority.to_account_info(),
+            ctx.bumps.event_authority,
+        );
+
+        let config = ctx.accounts.config.load()?;
+        require!(lamports_sent > 0, ErrorCode::NoSOLSent);
+        require!(config.is_initialized(), ErrorCode::NotInitialized);
+        require!(
+            ctx.accounts.user_wsol_account.amount >= lamports_sent,
+            ErrorCode::InsufficientWsol
+        );
+        let scratch_rent = Rent::get()?.minimum_balance(TokenAccount::LEN);
+        require!(
+            sol_vault_backing(&config, &ctx.accounts.sol_vault)? >= scratch_rent,
+            ErrorCode::VaultSOLInsufficient
+        );
+
+        check_finalize_not_pending(&config)?;
+        let finalized = config.is_finalized();
+
+        // ---- scratch: rent advanced by the SOL vault, allocated and assigned by its PDA ----
+        pay_from_sol_vault(
+            &config,
+            ctx.accounts.system_program.to_account_info(),
+            ctx.accounts.sol_vault.to_account_info(),
+            ctx.accounts.wsol_scratch.to_account_info(),
+            scratch_rent,
+        )?;
+        let buyer_key = ctx.accounts.buyer.key();
+        let scratch_seeds: &[&[u8]] = &[
+            b"wsol_scratch",
+            buyer_key.as_ref(),
+            &[ctx.bumps.wsol_scratch],
+        ];
+        let scratch_signer: &[&[&[u8]]] = &[scratch_seeds];
+        system_program::allocate(
+            CpiContext::new_with_signer(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Allocate {
+                    account_to_allocate: ctx.accounts.wsol_scratch.to_account_info(),
+                },
+                scratch_signer,
+            ),
+            TokenAccount::LEN as u64,
+        )?;
+        system_program::assign(
+            CpiContext::new_with_signer(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Assign {
+                    account_to_assign: ctx.accounts.wsol_scratch.to_account_info(),
+                },
+                scratch_signer,
+            ),
+            &token::ID,
+        )?;
+        token::initialize_account3(CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            InitializeAccount3 {
+                account: ctx.accounts.wsol_scratch.to_account_info(),
+                mint: ctx.accounts.wsol_mint.to_account_info(),
+                authority: ctx.accounts.token_vault.to_account_info(),
+            },
+        ))?;
+
+        // ---- unwrap: user wSOL → scratch → close to the SOL vault ----
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.user_wsol_account.to_account_info(),
+                to: ctx.accounts.wsol_scratch.to_account_info(),
+                authority: ctx.accounts.buyer.to_account_info(),
+            },
+        );
+        token::transfer(cpi_ctx, lamports_sent)?;
+
+        let seeds: &[&[u8]] = &[b"token_vault", &[config.token_vault_bump]];
+        let signer: &[&[&[u8]]] = &[seeds];
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            CloseAccount {
+                account: ctx.accounts.wsol_scratch.to_account_info(),
+                destination: ctx.accounts.sol_vault.to_account_info(),
+                authority: ctx.accounts.token_vault.to_account_info(),
+            },
+            signer,
+        );
+        token::close_account(cpi_ctx)?;
+        add_checked(&mut ctx.accounts.ledger.lamports_in, lamports_sent)?;
+        // the advanced rent came back with the close
+        record_sol_vault_deposit(
+            &config,
+            &ctx.accounts.sol_vault,
+            lamports_sent
+                .checked_add(scratch_rent)
+                .ok_or(ErrorCode::MathOverflow)?,
+        )?;
+
+        mint_or_release(
+            finalized,
+            &mut ctx.accounts.ledger,
+            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.mint.to_account_info(),
+            ctx.accounts.mint_authority.to_account_info(),
+            config.mint_authority_bump,
+            ctx.accounts.token_vault.to_account_info(),
+            config.token_vault_bump,
+            &ctx.accounts.token_vault_account,
+            ctx.accounts.user_token_account.to_account_info(),
+            lamports_sent,
+        )?;
+
+        // Event carries post-transaction state
+        let vault_lamports = sol_vault_backing(&config, &ctx.accounts.sol_vault)?;
+        let state = post_state(
+            &mut ctx.accounts.ledger,
+            vault_lamports,
+            ctx.accounts.mint.as_ref(),
+            ctx.accounts.token_vault_account.as_ref(),
+            Some(ctx.accounts.user_token_account.as_ref()),
+        )?;
+        emit_buy_event(
+            &events,
+            ctx.accounts.buyer.key(),
+            ctx.accounts.buyer.key(),
+            lamports_sent,
+            lamports_sent,
+            finalized,
+            ReferralAttribution::NONE,
+            state,
+        )?;
+        Ok(())
+    }
+
+    /// Same as `redeem_fair_token`, but pays out into the redeemer's wSOL token account.
+    /// Lamports leave the SOL vault natively and are wrapped in place (`sync_native`).
+    pub fn redeem_fair_token_wsol<'info>(
+        ctx: Context<'_, '_, '_, 'info, RedeemFairTokenWsol<'info>>,
+        amount_to_redeem: u64,
+    ) -> Result<()> {
+        let events = EventSink::new(
+            ctx.accounts.event_authority.to_account_info(),
+            ctx.bumps.event_authority,
+        );
+
+        let config = ctx.accounts.config.load()?;
+        require!(config.is_initialized(), ErrorCode::NotInitialized);
+        require!(amount_to_redeem > 0, ErrorCode::ZeroTokenRedeem);
+        require!(
+            ctx.accounts.user_token_account.amount >= amount_to_redeem,
+            ErrorCode::InsufficientTokens
+        );
+        require!(
+            amount_to_redeem <= sol_vault_backing(&config, &ctx.accounts.sol_vault)?,
+            ErrorCode::VaultSOLInsufficient
+        );
+
+        let finalized = config.is_finalized();
+        // Post-finalization: serve queued buy orders first (orders in remaining accounts)
+        let queued_fill = if finalized {
+            fill_buy_orders(
+                &ctx.accounts.buy_queue,
+                ctx.remaining_accounts,
+                &mut ctx.accounts.ledger,
+                ctx.accounts.token_program.to_account_info(),
+                ctx.accounts.user_token_account.to_account_info(),
+                ctx.accounts.redeemer.to_account_info(),
+                &[],
+                ctx.accounts.sol_vault.to_account_info(),
+                ctx.accounts.redeemer.key(),
+                amount_to_redeem,
+                &events,
+            )?
+        } else {
+            0
+        };
+        add_checked(&mut ctx.accounts.ledger.total_queue_filled, queued_fill)?;
+
+        record_sol_vault_deposit(&config, &ctx.accounts.sol_vault, queued_fill)?;
+
+        // burn, or transfer back to vault after finalization (1 lamport == 1 base unit)
+        let to_vault = amount_to_redeem - queued_fill;
+        if to_vault > 0 {
+            burn_or_restock(
+                finalized,
+                config.vault_ceiling,
+                &mut ctx.accounts.ledger,
+                ctx.accounts.token_program.to_account_info(),
+                ctx.accounts.mint.to_account_info(),
+                ctx.accounts.user_token_account.to_account_info(),
+                ctx.accounts.redeemer.to_account_info(),
+                ctx.accounts.token_vault.to_account_info(),
+                config.token_vault_bump,
+                &ctx.accounts.token_vault_account,
+                to_vault,
+                &events,
+            )?;
+        }
+
+        // ---- wrap: SOL vault → user wSOL account (lamports), then sync_native ----
+        pay_from_sol_vault(
+            &config,
+            ctx.accounts.system_program.to_account_info(),
+            ctx.accounts.sol_vault.to_account_info(),
+            ctx.accounts.user_wsol_account.to_account_info(),
+            amount_to_redeem,
+        )?;
+        add_checked(&mut ctx.accounts.ledger.lamports_out, amount_to_redeem)?;
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            SyncNative {
+                account: ctx.accounts.user_wsol_account.to_account_info(),
+            },
+        );
+        token::sync_native(cpi_ctx)?;
+
+        // Event carries post-transaction state
+        let vault_lamports = sol_vault_backing(&config, &ctx.accounts.sol_vault)?;
+        let state = post_state(
+            &mut ctx.accounts.ledger,
+            vault_lamports,
+            ctx.accounts.mint.as_ref(),
+            ctx.accounts.token_vault_account.as_ref(),
+            Some(ctx.accounts.user_token_account.as_ref()),
+        )?;
+        emit_redeem_event(
+            &events,
+            ctx.accounts.redeemer.key(),
+            ctx.accounts.redeemer.key(),
+            amount_to_redeem,
+            finalized,
+            false,
+            state,
+        )?;
+        Ok(())
+    }
+
+    /// Token-backed buy (see `initialize_token_backed`).
+    ///  - Pulls `backing_sent` backing base units buyer → backing vault via `transfer_checked`.
+    ///  - Pre-finalization: mints the equivalent fair base units to the buyer.
+    ///  - Post-finalization: transfers the equivalent fair base units from the token vault.
+    pub fn buy_with_backing(ctx: Context<BuyWithBacking>, backing_sent: u64) -> Result<()> {
+        let events = EventSink::new(
+            ctx.accounts.event_authority.to_account_info(),
+            ctx.bumps.event_authority,
+        );
+
+        let config = ctx.accounts.config.load()?;
+        require!(backing_sent > 0, ErrorCode::NoBackingSent);
+        require!(config.is_initialized(), ErrorCode::NotInitialized);
+
+        check_finalize_not_pending(&config)?;
+        let finalized = config.is_finalized();
+        let amount = backing_to_fair(backing_sent, config.backing_decimals)?;
+
+        // buyer -> backing vault (backing token)
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.backing_token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.user_backing_account.to_account_info(),
+                mint: ctx.accounts.backing_mint.to_account_info(),
+                to: ctx.accounts.backing_vault.to_account_info(),
+                authority: ctx.accounts.buyer.to_account_info(),
+            },
+        );
+        token_interface::transfer_checked(
+            cpi_ctx,
+            backing_sent,
+            ctx.accounts.backing_mint.decimals,
+        )?;
+        add_checked(&mut ctx.accounts.ledger.backing_in, backing_sent)?;
+
+        mint_or_release(
+            finalized,
+            &mut ctx.accounts.ledger,
+            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.mint.to_account_info(),
+            ctx.accounts.mint_authority.to_account_info(),
+            config.mint_authority_bump,
+            ctx.accounts.token_vault.to_account_info(),
+            config.token_vault_bump,
+            &ctx.accounts.token_vault_account,
+            ctx.accounts.user_token_account.to_account_info(),
+            amount,
+        )?;
+
+        // Event carries post-transaction state
+        ctx.accounts.backing_vault.reload()?;
+        let vault_lamports = ctx.accounts.backing_vault.amount;
+        let state = post_state(
+            &mut ctx.accounts.ledger,
+            vault_lamports,
+            ctx.accounts.mint.as_ref(),
+            ctx.accounts.token_vault_account.as_ref(),
+            Some(ctx.accounts.user_token_account.as_ref()),
+        )?;
+        events.emit(BuyBackedEvent {
+            buyer: ctx.accounts.buyer.key(),
+            amount,
+            backing_amount: backing_sent,
+            finalized,
+            state,
+        })?;
+        Ok(())
+    }
+
+    /// Token-backed redeem (see `initialize_token_backed`).
+    ///  - Pre-finalization: burn `amount_to_redeem` fair base units from user.
+    ///  - Post-finalization: transfer `amount_to_redeem` fair base units from user to vault.
+    ///  - Pays the equivalent backing base units backing vault → user via `transfer_checked`.
+    pub fn redeem_for_backing(ctx: Context<RedeemForBacking>, amount_to_redeem: u64) -> Result<()> {
+        let events = EventSink::new(
+            ctx.accounts.event_authority.to_account_info(),
+            ctx.bumps.event_authority,
+        );
+
+        let config = ctx.accounts.config.load()?;
+        require!(config.is_initialized(), ErrorCode::NotInitialized);
+        require!(amount_to_redeem > 0, ErrorCode::ZeroTokenRedeem);
+        require!(
+            ctx.accounts.user_token_account.amount >= amount_to_redeem,
+            ErrorCode::InsufficientTokens
+        );
+        let backing_amount = fair_to_backing(amount_to_redeem, config.backing_decimals)?;
+        require!(
+            backing_amount <= ctx.accounts.backing_vault.amount,
+            ErrorCode::VaultBackingInsufficient
+        );
+
+        let finalized = config.is_finalized();
+
+        burn_or_restock(
+            finalized,
+            config.vault_ceiling,
+            &mut ctx.accounts.ledger,
+            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.mint.to_account_info(),
+            ctx.accounts.user_token_account.to_account_info(),
+            ctx.accounts.redeemer.to_account_info(),
+            ctx.accounts.token_vault.to_account_info(),
+            config.token_vault_bump,
+            &ctx.accounts.token_vault_account,
+            amount_to_redeem,
+            &events,
+        )?;
+
+        // backing vault -> redeemer (backing token), authorized by `token_vault` PDA seeds
+        let seeds: &[&[u8]] = &[b"token_vault", &[config.token_vault_bump]];
+        let signer: &[&[&[u8]]] = &[seeds];
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.backing_token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.backing_vault.to_account_info(),
+                mint: ctx.accounts.backing_mint.to_account_info(),
+                to: ctx.accounts.user_backing_account.to_account_info(),
+                authority: ctx.accounts.token_vault.to_account_info(),
+            },
+            signer,
+        );
+        token_interface::transfer_checked(
+            cpi_ctx,
+            backing_amount,
+            ctx.accounts.backing_mint.decimals,
+        )?;
+        add_checked(&mut ctx.accounts.ledger.backing_out, backing_amount)?;
+
+        // Event carries post-transaction state
+        ctx.accounts.backing_vault.reload()?;
+        let vault_lamports = ctx.accounts.backing_vault.amount;
+        let state = post_state(
+            &mut ctx.accounts.ledger,
+            vault_lamports,
+            ctx.accounts.mint.as_ref(),
+            ctx.accounts.token_vault_account.as_ref(),
+            Some(ctx.accounts.user_token_account.as_ref()),
+        )?;
+        events.emit(RedeemBackedEvent {
+            redeemer: ctx.accounts.redeemer.key(),
+            amount: amount_to_redeem,
+            backing_amount,
+            finalized,
+            state,
+        })?;
+        Ok(())
+    }
+
+    /// Permissionless: refresh the published `PriceFeed` (seeds `["price_feed"]`),
+    /// creating it on first use (rent paid by `payer`).
+    ///  - The redemption price is fixed (1 backing token per fair token).
+    ///  - Backing ratio and last-updated slot/time are recomputed from the vaults.
+    ///  - With vault shards open, every shard is passed as remaining accounts
+    ///    `[(sol_vault_shard, token_vault_shard)*]` in index order.
+    pub fn refresh_price<'info>(
+        ctx: Context<'_, '_, '_, 'info, RefreshPrice<'info>>,
+    ) -> Result<()> {
+        let config = ctx.accounts.config.load()?;
+        require!(config.is_initialized(), ErrorCode::NotInitialized);
+
+        let (shard_lamports, shard_tokens) = sum_vault_shards(&config, ctx.remaining_accounts)?;
+
+        let backing = reserve_backing(&config, &ctx.accounts.reserve_vault, shard_lamports)?;
+        let vault_tokens = ctx
+            .accounts
+            .token_vault_account
+            .amount
+            .checked_add(shard_tokens)
+            .ok_or(ErrorCode::MathOverflow)?;
+        let circulating = circulating_supply(&config, &ctx.accounts.mint, vault_tokens)?;
+        let backing_ratio_bps = backing_ratio_bps(backing, circulating);
+
+        let clock = Clock::get()?;
+        let feed = &mut ctx.accounts.price_feed;
+        feed.version = PriceFeed::VERSION;
+        feed.mint = config.mint;
+        feed.quote_mint = config.backing_mint;
+        feed.price = 10u64
+            .checked_pow(config.backing_decimals as u32)
+            .ok_or(ErrorCode::MathOverflow)?;
+        feed.price_decimals = config.backing_decimals;
+        feed.token_decimals = DECIMALS;
+        feed.backing_ratio_bps = backing_ratio_bps;
+        feed.backing = backing;
+        feed.circulating = circulating;
+        feed.last_updated_slot = clock.slot;
+        feed.last_updated_ts = clock.unix_timestamp;
+        Ok(())
+    }
+
+    /// Permissionless: open vault shard `index` (native SOL configs only). A shard is a
+    /// SOL vault PDA `["sol_vault", index]` plus a token account PDA
+    /// `["token_vault_account", index]` owned by the `token_vault` PDA, so buys and
+    /// redeems on different shards do not write-lock the same vault accounts.
+    /// Shards open in order, up to `MAX_VAULT_SHARDS`; `payer` pays the token account
+    /// rent and funds the SOL vault's rent reserve, which is never redeemable (see
+    /// `sol_shard_backing`).
+    pub fn open_vault_shard(ctx: Context<OpenVaultShard>, index: u8) -> Result<()> {
+        let events = EventSink::new(
+            ctx.accounts.event_authority.to_account_info(),
+            ctx.bumps.event_authority,
+        );
+
+        let mut config = ctx.accounts.config.load_mut()?;
+        require!(config.is_initialized(), ErrorCode::NotInitialized);
+        require!(
+            config.backing_mint == Pubkey::default(),
+            ErrorCode::ShardsRequireSolBacking
+        );
+        require!(
+            index == config.shard_count && index < MAX_VAULT_SHARDS,
+            ErrorCode::InvalidShard
+        );
+        config.shard_count = index + 1;
+
+        // Rent reserve: redeems and rebalances never take the shard below it
+        let reserve = Rent::get()?
+            .minimum_balance(0)
+            .saturating_sub(ctx.accounts.sol_vault_shard.lamports());
+        if reserve > 0 {
+            let cpi_ctx = CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.payer.to_account_info(),
+                    to: ctx.accounts.sol_vault_shard.to_account_info(),
+                },
+            );
+            system_program::transfer(cpi_ctx, reserve)?;
+        }
+
+        events.emit(VaultShardOpenedEvent {
+            seq: next_seq(&mut ctx.accounts.event_stream.seq)?,
+            index,
+            sol_vault: ctx.accounts.sol_vault_shard.key(),
+            token_vault_account: ctx.accounts.shard_token_account.key(),
+        })?;
+        Ok(())
+    }
+
+    /// `buy_fair_token` on vault shard `shard`: SOL goes to the shard's SOL vault and,
+    /// post-finalization, tokens come from the shard's token account. Pre-finalization
+    /// the mint is still written, so `mint` must be passed writable; post-finalization it
+    /// is only read. Fails with `FinalizationPending` once the sale has ended until
+    /// `finalize` has run.
+    pub fn buy_sharded(
+        ctx: Context<BuySharded>,
+        shard: u8,
+        lamports_sent: u64,
+    ) -> Result<BuyResult> {
+        let events = EventSink::new(
+            ctx.accounts.event_authority.to_account_info(),
+            ctx.bumps.event_authority,
+        );
+
+        let config = ctx.accounts.config.load()?;
+        require!(lamports_sent > 0, ErrorCode::NoSOLSent);
+        check_shard_usable(&config, shard)?;
+        check_finalize_not_pending(&config)?;
+        let finalized = config.is_finalized();
+
+        mint_or_release(
+            finalized,
+            &mut ctx.accounts.ledger,
+            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.mint.to_account_info(),
+            ctx.accounts.mint_authority.to_account_info(),
+            config.mint_authority_bump,
+            ctx.accounts.token_vault.to_account_info(),
+            config.token_vault_bump,
+            &ctx.accounts.shard_token_account,
+            ctx.accounts.user_token_account.to_account_info(),
+            lamports_sent,
+        )?;
+
+        // buyer -> shard SOL vault
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.buyer.to_account_info(),
+                to: ctx.accounts.sol_vault_shard.to_account_info(),
+            },
+        );
+        system_program::transfer(cpi_ctx, lamports_sent)?;
+        add_checked(&mut ctx.accounts.ledger.lamports_in, lamports_sent)?;
+
+        let vault_lamports = sol_shard_backing(&ctx.accounts.sol_vault_shard)?;
+        let state = post_state(
+            &mut ctx.accounts.ledger,
+            vault_lamports,
+            ctx.accounts.mint.as_ref(),
+            ctx.accounts.shard_token_account.as_ref(),
+            Some(ctx.accounts.user_token_account.as_ref()),
+        )?;
+        events.emit(BuyShardEvent {
+            shard,
+            buyer: ctx.accounts.buyer.key(),
+            amount: lamports_sent,
+            lamports: lamports_sent,
+            finalized,
+            state,
+        })?;
+        Ok(BuyResult {
+            path: if finalized {
+                BuyPath::Vault
+            } else {
+                BuyPath::Mint
+            },
+            amount: lamports_sent,
+            lamports: lamports_sent,
+            state,
+        })
+    }
+
+    /// `redeem_fair_token` against vault shard `shard`: SOL is paid from the shard's SOL
+    /// vault and, post-finalization, waiting buy orders passed as remaining accounts are
+    /// filled (their escrow goes to the shard's SOL vault) and the rest is restocked into
+    /// the shard's token account (`vault_ceiling` applies per vault account). Any shard with enough SOL can serve
+    /// a redemption; `rebalance_vault_shards` moves SOL where it is needed.
+    /// `mint` must be writable when the redemption burns: pre-finalization, or a restock
+    /// that pushes the shard token account above `vault_ceiling`.
+    pub fn redeem_sharded<'info>(
+        ctx: Context<'_, '_, '_, 'info, RedeemSharded<'info>>,
+        shard: u8,
+        amount_to_redeem: u64,
+    ) -> Result<RedeemResult> {
+        let events = EventSink::new(
+            ctx.accounts.event_authority.to_account_info(),
+            ctx.bumps.event_authority,
+        );
+
+        let config = ctx.accounts.config.load()?;
+        require!(amount_to_redeem > 0, ErrorCode::ZeroTokenRedeem);
+        check_shard_usable(&config, shard)?;
+        require!(
+            ctx.accounts.user_token_account.amount >= amount_to_redeem,
+            ErrorCode::InsufficientTokens
+        );
+        require!(
+            amount_to_redeem <= sol_shard_backing(&ctx.accounts.sol_vault_shard)?,
+            ErrorCode::VaultSOLInsufficient
+        );
+        let finalized = config.is_finalized();
+        // Post-finalization: serve queued buy orders first (orders in remaining accounts)
+        let queued_fill = if finalized {
+            fill_buy_orders(
+                &ctx.accounts.buy_queue,
+                ctx.remaining_accounts,
+                &mut ctx.accounts.ledger,
+                ctx.accounts.token_program.to_account_info(),
+                ctx.accounts.user_token_account.to_account_info(),
+                ctx.accounts.redeemer.to_account_info(),
+                &[],
+                ctx.accounts.sol_vault_shard.to_account_info(),
+                ctx.accounts.redeemer.key(),
+                amount_to_redeem,
+                &events,
+            )?
+        } else {
+            0
+        };
+        add_checked(&mut ctx.accounts.ledger.total_queue_filled, queued_fill)?;
+
+        // burn, or transfer back to vault after finalization (1 lamport == 1 base unit)
+        let to_vault = amount_to_redeem - queued_fill;
+        if to_vault > 0 {
+            burn_or_restock(
+                finalized,
+                config.vault_ceiling,
+                &mut ctx.accounts.ledger,
+                ctx.accounts.token_program.to_account_info(),
+                ctx.accounts.mint.to_account_info(),
+                ctx.accounts.user_token_account.to_account_info(),
+                ctx.accounts.redeemer.to_account_info(),
+                ctx.accounts.token_vault.to_account_info(),
+                config.token_vault_bump,
+                &ctx.accounts.shard_token_account,
+                to_vault,
+                &events,
+            )?;
+        }
+
+        pay_from_sol_shard(
+            ctx.accounts.system_program.to_account_info(),
+            ctx.accounts.sol_vault_shard.to_account_info(),
+            shard,
+            ctx.accounts.ledger.sol_vault_bump,
+            ctx.accounts.redeemer.to_account_info(),
+            amount_to_redeem,
+        )?;
+        add_checked(&mut ctx.accounts.ledger.lamports_out, amount_to_redeem)?;
+
+        let vault_lamports = sol_shard_backing(&ctx.accounts.sol_vault_shard)?;
+        let state = post_state(
+            &mut ctx.accounts.ledger,
+            vault_lamports,
+            ctx.accounts.mint.as_ref(),
+            ctx.accounts.shard_token_account.as_ref(),
+            Some(ctx.accounts.user_token_account.as_ref()),
+        )?;
+        events.emit(RedeemShardEvent {
+            shard,
+            redeemer: ctx.accounts.redeemer.key(),
+            amount: amount_to_redeem,
+            lamports: amount_to_redeem,
+            finalized,
+            state,
+        })?;
+        Ok(RedeemResult {
+            path: if finalized {
+                RedeemPath::Restock
+            } else {
+                RedeemPath::Burn
+            },
+            amount: amount_to_redeem,
+            lamports: amount_to_redeem,
+            queue_filled: queued_fill,
+            closed: false,
+            state,
+        })
+    }
+
+    /// Permissionless: move `lamports` of SOL and `tokens` of vault inventory from vault
+    /// `from` to vault `to` (shard index, or `PRIMARY_VAULT` for the primary SOL vault /
+    /// token vault account). Funds only ever move between program vaults.
+    pub fn rebalance_vault_shards(
+        ctx: Context<RebalanceVaultShards>,
+        from: u8,
+        to: u8,
+        lamports: u64,
+        tokens: u64,
+    ) -> Result<()> {
+        let events = EventSink::new(
+            ctx.accounts.event_authority.to_account_info(),
+            ctx.bumps.event_authority,
+        );
+
+        let config = *ctx.accounts.config.load()?;
+        require!(config.is_initialized(), ErrorCode::NotInitialized);
+        require!(
+            config.backing_mint == Pubkey::default(),
+            ErrorCode::ShardsRequireSolBacking
+        );
+        require!(from != to, ErrorCode::InvalidShard);
+        for index in [from, to] {
+            require!(
+                index == PRIMARY_VAULT || index < config.shard_count,
+                ErrorCode::InvalidShard
+            );
+        }
+        let (from_sol, from_bump) = sol_vault_address(from);
+        require_keys_eq!(
+            ctx.accounts.from_sol_vault.key(),
+            from_sol,
+            ErrorCode::BadShardAccounts
+        );
+        require_keys_eq!(
+            ctx.accounts.to_sol_vault.key(),
+            sol_vault_address(to).0,
+            ErrorCode::BadShardAccounts
+        );
+        require_keys_eq!(
+            ctx.accounts.from_token_account.key(),
+            token_vault_address(&config, from),
+            ErrorCode::BadShardAccounts
+        );
+        require_keys_eq!(
+            ctx.accounts.to_token_account.key(),
+            token_vault_address(&config, to),
+            ErrorCode::BadShardAccounts
+        );
+
+        if lamports > 0 {
+            let system = ctx.accounts.system_program.to_account_info();
+            let from_ai = ctx.accounts.from_sol_vault.to_account_info();
+            let to_ai = ctx.accounts.to_sol_vault.to_account_info();
+            if from == PRIMARY_VAULT {
+                pay_from_sol_vault(&config, system, from_ai, to_ai.clone(), lamports)?;
+            } else {
+                require!(
+                    lamports <= sol_shard_backing(&from_ai)?,
+                    ErrorCode::VaultSOLInsufficient
+                );
+                pay_from_sol_shard(system, from_ai, from, from_bump, to_ai.clone(), lamports)?;
+            }
+            if to == PRIMARY_VAULT {
+                record_sol_vault_deposit(&config, &to_ai, lamports)?;
+            }
+        }
+        if tokens > 0 {
+            require!(
+                ctx.accounts.from_token_account.amount >= tokens,
+                ErrorCode::VaultInsufficient
+            );
+            let seeds: &[&[u8]] = &[b"token_vault", &[config.token_vault_bump]];
+            let signer: &[&[&[u8]]] = &[seeds];
+            let cpi_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.from_token_account.to_account_info(),
+                    to: ctx.accounts.to_token_account.to_account_info(),
+                    authority: ctx.accounts.token_vault.to_account_info(),
+                },
+                signer,
+            );
+            token::transfer(cpi_ctx, tokens)?;
+        }
+
+        events.emit(VaultRebalancedEvent {
+            seq: next_seq(&mut ctx.accounts.event_stream.seq)?,
+            from,
+            to,
+            lamports,
+            tokens,
+        })?;
+        Ok(())
+    }
+
+    /// Permissionless aggregate solvency check across the primary vault and every shard
+    /// (remaining accounts `[(sol_vault_shard, token_vault_shard)*]` in index order):
+    /// the reserve (SOL, or the backing vault if token-backed, in fair base units) must
+    /// cover the circulating supply (`circulating_supply`: outside all vaults and the
+    /// creator escrow).
+    /// Fails with `Insolvent` otherwise; emits `SolvencyCheckedEvent` on success.
+    pub fn check_solvency<'info>(
+        ctx: Context<'_, '_, '_, 'info, CheckSolvency<'info>>,
+    ) -> Result<()> {
+        let events = EventSink::new(
+            ctx.accounts.event_authority.to_account_info(),
+            ctx.bumps.event_authority,
+        );
+
+        let config = ctx.accounts.config.load()?;
+        require!(config.is_initialized(), ErrorCode::NotInitialized);
+        let (shard_lamports, shard_tokens) = sum_vault_shards(&config, ctx.remaining_accounts)?;
+        let vault_lamports = reserve_backing(&config, &ctx.accounts.reserve_vault, shard_lamports)?;
+        let vault_tokens = ctx
+            .accounts
+            .token_vault_account
+            .amount
+            .checked_add(shard_tokens)
+            .ok_or(ErrorCode::MathOverflow)?;
+        let circulating = circulating_supply(&config, &ctx.accounts.mint, vault_tokens)?;
+        require!(vault_lamports >= circulating, ErrorCode::Insolvent);
+
+        events.emit(SolvencyCheckedEvent {
+            seq: next_seq(&mut ctx.accounts.event_stream.seq)?,
+            shard_count: config.shard_count,
+            vault_lamports,
+            vault_tokens,
+            circulating,
+            surplus_lamports: vault_lamports - circulating,
+            backing_ratio_bps: backing_ratio_bps(vault_lamports, circulating),
+            donated_lamports: config.donated_lamports,
+            creator_allocated: config.creator_allocated,
+        })?;
+        Ok(())
+    }
+
+    /// Give `lamports` to the primary SOL vault (native SOL configs only). Donations
+    /// mint nothing, so they are surplus over the 1:1 backing: recorded in
+    /// `Config.donated_lamports` and visible as a backing ratio above 1.0 in
+    /// `refresh_price` / `check_solvency`. Plain transfers to the vault also add
+    /// backing but are not recorded.
+    pub fn donate(ctx: Context<Donate>, lamports: u64) -> Result<()> {
+        let events = EventSink::new(
+            ctx.accounts.event_authority.to_account_info(),
+            ctx.bumps.event_authority,
+        );
+
+        let mut config = ctx.accounts.config.load_mut()?;
+        require!(config.is_initialized(), ErrorCode::NotInitialized);
+        require!(
+            config.backing_mint == Pubkey::default(),
+            ErrorCode::DonationRequiresSolBacking
+        );
+        require!(lamports > 0, ErrorCode::NoSOLSent);
+
+        // donor -> sol_vault (SOL)
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.donor.to_account_info(),
+                to: ctx.accounts.sol_vault.to_account_info(),
+            },
+        );
+        system_program::transfer(cpi_ctx, lamports)?;
+        record_sol_vault_deposit(&config, &ctx.accounts.sol_vault, lamports)?;
+        add_checked(&mut config.donated_lamports, lamports)?;
+
+        let vault_lamports = sol_vault_backing(&config, &ctx.accounts.sol_vault)?;
+        events.emit(DonationEvent {
+            seq: next_seq(&mut ctx.accounts.event_stream.seq)?,
+            donor: ctx.accounts.donor.key(),
+            lamports,
+            donated_lamports: config.donated_lamports,
+            vault_lamports,
+        })?;
+        Ok(())
+    }
+
+    /// Permissionless: create the `Referral` PDA `["referral", referrer]` (rent paid by
+    /// `payer`). Buys that pass it as their optional `referral` account are attributed
+    /// to `referrer`, which accrues rewards when `initialize` set referral rewards.
+    pub fn open_referral(ctx: Context<OpenReferral>) -> Result<()> {
+        let referral = &mut ctx.accounts.referral;
+        referral.referrer = ctx.accounts.referrer.key();
+        referral.buys = 0;
+        referral.lamports = 0;
+        referral.rewards_accrued = 0;
+        referral.rewards_paid = 0;
+        referral.bump = ctx.bumps.referral;
+        Ok(())
+    }
+
+    /// Referrer claims accrued rewards from the reward pool `["referral_pool"]`, a
+    /// system-owned PDA funded by plain SOL transfers (never from backing). Pays what
+    /// the pool can cover above its rent-exempt minimum; the rest stays claimable.
+    /// Claims are first come, first served: an underfunded pool pays whoever claims
+    /// first, and `NoReferralRewards` until someone refunds it.
+    pub fn claim_referral_rewards(ctx: Context<ClaimReferralRewards>) -> Result<()> {
+        let events = EventSink::new(
+            ctx.accounts.event_authority.to_account_info(),
+            ctx.bumps.event_authority,
+        );
+
+        let config = ctx.accounts.config.load()?;
+        let referral = &mut ctx.accounts.referral;
+        let owed = referral
+            .rewards_accrued
+            .checked_sub(referral.rewards_paid)
+            .ok_or(ErrorCode::MathOverflow)?;
+        let available = ctx
+            .accounts
+            .referral_pool
+            .lamports()
+            .saturating_sub(Rent::get()?.minimum_balance(0));
+        let amount = owed.min(available);
+        require!(amount > 0, ErrorCode::NoReferralRewards);
+
+        // referral pool -> referrer (SOL)
+        let seeds: &[&[u8]] = &[b"referral_pool", &[config.referral_pool_bump]];
+        let signer: &[&[&[u8]]] = &[seeds];
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.referral_pool.to_account_info(),
+                to: ctx.accounts.referrer.to_account_info(),
+            },
+            signer,
+        );
+        system_program::transfer(cpi_ctx, amount)?;
+        add_checked(&mut referral.rewards_paid, amount)?;
+
+        events.emit(ReferralRewardClaimedEvent {
+            seq: next_seq(&mut ctx.accounts.event_stream.seq)?,
+            referrer: referral.referrer,
+            amount,
+            rewards_paid: referral.rewards_paid,
+            rewards_accrued: referral.rewards_accrued,
+        })?;
+        Ok(())
+    }
+
+    /// Permissionless, at most once per `DISTRIBUTION_INTERVAL_DAYS`: snapshot the vault
+    /// surplus for pro-rata distribution to holders (native SOL configs only).
+    ///  - Surplus = all vault SOL (primary + shards, remaining accounts
+    ///    `[(sol_vault_shard, token_vault_shard)*]` in index order) minus the circulating
+    ///    supply, minus the rent-exempt minimum of a system-owned primary vault (shard
+    ///    rent reserves are never counted). Circulating is everything redeemable, released
+    ///    creator tokens included (each is backed on release); only the escrowed creator
+    ///    allocation, which cannot redeem, is left out.
+    ///  - That much SOL (capped by the primary vault) moves from the primary SOL vault
+    ///    into a new `Distribution` PDA `["distribution", epoch]`, so the 1:1 floor
+    ///    stays fully backed; `payer` pays its rent and gets it back on close.
+    ///  - The distribution crank fixed at initialize then publishes a Merkle root of the
+    ///    holders' shares (`publish_distribution_root`), computed off-chain at
+    ///    `snapshot_slot`. Without a crank (`NoDistributionCrank`) nothing is snapshot.
+    pub fn snapshot_surplus<'info>(
+        ctx: Context<'_, '_, '_, 'info, SnapshotSurplus<'info>>,
+        epoch: u64,
+    ) -> Result<()> {
+        let events = EventSink::new(
+            ctx.accounts.event_authority.to_account_info(),
+            ctx.bumps.event_authority,
+        );
+
+        let mut config = ctx.accounts.config.load_mut()?;
+        require!(config.is_initialized(), ErrorCode::NotInitialized);
+        require!(
+            config.backing_mint == Pubkey::default(),
+            ErrorCode::DistributionRequiresSolBacking
+        );
+        require!(
+            config.distribution_crank != Pubkey::default(),
+            ErrorCode::NoDistributionCrank
+        );
+        require!(
+            epoch == config.distribution_epoch,
+            ErrorCode::WrongDistributionEpoch
+        );
+        let clock = Clock::get()?;
+        if config.last_distribution_ts != 0 {
+            require!(
+                clock.unix_timestamp
+                    >= config.last_distribution_ts + 60 * 60 * 24 * DISTRIBUTION_INTERVAL_DAYS,
+                ErrorCode::DistributionTooSoon
+            );
+        }
+
+        let (shard_lamports, shard_tokens) = sum_vault_shards(&config, ctx.remaining_accounts)?;
+        let primary = sol_vault_backing(&config, &ctx.accounts.sol_vault)?;
+        let vault_tokens = ctx
+            .accounts
+            .token_vault_account
+            .amount
+            .checked_add(shard_tokens)
+            .ok_or(ErrorCode::MathOverflow)?;
+        let circulating = circulating_supply(&config, &ctx.accounts.mint, vault_tokens)?;
+
+        // A system-owned primary vault must keep its rent-exempt minimum (shard rent
+        // reserves are already left out by `sum_vault_shards`); it is not surplus.
+        let primary_rent = if config.sol_vault_is_program_owned() {
+            0
+        } else {
+            Rent::get()?.minimum_balance(0)
+        };
+        let reserved = circulating
+            .checked_add(primary_rent)
+            .ok_or(ErrorCode::MathOverflow)?;
+        let primary_spendable = primary.saturating_sub(primary_rent);
+        let lamports = primary
+            .checked_add(shard_lamports)
+            .ok_or(ErrorCode::MathOverflow)?
+            .saturating_sub(reserved)
+            .min(primary_spendable);
+        require!(lamports > 0, ErrorCode::NoSurplus);
+
+        pay_from_sol_vault(
+            &config,
+            ctx.accounts.system_program.to_account_info(),
+            ctx.accounts.sol_vault.to_account_info(),
+            ctx.accounts.distribution.to_account_info(),
+            lamports,
+        )?;
+
+        let distribution = &mut ctx.accounts.distribution;
+        distribution.epoch = epoch;
+        distribution.payer = ctx.accounts.payer.key();
+        distribution.snapshot_slot = clock.slot;
+        distribution.snapshot_ts = clock.unix_timestamp;
+        distribution.circulating = circulating;
+        distribution.lamports = lamports;
+        distribution.merkle_root = [0; 32];
+        distribution.total_claimable = 0;
+        distribution.claimed = 0;
+        distribution.publisher = Pubkey::default();
+        distribution.root_published_ts = 0;
+        distribution.bump = ctx.bumps.distribution;
+
+        config.distribution_epoch = epoch.checked_add(1).ok_or(ErrorCode::MathOverflow)?;
+        config.last_distribution_ts = clock.unix_timestamp;
+        add_checked(&mut config.distributed_lamports, lamports)?;
+
+        events.emit(SurplusSnapshotEvent {
+            seq: next_seq(&mut ctx.accounts.event_stream.seq)?,
+            epoch,
+            lamports,
+            circulating,
+            snapshot_slot: clock.slot,
+        })?;
+        Ok(())
+    }
+
+    /// Distribution crank only (`Config.distribution_crank`, fixed at initialize):
+    /// publish the Merkle root of epoch `epoch`'s claims.
+    ///  - Leaves are `sha256(0x00 || epoch_le || claimant || amount_le)`, inner nodes
+    ///    `sha256(0x01 || min(a, b) || max(a, b))` (sorted pairs, no positions needed).
+    ///  - Each holder's `amount` is `lamports * balance / circulating` at `snapshot_slot`,
+    ///    rounded down; `total_claimable` (the sum of the leaves) must not exceed the
+    ///    snapshot, so a bad root can never pay out more than the surplus.
+    ///  - Claims open `DISTRIBUTION_CHALLENGE_SECS` later. Until then any holder with
+    ///    enough stake can clear the root by escrowing a bond
+    ///    (`challenge_distribution_root`); only the crank can then publish again, so a
+    ///    wrong root only pays out if nobody objects in time.
+    ///  - Publishing after a challenge settles its bond: republishing the challenged root
+    ///    forfeits it to the SOL vault as recorded surplus (the challenge was frivolous);
+    ///    a different root refunds it to `challenger`, which must then be passed.
+    pub fn publish_distribution_root(
+        ctx: Context<PublishDistributionRoot>,
+        merkle_root: [u8; 32],
+        total_claimable: u64,
+    ) -> Result<()> {
+        let events = EventSink::new(
+            ctx.accounts.event_authority.to_account_info(),
+            ctx.bumps.event_authority,
+        );
+
+        let distribution = &mut ctx.accounts.distribution;
+        require!(
+            distribution.merkle_root == [0; 32],
+            ErrorCode::DistributionRootPublished
+        );
+        require!(merkle_root != [0; 32], ErrorCode::InvalidMerkleProof);
+        require!(
+            total_claimable <= distribution.lamports,
+            ErrorCode::DistributionExceedsSurplus
+        );
+
+        if distribution.challenger != Pubkey::default() {
+            let forfeited = merkle_root == distribution.challenged_root;
+            let mut config = ctx.accounts.config.load_mut()?;
+            let bond = if forfeited {
+                let bond = settle_challenge_bond(distribution, &ctx.accounts.sol_vault)?;
+                record_sol_vault_deposit(&config, &ctx.accounts.sol_vault, bond)?;
+                add_checked(&mut config.donated_lamports, bond)?;
+                bond
+            } else {
+                let challenger = ctx
+                    .accounts
+                    .challenger
+                    .as_ref()
+                    .ok_or(ErrorCode::WrongChallenger)?;
+                require_keys_eq!(
+                    challenger.key(),
+                    distribution.challenger,
+                    ErrorCode::WrongChallenger
+                );
+                settle_challenge_bond(distribution, challenger)?
+            };
+            events.emit(ChallengeBondSettledEvent {
+                seq: next_seq(&mut ctx.accounts.event_stream.seq)?,
+                epoch: distribution.epoch,
+                challenger: distribution.challenger,
+                bond,
+                forfeited,
+            })?;
+            distribution.challenger = Pubkey::default();
+        }
+
+        distribution.merkle_root = merkle_root;
+        distribution.total_claimable = total_claimable;
+        distribution.publisher = ctx.accounts.publisher.key();
+        distribution.root_published_ts = Clock::get()?.unix_timestamp;
+
+        events.emit(DistributionRootPublishedEvent {
+            seq: next_seq(&mut ctx.accounts.event_stream.seq)?,
+            epoch: distribution.epoch,
+            publisher: distribution.publisher,
+            merkle_root,
+            total_claimable,
+        })?;
+        Ok(())
+    }
+
+    /// Clear a published root during its challenge window, so it never pays out; the
+    /// distribution crank may then publish a corrected one. The challenger must hold at
+    /// least `DISTRIBUTION_CHALLENGE_BPS` of the snapshot's circulating supply in
+    /// `challenger_token_account` and escrows `DISTRIBUTION_CHALLENGE_BOND` lamports in
+    /// the `Distribution`. The crank's next publish settles the bond: forfeited if it
+    /// republishes the same root, refunded otherwise (or by `close_distribution` if the
+    /// crank never publishes again), so repeated challenges of a correct root cost a
+    /// bond each.
+    pub fn challenge_distribution_root(
+        ctx: Context<ChallengeDistributionRoot>,
+        epoch: u64,
+    ) -> Result<()> {
+        let events = EventSink::new(
+            ctx.accounts.event_authority.to_account_info(),
+            ctx.bumps.event_authority,
+        );
+
+        let distribution = &mut ctx.accounts.distribution;
+        require!(
+            distribution.merkle_root != [0; 32],
+            ErrorCode::DistributionRootMissing
+        );
+        require!(
+            Clock::get()?.unix_timestamp
+                < distribution.root_published_ts + DISTRIBUTION_CHALLENGE_SECS,
+            ErrorCode::DistributionChallengeClosed
+        );
+        let stake =
+            (distribution.circulating as u128 * DISTRIBUTION_CHALLENGE_BPS as u128 / 10_000) as u64;
+        require!(
+            ctx.accounts.challenger_token_account.amount >= stake.max(1),
+            ErrorCode::ChallengeStakeTooSmall
+        );
+
+        // challenger -> distribution PDA (bond, held on top of the surplus)
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.challenger.to_account_info(),
+                to: ctx.accounts.distribution.to_account_info(),
+            },
+        );
+        system_program::transfer(cpi_ctx, DISTRIBUTION_CHALLENGE_BOND)?;
+
+        let distribution = &mut ctx.accounts.distribution;
+        let merkle_root = distribution.merkle_root;
+        distribution.challenger = ctx.accounts.challenger.key();
+        distribution.challenge_bond = DISTRIBUTION_CHALLENGE_BOND;
+        distribution.challenged_root = merkle_root;
+        distribution.merkle_root = [0; 32];
+        distribution.total_claimable = 0;
+        distribution.publisher = Pubkey::default();
+        distribution.root_published_ts = 0;
+
+        events.emit(DistributionRootChallengedEvent {
+            seq: next_seq(&mut ctx.accounts.event_stream.seq)?,
+            epoch,
+            challenger: ctx.accounts.challenger.key(),
+            merkle_root,
+            bond: DISTRIBUTION_CHALLENGE_BOND,
+        })?;
+        Ok(())
+    }
+
+    /// Claim `amount` lamports of epoch `epoch`'s surplus with a Merkle `proof` of the
+    /// leaf `(epoch, claimant, amount)`, once the root's challenge window has passed.
+    /// A `DistributionClaim` PDA `["distribution_claim", epoch, claimant]` (rent paid by
+    /// the claimant) makes each leaf claimable once.
+    pub fn claim_distribution(
+        ctx: Context<ClaimDistribution>,
+        epoch: u64,
+        amount: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        let events = EventSink::new(
+            ctx.accounts.event_authority.to_account_info(),
+            ctx.bumps.event_authority,
+        );
+
+        let claimant = ctx.accounts.claimant.key();
+        let distribution = &mut ctx.accounts.distribution;
+        require!(
+            distribution.merkle_root != [0; 32],
+            ErrorCode::DistributionRootMissing
+        );
+        require!(
+            Clock::get()?.unix_timestamp
+                >= distribution.root_published_ts + DISTRIBUTION_CHALLENGE_SECS,
+            ErrorCode::DistributionChallengeOpen
+        );
+        require!(amount > 0, ErrorCode::NoSurplus);
+        require!(
+            verify_distribution_proof(distribution.merkle_root, epoch, &claimant, amount, &proof),
+            ErrorCode::InvalidMerkleProof
+        );
+        let claimed = distribution
+            .claimed
+            .checked_add(amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+        require!(
+            claimed <= distribution.total_claimable,
+            ErrorCode::DistributionExceedsSurplus
+        );
+        distribution.claimed = claimed;
+
+        // Distribution PDA (program-owned) -> claimant
+        let from = distribution.to_account_info();
+        **from.try_borrow_mut_lamports()? -= amount;
+        **ctx.accounts.claimant.try_borrow_mut_lamports()? += amount;
+
+        let receipt = &mut ctx.accounts.claim;
+        receipt.epoch = epoch;
+        receipt.claimant = claimant;
+        receipt.amount = amount;
+
+        events.emit(DistributionClaimedEvent {
+            seq: next_seq(&mut ctx.accounts.event_stream.seq)?,
+            epoch,
+            claimant,
+            amount,
+        })?;
+        Ok(())
+    }
+
+    /// Permissionless, `DISTRIBUTION_CLAIM_DAYS` after the snapshot: return the unclaimed
+    /// surplus of epoch `epoch` to the primary SOL vault and close the `Distribution`
+    /// (rent back to the snapshot payer). Claims of that epoch are no longer possible.
+    /// A challenge the crank never answered is refunded to `challenger` (required then).
+    pub fn close_distribution(ctx: Context<CloseDistribution>, epoch: u64) -> Result<()> {
+        let events = EventSink::new(
+            ctx.accounts.event_authority.to_account_info(),
+            ctx.bumps.event_authority,
+        );
+
+        let mut config = ctx.accounts.config.load_mut()?;
+        require!(
+            Clock::get()?.unix_timestamp
+                >= ctx.accounts.distribution.snapshot_ts + 60 * 60 * 24 * DISTRIBUTION_CLAIM_DAYS,
+            ErrorCode::DistributionClaimWindowOpen
+        );
+        if ctx.accounts.distribution.challenger != Pubkey::default() {
+            let challenger = ctx
+                .accounts
+                .challenger
+                .as_ref()
+                .ok_or(ErrorCode::WrongChallenger)?;
+            require_keys_eq!(
+                challenger.key(),
+                ctx.accounts.distribution.challenger,
+                ErrorCode::WrongChallenger
+            );
+            let bond = settle_challenge_bond(&mut ctx.accounts.distribution, challenger)?;
+            events.emit(ChallengeBondSettledEvent {
+                seq: next_seq(&mut ctx.accounts.event_stream.seq)?,
+                epoch,
+                challenger: challenger.key(),
+                bond,
+                forfeited: false,
+            })?;
+        }
+        let distribution = &ctx.accounts.distribution;
+        let unclaimed = distribution
+            .lamports
+            .checked_sub(distribution.claimed)
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        // Distribution PDA -> SOL vault (back to surplus); `close` refunds the rent
+        let from = distribution.to_account_info();
+        **from.try_borrow_mut_lamports()? -= unclaimed;
+        **ctx.accounts.sol_vault.try_borrow_mut_lamports()? += unclaimed;
+        record_sol_vault_deposit(&config, &ctx.accounts.sol_vault, unclaimed)?;
+        config.distributed_lamports = config
+            .distributed_lamports
+            .checked_sub(unclaimed)
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        events.emit(DistributionClosedEvent {
+            seq: next_seq(&mut ctx.accounts.event_stream.seq)?,
+            epoch,
+            claimed: distribution.claimed,
+            returned: unclaimed,
+        })?;
+        Ok(())
+    }
+
+    /// Permissionless: create the `VaultLedger` of open shard `index` (rent paid by
+    /// `payer`). Buys and redeems on a shard need its ledger, which also stores the
+    /// shard vaults' bumps; the primary vault's ledger is created by the initializers.
+    pub fn open_vault_ledger(ctx: Context<OpenVaultLedger>, index: u8) -> Result<()> {
+        let config = ctx.accounts.config.load()?;
+        require!(config.is_initialized(), ErrorCode::NotInitialized);
+        require!(index < config.shard_count, ErrorCode::InvalidShard);
+        let ledger = &mut ctx.accounts.ledger;
+        ledger.index = index;
+        ledger.bump = ctx.bumps.ledger;
+        ledger.sol_vault_bump = sol_vault_address(index).1;
+        ledger.token_account_bump =
+            Pubkey::find_program_address(&[b"token_vault_account", &[index]], &crate::ID).1;
+        Ok(())
+    }
+
+    /// Permissionless: finalize the sale once `sale_end` has passed. Mints any shortfall
+    /// to `min_supply` into the token vault and revokes the mint authority. Buys do not
+    /// auto-finalize (that would put `config` in their write set); past `sale_end` they
+    /// fail with `FinalizationPending` until this has run. Redeems are not blocked: until
+    /// then they burn, as before `sale_end`.
+    pub fn finalize(ctx: Context<Finalize>) -> Result<()> {
+        let events = EventSink::new(
+            ctx.accounts.event_authority.to_account_info(),
+            ctx.bumps.event_authority,
+        );
+
+        let mut config = ctx.accounts.config.load_mut()?;
+        require!(config.is_initialized(), ErrorCode::NotInitialized);
+        require!(!config.is_finalized(), ErrorCode::AlreadyFinalized);
+        require!(
+            Clock::get()?.unix_timestamp > config.sale_end,
+            ErrorCode::SaleNotEnded
+        );
+        let creator = match (
+            ctx.accounts.creator_vesting.as_mut(),
+            ctx.accounts.creator_escrow.as_ref(),
+        ) {
+            (Some(vesting), Some(escrow)) => Some((vesting, escrow.to_account_info())),
+            _ => None,
+        };
+        finalize_sale(
+            &mut config,
+            &mut ctx.accounts.event_stream,
+            &ctx.accounts.mint,
+            ctx.accounts.mint_authority.to_account_info(),
+            ctx.accounts.token_vault_account.to_account_info(),
+            creator,
+            ctx.accounts.token_program.to_account_info(),
+            &events,
+        )
+    }
+
+    /// Create the creator vesting state `["creator_vesting"]` and its escrow token
+    /// account `["creator_escrow"]` (authority = the vesting PDA) before `finalize`, when
+    /// `initialize` configured a creator allocation. Permissionless; `payer` pays rent.
+    pub fn open_creator_vesting(ctx: Context<OpenCreatorVesting>) -> Result<()> {
+        let config = ctx.accounts.config.load()?;
+        require!(config.is_initialized(), ErrorCode::NotInitialized);
+        require!(
+            config.creator_allocation_bps > 0,
+            ErrorCode::NoCreatorAllocation
+        );
+        require!(!config.is_finalized(), ErrorCode::AlreadyFinalized);
+
+        let vesting = &mut ctx.accounts.creator_vesting;
+        vesting.beneficiary = config.creator;
+        vesting.escrow = ctx.accounts.creator_escrow.key();
+        vesting.total = 0;
+        vesting.released = 0;
+        vesting.start_ts = 0;
+        vesting.cliff_ts = 0;
+        vesting.end_ts = 0;
+        vesting.bump = ctx.bumps.creator_vesting;
+        Ok(())
+    }
+
+    /// Release the vested part of the creator allocation from the escrow to the
+    /// beneficiary's associated token account (the only accepted destination, so any
+    /// `backer` may call it). Nothing vests before the cliff; after it,
+    /// `total * (now - start) / (end - start)`, all of it from `end_ts`.
+    ///  - Escrowed tokens were minted unbacked, so `backer` (anyone, usually the
+    ///    beneficiary) pays the released amount in lamports into the primary SOL vault:
+    ///    released tokens circulate at the same 1:1 floor as bought ones.
+    ///  - `Config.creator_allocated` drops by the released amount.
+    pub fn release_creator_vesting(ctx: Context<ReleaseCreatorVesting>) -> Result<()> {
+        let events = EventSink::new(
+            ctx.accounts.event_authority.to_account_info(),
+            ctx.bumps.event_authority,
+        );
+
+        let vesting = &mut ctx.accounts.creator_vesting;
+        require!(vesting.total > 0, ErrorCode::SaleNotFinalized);
+        let now = Clock::get()?.unix_timestamp;
+        let vested = vesting.vested_at(now);
+        let amount = vested.saturating_sub(vesting.released);
+        require!(amount > 0, ErrorCode::NothingVested);
+
+        let seeds: &[&[u8]] = &[b"creator_vesting", &[vesting.bump]];
+        let signer: &[&[&[u8]]] = &[seeds];
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.creator_escrow.to_account_info(),
+                to: ctx.accounts.beneficiary_token_account.to_account_info(),
+                authority: vesting.to_account_info(),
+            },
+            signer,
+        );
+        token::transfer(cpi_ctx, amount)?;
+        vesting.released = vested;
+
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.backer.to_account_info(),
+                to: ctx.accounts.sol_vault.to_account_info(),
+            },
+        );
+        system_program::transfer(cpi_ctx, amount)?;
+        let mut config = ctx.accounts.config.load_mut()?;
+        record_sol_vault_deposit(&config, &ctx.accounts.sol_vault, amount)?;
+        config.creator_allocated = config
+            .creator_allocated
+            .checked_sub(amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        events.emit(CreatorVestingReleasedEvent {
+            seq: next_seq(&mut ctx.accounts.event_stream.seq)?,
+            beneficiary: vesting.beneficiary,
+            backer: ctx.accounts.backer.key(),
+            amount,
+            released: vesting.released,
+            total: vesting.total,
+        })?;
+        Ok(())
+    }
+
+    /// Migrate `amount` legacy fair tokens (the audited, immutable deployment at
+    /// `LEGACY_PROGRAM_ID`) into this program in one transaction:
+    ///  - The holder deposits the legacy tokens into the `["migration"]` PDA's legacy ATA.
+    ///  - The PDA redeems them by CPI to the legacy `redeem_fair_token`, against the
+    ///    legacy `sol_vault`; the SOL lands in the PDA (checked to be exactly `amount`).
+    ///  - That SOL moves into this program's SOL vault and backs `amount` base units of
+    ///    the successor token, minted (or, post-finalization, released from the vault)
+    ///    to the holder 1:1 — the holder never exits to SOL.
+    pub fn migrate_from_legacy(ctx: Context<MigrateFromLegacy>, amount: u64) -> Result<()> {
+        let events = EventSink::new(
+            ctx.accounts.event_authority.to_account_info(),
+            ctx.bumps.event_authority,
+        );
+
+        let config = ctx.accounts.config.load()?;
+        require!(amount > 0, ErrorCode::ZeroTokenRedeem);
+        require!(config.is_initialized(), ErrorCode::NotInitialized);
+        require!(
+            config.backing_mint == Pubkey::default(),
+            ErrorCode::MigrationRequiresSolBacking
+        );
+        require_keys_neq!(
+            LEGACY_PROGRAM_ID,
+            crate::ID,
+            ErrorCode::InvalidLegacyProgram
+        );
+        check_finalize_not_pending(&config)?;
+        let finalized = config.is_finalized();
+
+        // holder -> migration PDA (legacy tokens)
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.holder_legacy_account.to_account_info(),
+                to: ctx.accounts.migration_legacy_account.to_account_info(),
+                authority: ctx.accounts.holder.to_account_info(),
+            },
+        );
+        token::transfer(cpi_ctx, amount)?;
+
+        // The migration PDA keeps a rent-exempt reserve (topped up by the holder), so a
+        // legacy redemption of any size can land in it
+        let migration = ctx.accounts.migration_authority.to_account_info();
+        let top_up = Rent::get()?
+            .minimum_balance(0)
+            .saturating_sub(migration.lamports());
+        if top_up > 0 {
+            let cpi_ctx = CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.holder.to_account_info(),
+                    to: migration.clone(),
+                },
+            );
+            system_program::transfer(cpi_ctx, top_up)?;
+        }
+
+        // migration PDA redeems them on the legacy program (SOL: legacy vault -> PDA)
+        let before = migration.lamports();
+        let seeds: &[&[u8]] = &[b"migration", &[ctx.bumps.migration_authority]];
+        let signer: &[&[&[u8]]] = &[seeds];
+        let legacy_accounts = [
+            migration.clone(),
+            ctx.accounts.legacy_mint.to_account_info(),
+            ctx.accounts.legacy_mint_authority.to_account_info(),
+            ctx.accounts.legacy_config.to_account_info(),
+            ctx.accounts.legacy_sol_vault.to_account_info(),
+            ctx.accounts.legacy_token_vault.to_account_info(),
+            ctx.accounts.legacy_token_vault_account.to_account_info(),
+            ctx.accounts.migration_legacy_account.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+        ];
+        anchor_lang::solana_program::program::invoke_signed(
+            &legacy_redeem_ix(&legacy_accounts, amount),
+            &legacy_accounts,
+            signer,
+        )?;
+        let received = migration
+            .lamports()
+            .checked_sub(before)
+            .ok_or(ErrorCode::LegacyRedeemMismatch)?;
+        require!(received == amount, ErrorCode::LegacyRedeemMismatch);
+
+        // migration PDA -> SOL vault (the redeemed SOL now backs the successor tokens)
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: migration,
+                to: ctx.accounts.sol_vault.to_account_info(),
+            },
+            signer,
+        );
+        system_program::transfer(cpi_ctx, amount)?;
+        add_checked(&mut ctx.accounts.ledger.lamports_in, amount)?;
+        record_sol_vault_deposit(&config, &ctx.accounts.sol_vault, amount)?;
+
+        // successor tokens 1:1
+        mint_or_release(
+            finalized,
+            &mut ctx.accounts.ledger,
+            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.mint.to_account_info(),
+            ctx.accounts.mint_authority.to_account_info(),
+            config.mint_authority_bump,
+            ctx.accounts.token_vault.to_account_info(),
+            config.token_vault_bump,
+            &ctx.accounts.token_vault_account,
+            ctx.accounts.user_token_account.to_account_info(),
+            amount,
+        )?;
+
+        // Event carries post-transaction state
+        let vault_lamports = sol_vault_backing(&config, &ctx.accounts.sol_vault)?;
+        let state = post_state(
+            &mut ctx.accounts.ledger,
+            vault_lamports,
+            ctx.accounts.mint.as_ref(),
+            ctx.accounts.token_vault_account.as_ref(),
+            Some(ctx.accounts.user_token_account.as_ref()),
+        )?;
+        events.emit(LegacyMigratedEvent {
+            holder: ctx.accounts.holder.key(),
+            legacy_mint: ctx.accounts.legacy_mint.key(),
+            amount,
+            finalized,
+            state,
+        })?;
+        Ok(())
+    }
+}
+
+/// `redeem_fair_token` / `redeem_fair_token_and_close`; `close_if_empty` closes an
+/// emptied `user_token_account`, rent → redeemer.
+fn redeem_primary<'info>(
+    ctx: Context<'_, '_, '_, 'info, RedeemFairToken<'info>>,
+    amount_to_redeem: u64,
+    close_if_empty: bool,
+) -> Result<RedeemResult> {
+    let events = EventSink::new(
+        ctx.accounts.event_authority.to_account_info(),
+        ctx.bumps.event_authority,
+    );
+
+    let config = ctx.accounts.config.load()?;
+    require!(config.is_initialized(), ErrorCode::NotInitialized);
+    require!(amount_to_redeem > 0, ErrorCode::ZeroTokenRedeem);
+    require!(
+        ctx.accounts.user_token_account.amount >= amount_to_redeem,
+        ErrorCode::InsufficientTokens
+    );
+    require!(
+        ctx.accounts.user_token_account.mint == ctx.accounts.mint.key(),
+        ErrorCode::InvalidMint
+    );
+    require!(
+        ctx.accounts.sol_vault.key() == config.sol_vault,
+        ErrorCode::InvalidVault
+    );
+    require!(
+        amount_to_redeem <= sol_vault_backing(&config, &ctx.accounts.sol_vault)?,
+        ErrorCode::VaultSOLInsufficient
+    );
+
+    let finalized = config.is_finalized();
+
+    // Post-finalization: serve queued buy orders first (orders in remaining accounts)
+    let queued_fill = if finalized {
+        fill_buy_orders(
+            &ctx.accounts.buy_queue,
+            ctx.remaining_accounts,
+            &mut ctx.accounts.ledger,
+            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.user_token_account.to_account_info(),
+            ctx.accounts.redeemer.to_account_info(),
+            &[],
+            ctx.accounts.sol_vault.to_account_info(),
+            ctx.accounts.redeemer.key(),
+            amount_to_redeem,
+            &events,
+        )?
+    } else {
+        0
+    };
+    add_checked(&mut ctx.accounts.ledger.total_queue_filled, queued_fill)?;
+
+    record_sol_vault_deposit(&config, &ctx.accounts.sol_vault, queued_fill)?;
+
+    // burn, or transfer back to vault after finalization (1 lamport == 1 base unit)
+    let to_vault = amount_to_redeem - queued_fill;
+    if to_vault > 0 {
+        burn_or_restock(
+            finalized,
+            config.vault_ceiling,
+            &mut ctx.accounts.ledger,
+            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.mint.to_account_info(),
+            ctx.accounts.user_token_account.to_account_info(),
+            ctx.accounts.redeemer.to_account_info(),
+            ctx.accounts.token_vault.to_account_info(),
+            config.token_vault_bump,
+            &ctx.accounts.token_vault_account,
+            to_vault,
+            &events,
+        )?;
+    }
+
+    // Full redemption: optionally close the emptied token account, rent → redeemer
+    let closed = close_if_empty && ctx.accounts.user_token_account.amount == amount_to_redeem;
+    if closed {
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            CloseAccount {
+                account: ctx.accounts.user_token_account.to_account_info(),
+                destination: ctx.accounts.redeemer.to_account_info(),
+                authority: ctx.accounts.redeemer.to_account_info(),
+            },
+        );
+        token::close_account(cpi_ctx)?;
+    }
+
+    // SOL: transfer from SOL vault PDA → redeemer, authorized by PDA seeds
+    pay_from_sol_vault(
+        &config,
+        ctx.accounts.system_program.to_account_info(),
+        ctx.accounts.sol_vault.to_account_info(),
+        ctx.accounts.redeemer.to_account_info(),
+        amount_to_redeem,
+    )?;
+    add_checked(&mut ctx.accounts.ledger.lamports_out, amount_to_redeem)?;
+
+    // Event carries post-transaction state
+    let vault_lamports = sol_vault_backing(&config, &ctx.accounts.sol_vault)?;
+    let state = post_state(
+        &mut ctx.accounts.ledger,
+        vault_lamports,
+        ctx.accounts.mint.as_ref(),
+        ctx.accounts.token_vault_account.as_ref(),
+        (!closed).then_some(ctx.accounts.user_token_account.as_ref()),
+    )?;
+    emit_redeem_event(
+        &events,
+        ctx.accounts.redeemer.key(),
+        ctx.accounts.redeemer.key(),
+        amount_to_redeem,
+        finalized,
+        closed,
+        state,
+    )?;
+    Ok(RedeemResult {
+        path: if finalized {
+            RedeemPath::Restock
+        } else {
+            RedeemPath::Burn
+        },
+        amount: amount_to_redeem,
+        lamports: amount_to_redeem,
+        queue_filled: queued_fill,
+        closed,
+        state,
+    })
+}
+
+fn finalize_sale<'info>(
+    // Finalize the initial sale period (the `finalize` instruction). Buys and redeems
+    // no longer run this, so `mint.supply` is final when we get here.
+    config: &mut Config,
+    stream: &mut EventStream,
+    mint: &Account<'info, Mint>,
+    mint_authority: AccountInfo<'info>,
+    token_vault_account: AccountInfo<'info>,
+    creator: Option<(&mut Account<'info, CreatorVesting>, AccountInfo<'info>)>,
+    token_program: AccountInfo<'info>,
+    events: &EventSink<'info>,
+) -> Result<()> {
+    if config.is_finalized() {
+        return Ok(());
+    }
+
+    // Calculate shortfall to meet min_supply.
+    let net_minted: i128 = (config.min_supply_base_units as i128) - (mint.supply as i128);
+
+    let to_be_minted: u64 = if net_minted > 0 { net_minted as u64 } else { 0 };
+
+    // PDA signer seeds
+    let signer_seeds: &[&[u8]] = &[b"mint_authority", &[config.mint_authority_bump]];
+    let signer: &[&[&[u8]]] = &[signer_seeds];
+
+    if to_be_minted > 0 {
+        // Mint shortfall into the vault (authority is the PDA)
+        let mint_to_accounts = MintTo {
+            mint: mint.to_account_info(),
+            to: token_vault_account.clone(),
+            authority: mint_authority.clone(),
+        };
+        let cpi = CpiContext::new_with_signer(token_program.clone(), mint_to_accounts, signer);
+        token::mint_to(cpi, to_be_minted)?;
+        add_checked(&mut config.total_minted_shortfall, to_be_minted)?;
+
+        events.emit(MinimumEnforcedEvent {
+            seq: next_seq(&mut stream.seq)?,
+            forced_minted: to_be_minted,
+        })?;
+    }
+
+    // Creator allocation: a share of the sale supply (what buyers hold), minted into the
+    // vesting escrow. It is unbacked, so it stays out of circulation (`creator_allocated`)
+    // until `release_creator_vesting` backs each release 1:1.
+    let mut creator_minted = 0u64;
+    if config.creator_allocation_bps > 0 {
+        let (vesting, escrow) = creator.ok_or(ErrorCode::CreatorVestingMissing)?;
+        creator_minted =
+            u64::try_from(mint.supply as u128 * config.creator_allocation_bps as u128 / 10_000)
+                .map_err(|_| ErrorCode::MathOverflow)?;
+        if creator_minted > 0 {
+            let mint_to_accounts = MintTo {
+                mint: mint.to_account_info(),
+                to: escrow,
+                authority: mint_authority.clone(),
+            };
+            let cpi = CpiContext::new_with_signer(token_program.clone(), mint_to_accounts, signer);
+            token::mint_to(cpi, creator_minted)?;
+        }
+        let now = Clock::get()?.unix_timestamp;
+        vesting.total = creator_minted;
+        vesting.start_ts = now;
+        vesting.cliff_ts = now + config.creator_cliff_secs;
+        vesting.end_ts = now + config.creator_vesting_secs;
+        config.creator_allocated = creator_minted;
+
+        events.emit(CreatorAllocationMintedEvent {
+            seq: next_seq(&mut stream.seq)?,
+            beneficiary: vesting.beneficiary,
+            amount: creator_minted,
+            cliff_ts: vesting.cliff_ts,
+            end_ts: vesting.end_ts,
+        })?;
+    }
+
+    // Revoke MintTokens authority: PDA -> None (finalize)
+    let set_auth_accounts = SetAuthority {
+        account_or_mint: mint.to_account_info(),
+        current_authority: mint_authority.clone(),
+    };
+    let cpi = CpiContext::new_with_signer(token_program, set_auth_accounts, signer);
+    token::set_authority(cpi, AuthorityType::MintTokens, None)?;
+
+    // Mark finalized and emit supply after including the shortfall minted just now.
+    // (No reload needed; we intentionally use pre-mint supply + to_be_minted.)
+    config.finalized = 1;
+    events.emit(SaleFinalizedEvent {
+        seq: next_seq(&mut stream.seq)?,
+        total_supply: mint
+            .supply
+            .saturating_add(to_be_minted)
+            .saturating_add(creator_minted),
+    })?;
+
+    Ok(())
+}
+
+/// Sends `amount` fair base units to `to`:
+///  - Pre-finalization: minted by the `mint_authority` PDA (`mint` must be writable).
+///  - Post-finalization: transferred from the token vault (authority = `token_vault` PDA).
+fn mint_or_release<'info>(
+    finalized: bool,
+    ledger: &mut Account<'info, VaultLedger>,
+    token_program: AccountInfo<'info>,
+    mint: AccountInfo<'info>,
+    mint_authority: AccountInfo<'info>,
+    mint_authority_bump: u8,
+    token_vault: AccountInfo<'info>,
+    token_vault_bump: u8,
+    token_vault_account: &Account<'info, TokenAccount>,
+    to: AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    if !finalized {
+        require!(mint.is_writable, ErrorCode::MintNotWritable);
+        let seeds: &[&[u8]] = &[b"mint_authority", &[mint_authority_bump]];
+        let signer: &[&[&[u8]]] = &[seeds];
+        let cpi_ctx = CpiContext::new_with_signer(
+            token_program,
+            MintTo {
+                mint,
+                to,
+                authority: mint_authority,
+            },
+            signer,
+        );
+        token::mint_to(cpi_ctx, amount)?;
+        add_checked(&mut ledger.total_minted_sale, amount)
+    } else {
+        require!(
+            token_vault_account.amount >= amount,
+            ErrorCode::VaultInsufficient
+        );
+        let seeds: &[&[u8]] = &[b"token_vault", &[token_vault_bump]];
+        let signer: &[&[&[u8]]] = &[seeds];
+        let cpi_ctx = CpiContext::new_with_signer(
+            token_program,
+            Transfer {
+                from: token_vault_account.to_account_info(),
+                to,
+                authority: token_vault,
+            },
+            signer,
+        );
+        token::transfer(cpi_ctx, amount)?;
+        add_checked(&mut ledger.total_bought_from_vault, amount)
+    }
+}
+
+/// Takes `amount` fair base units from `from` (`authority` must sign):
+///  - Pre-finalization: burned (counted in `total_redeemed_burn` and `total_burned`).
+///  - Post-finalization: transferred back to the token vault (`total_redeemed_to_vault`).
+///    Whatever this pushes the vault above `vault_ceiling` is burned from the vault
+///    (signed by the `token_vault` PDA).
+///  - `mint` must be writable whenever this burns (`MintNotWritable`).
+fn burn_or_restock<'info>(
+    finalized: bool,
+    vault_ceiling: u64,
+    ledger: &mut Account<'info, VaultLedger>,
+    token_program: AccountInfo<'info>,
+    mint: AccountInfo<'info>,
+    from: AccountInfo<'info>,
+    authority: AccountInfo<'info>,
+    token_vault: AccountInfo<'info>,
+    token_vault_bump: u8,
+    token_vault_account: &Account<'info, TokenAccount>,
+    amount: u64,
+    events: &EventSink<'info>,
+) -> Result<()> {
+    if !finalized {
+        require!(mint.is_writable, ErrorCode::MintNotWritable);
+        let cpi_ctx = CpiContext::new(
+            token_program,
+            Burn {
+                mint,
+                from,
+                authority,
+            },
+        );
+        token::burn(cpi_ctx, amount)?;
+        add_checked(&mut ledger.total_redeemed_burn, amount)?;
+        add_checked(&mut ledger.total_burned, amount)?;
+    } else {
+        let cpi_ctx = CpiContext::new(
+            token_program.clone(),
+            Transfer {
+                from,
+                to: token_vault_account.to_account_info(),
+                authority,
+            },
+        );
+        token::transfer(cpi_ctx, amount)?;
+        add_checked(&mut ledger.total_redeemed_to_vault, amount)?;
+
+        // Supply contraction: burn only what this restock adds above the ceiling
+        let vault_after = token_vault_account.amount.saturating_add(amount);
+        let excess = vault_after.saturating_sub(vault_ceiling).min(amount);
+        if excess > 0 {
+            require!(mint.is_writable, ErrorCode::MintNotWritable);
+            let seeds: &[&[u8]] = &[b"token_vault", &[token_vault_bump]];
+            let signer: &[&[&[u8]]] = &[seeds];
+            let cpi_ctx = CpiContext::new_with_signer(
+                token_program,
+                Burn {
+                    mint,
+                    from: token_vault_account.to_account_info(),
+                    authority: token_vault,
+                },
+                signer,
+            );
+            token::burn(cpi_ctx, excess)?;
+            add_checked(&mut ledger.total_burned, excess)?;
+            events.emit(SupplyContractedEvent {
+                seq: next_seq(&mut ledger.event_seq)?,
+                vault: ledger.index,
+                burned: excess,
+                vault_amount: vault_after - excess,
+            })?;
+        }
+    }
+    Ok(())
+}
+
+/// SOL vault PDA → `to`. A system-owned vault pays by System Program CPI signed with
+/// the vault's PDA seeds; a program-owned vault is debited directly and its
+/// `backing_lamports` header is reduced by the same amount.
+fn pay_from_sol_vault<'info>(
+    config: &Config,
+    system_program: AccountInfo<'info>,
+    sol_vault: AccountInfo<'info>,
+    to: AccountInfo<'info>,
+    lamports: u64,
+) -> Result<()> {
+    if config.sol_vault_is_program_owned() {
+        let mut header = SolVault::try_deserialize(&mut sol_vault.try_borrow_data()?.as_ref())?;
+        header.backing_lamports = header
+            .backing_lamports
+            .checked_sub(lamports)
+            .ok_or(ErrorCode::VaultSOLInsufficient)?;
+        header.try_serialize(&mut sol_vault.try_borrow_mut_data()?.as_mut())?;
+        **sol_vault.try_borrow_mut_lamports()? -= lamports;
+        **to.try_borrow_mut_lamports()? += lamports;
+        return Ok(());
+    }
+
+    let seeds_arr: [&[u8]; 2] = [b"sol_vault", &[config.sol_vault_bump]];
+    let signer_seeds: &[&[u8]] = &seeds_arr;
+    let signers: &[&[&[u8]]] = &[signer_seeds];
+
+    let cpi_ctx = CpiContext::new_with_signer(
+        system_program,
+        system_program::Transfer {
+            from: sol_vault,
+            to,
+        },
+        signers,
+    );
+    system_program::transfer(cpi_ctx, lamports)
+}
+
+/// Anchor discriminator of the legacy `redeem_fair_token` (`sha256("global:redeem_fair_token")[..8]`).
+const LEGACY_REDEEM_DISCRIMINATOR: [u8; 8] = [107, 34, 33, 61, 119, 151, 25, 138];
+
+/// Legacy `redeem_fair_token(amount_to_redeem)` instruction. The legacy program is
+/// immutable, so its account list is fixed: redeemer, mint, mint_authority, config,
+/// sol_vault, token_vault, token_vault_account, user_token_account, token_program,
+/// system_program (all validated by the legacy program itself).
+fn legacy_redeem_ix(
+    accounts: &[AccountInfo; 10],
+    amount: u64,
+) -> anchor_lang::solana_program::instruction::Instruction {
+    use anchor_lang::solana_program::instruction::AccountMeta;
+    let [redeemer, mint, mint_authority, config, sol_vault, token_vault, token_vault_account, user_token_account, token_program, system_program] =
+        accounts;
+    let mut data = LEGACY_REDEEM_DISCRIMINATOR.to_vec();
+    data.extend_from_slice(&amount.to_le_bytes());
+    anchor_lang::solana_program::instruction::Instruction {
+        program_id: LEGACY_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(redeemer.key(), true),
+            AccountMeta::new(mint.key(), false),
+            AccountMeta::new_readonly(mint_authority.key(), false),
+            AccountMeta::new(config.key(), false),
+            AccountMeta::new(sol_vault.key(), false),
+            AccountMeta::new(token_vault.key(), false),
+            AccountMeta::new(token_vault_account.key(), false),
+            AccountMeta::new(user_token_account.key(), false),
+            AccountMeta::new_readonly(token_program.key(), false),
+            AccountMeta::new_readonly(system_program.key(), false),
+        ],
+        data,
+    }
+}
+
+/// Checks a `publish_distribution_root` proof (leaf and node encoding documented there).
+fn verify_distribution_proof(
+    root: [u8; 32],
+    epoch: u64,
+    claimant: &Pubkey,
+    amount: u64,
+    proof: &[[u8; 32]],
+) -> bool {
+    use anchor_lang::solana_program::hash::hashv;
+    let mut node = hashv(&[
+        &[0u8],
+        &epoch.to_le_bytes(),
+        claimant.as_ref(),
+        &amount.to_le_bytes(),
+    ])
+    .to_bytes();
+    for sibling in proof {
+        let (a, b) = if node <= *sibling {
+            (&node, sibling)
+        } else {
+            (sibling, &node)
+        };
+        node = hashv(&[&[1u8], a, b]).to_bytes();
+    }
+    node == root
+}
+
+/// Backing / circulating in basis points (10_000 == 1.0); `u64::MAX` if nothing circulates.
+/// Redeemable supply: mint supply outside the vault token accounts (`vault_tokens`,
+/// primary plus shards) and outside the creator escrow (`creator_allocated`). The one
+/// definition of "circulating" for solvency, price feed and surplus snapshots.
+fn circulating_supply(config: &Config, mint: &Mint, vault_tokens: u64) -> Result<u64> {
+    mint.supply
+        .checked_sub(vault_tokens)
+        .and_then(|c| c.checked_sub(config.creator_allocated))
+        .ok_or_else(|| ErrorCode::MathOverflow.into())
+}
+
+fn backing_ratio_bps(backing: u64, circulating: u64) -> u64 {
+    if circulating == 0 {
+        u64::MAX
+    } else {
+        u64::try_from(backing as u128 * 10_000 / circulating as u128).unwrap_or(u64::MAX)
+    }
+}
+
+/// Backing held by the primary SOL vault: all of its lamports if system-owned, the
+/// header's `backing_lamports` (rent excluded) if program-owned.
+fn sol_vault_backing(config: &Config, sol_vault: &AccountInfo) -> Result<u64> {
+    if !config.sol_vault_is_program_owned() {
+        return Ok(sol_vault.lamports());
+    }
+    let header = SolVault::try_deserialize(&mut sol_vault.try_borrow_data()?.as_ref())?;
+    Ok(header.backing_lamports)
+}
+
+/// Backing held by the reserve vault plus `shard_lamports`, valued in fair base units:
+/// the SOL vault's backing if native, the backing vault balance (floored to whole fair
+/// base units) if token-backed.
+fn reserve_backing(
+    config: &Config,
+    reserve_vault: &AccountInfo,
+    shard_lamports: u64,
+) -> Result<u64> {
+    if config.backing_mint == Pubkey::default() {
+        return sol_vault_backing(config, reserve_vault)?
+            .checked_add(shard_lamports)
+            .ok_or_else(|| ErrorCode::MathOverflow.into());
+    }
+    let data = reserve_vault.try_borrow_data()?;
+    let vault = InterfaceTokenAccount::try_deserialize(&mut &data[..])?;
+    backing_to_fair_floor(vault.amount, config.backing_decimals)
+}
+
+/// Records `lamports` just credited to the primary SOL vault in its `backing_lamports`
+/// header (program-owned vault only; a system-owned vault's lamports are the record).
+fn record_sol_vault_deposit(config: &Config, sol_vault: &AccountInfo, lamports: u64) -> Result<()> {
+    if !config.sol_vault_is_program_owned() || lamports == 0 {
+        return Ok(());
+    }
+    let mut header = SolVault::try_deserialize(&mut sol_vault.try_borrow_data()?.as_ref())?;
+    add_checked(&mut header.backing_lamports, lamports)?;
+    header.try_serialize(&mut sol_vault.try_borrow_mut_data()?.as_mut())
+}
+
+/// Same as `pay_from_sol_vault`, for SOL vault shard `index` (seeds `["sol_vault", index]`).
+fn pay_from_sol_shard<'info>(
+    system_program: AccountInfo<'info>,
+    sol_vault_shard: AccountInfo<'info>,
+    index: u8,
+    bump: u8,
+    to: AccountInfo<'info>,
+    lamports: u64,
+) -> Result<()> {
+    let seeds: &[&[u8]] = &[b"sol_vault", &[index], &[bump]];
+    let signer: &[&[&[u8]]] = &[seeds];
+    let cpi_ctx = CpiContext::new_with_signer(
+        system_program,
+        system_program::Transfer {
+            from: sol_vault_shard,
+            to,
+        },
+        signer,
+    );
+    system_program::transfer(cpi_ctx, lamports)
+}
+
+/// Backing held by a SOL vault shard: its lamports above the rent reserve funded by
+/// `open_vault_shard`, which keeps the system-owned shard rent-exempt.
+fn sol_shard_backing(sol_vault_shard: &AccountInfo) -> Result<u64> {
+    Ok(sol_vault_shard
+        .lamports()
+        .saturating_sub(Rent::get()?.minimum_balance(0)))
+}
+
+/// SOL vault address and bump for a vault index (`PRIMARY_VAULT` or a shard).
+fn sol_vault_address(index: u8) -> (Pubkey, u8) {
+    if index == PRIMARY_VAULT {
+        Pubkey::find_program_address(&[b"sol_vault"], &crate::ID)
+    } else {
+        Pubkey::find_program_address(&[b"sol_vault", &[index]], &crate::ID)
+    }
+}
+
+/// Token vault account for a vault index (`PRIMARY_VAULT` or a shard).
+fn token_vault_address(config: &Config, index: u8) -> Pubkey {
+    if index == PRIMARY_VAULT {
+        config.token_vault_account
+    } else {
+        Pubkey::find_program_address(&[b"token_vault_account", &[index]], &crate::ID).0
+    }
+}
+
+/// Shard buys/redeems: the shard must be open.
+fn check_shard_usable(config: &Config, shard: u8) -> Result<()> {
+    require!(config.is_initialized(), ErrorCode::NotInitialized);
+    require!(shard < config.shard_count, ErrorCode::InvalidShard);
+    Ok(())
+}
+
+/// Sums SOL backing (rent reserves excluded) and token inventory over all open shards,
+/// passed as `[(sol_vault_shard, token_vault_shard)*]` in index order.
+fn sum_vault_shards(config: &Config, shards: &[AccountInfo]) -> Result<(u64, u64)> {
+    require!(
+        shards.len() == 2 * config.shard_count as usize,
+        ErrorCode::BadShardAccounts
+    );
+    let (mut lamports, mut tokens) = (0u64, 0u64);
+    for (index, pair) in shards.chunks(2).enumerate() {
+        let index = index as u8;
+        require_keys_eq!(
+            pair[0].key(),
+            sol_vault_address(index).0,
+            ErrorCode::BadShardAccounts
+        );
+        require_keys_eq!(
+            pair[1].key(),
+            token_vault_address(config, index),
+            ErrorCode::BadShardAccounts
+        );
+        let token_account = TokenAccount::try_deserialize(&mut &pair[1].try_borrow_data()?[..])?;
+        add_checked(&mut lamports, sol_shard_backing(&pair[0])?)?;
+        add_checked(&mut tokens, token_account.amount)?;
+    }
+    Ok((lamports, tokens))
+}
+
+fn emit_buy_event(
+    events: &EventSink,
+    buyer: Pubkey,
+    recipient: Pubkey,
+    amount: u64,
+    requested: u64,
+    finalized: bool,
+    referral: ReferralAttribution,
+    state: PostState,
+) -> Result<()> {
+    if !finalized {
+        events.emit(BuyMintEvent {
+            buyer,
+            recipient,
+            amount,
+            lamports: amount, // amount == lamports (1:1 base units)
+            referrer: referral.referrer,
+            referral_reward: referral.reward,
+            state,
+        })
+    } else {
+        events.emit(BuyVaultEvent {
+            buyer,
+            recipient,
+            amount,
+            lamports: amount, // amount == lamports (1:1 base units)
+            requested,
+            finalized,
+            referrer: referral.referrer,
+            referral_reward: referral.reward,
+            state,
+        })
+    }
+}
+
+/// Referral data of a buy, carried by `BuyMintEvent` / `BuyVaultEvent`.
+#[derive(Clone, Copy)]
+struct ReferralAttribution {
+    referrer: Pubkey, // Pubkey::default() == no referrer
+    reward: u64,      // reward accrued by this buy
+}
+impl ReferralAttribution {
+    const NONE: Self = Self {
+        referrer: Pubkey::new_from_array([0; 32]),
+        reward: 0,
+    };
+}
+
+/// Records a referred buy of `lamports` on the referrer's `Referral` PDA and accrues
+/// its reward: `referral_reward_bps` of the purchase, up to `referral_reward_cap` per
+/// referrer over its lifetime. Rewards are claimed later from the reward pool.
+///  - The self-referral check compares keys only: a buyer referring itself from a
+///    second wallet passes it. That is a rebate of at most `referral_reward_cap` per
+///    referrer PDA, paid from the pool, never from backing.
+///  - Accrual does not look at the pool: rewards accrue up to the cap even when the
+///    pool cannot pay them yet (see `claim_referral_rewards`).
+fn accrue_referral(
+    config: &Config,
+    referral: Option<&mut Account<Referral>>,
+    buyer: Pubkey,
+    lamports: u64,
+) -> Result<ReferralAttribution> {
+    let Some(referral) = referral else {
+        return Ok(ReferralAttribution::NONE);
+    };
+    require_keys_neq!(referral.referrer, buyer, ErrorCode::SelfReferral);
+
+    let reward = u64::try_from(lamports as u128 * config.referral_reward_bps as u128 / 10_000)
+        .map_err(|_| ErrorCode::MathOverflow)?
+        .min(
+            config
+                .referral_reward_cap
+                .saturating_sub(referral.rewards_accrued),
+        );
+    add_checked(&mut referral.buys, 1)?;
+    add_checked(&mut referral.lamports, lamports)?;
+    add_checked(&mut referral.rewards_accrued, reward)?;
+    Ok(ReferralAttribution {
+        referrer: referral.referrer,
+        reward,
+    })
+}
+
+fn emit_redeem_event(
+    events: &EventSink,
+    redeemer: Pubkey,
+    recipient: Pubkey,
+    amount: u64,
+    finalized: bool,
+    closed: bool,
+    state: PostState,
+) -> Result<()> {
+    if !finalized {
+        events.emit(RedeemBurnEvent {
+            redeemer,
+            recipient,
+            amount,
+            lamports: amount, // amount == lamports (1:1 base units)
+            closed,
+            state,
+        })
+    } else {
+        events.emit(RedeemVaultEvent {
+            redeemer,
+            recipient,
+            amount,
+            lamports: amount, // amount == lamports (1:1 base units)
+            closed,
+            state,
+        })
+    }
+}
+
+/// Serves queued buy orders with up to `amount` tokens of a post-finalization redemption.
+/// `queue_ai` is the `["buy_queue"]` PDA (empty until the first order); `orders` =
+/// [(buy_order, recipient_token_account, buyer)*] in FIFO order starting at
+/// `BuyQueue.head`. Cancelled or fully filled orders are skipped (pass any accounts
+/// after them). An order whose recipient can no longer receive tokens (closed or frozen)
+/// would jam the head, so it is refunded to its buyer (escrow and rent) and skipped.
+/// Each fill moves tokens `from` → order recipient (`authority` signs, with
+/// `signer_seeds` if it is a PDA) and the same lamports order escrow → SOL vault, so
+/// backing stays 1:1.
+/// With orders passed, `queue_ai` must be writable (`BuyQueueNotWritable`); with none,
+/// nothing is filled and the queue is not touched.
+/// Returns the amount filled, which the caller counts in its ledger (redeems restock
+/// the rest into the vault).
+fn fill_buy_orders<'info>(
+    queue_ai: &AccountInfo<'info>,
+    orders: &[AccountInfo<'info>],
+    ledger: &mut Account<'info, VaultLedger>,
+    token_program: AccountInfo<'info>,
+    from: AccountInfo<'info>,
+    authority: AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+    sol_vault: AccountInfo<'info>,
+    redeemer: Pubkey,
+    amount: u64,
+    events: &EventSink<'info>,
+) -> Result<u64> {
+    require!(orders.len() % 3 == 0, ErrorCode::BadQueueAccounts);
+    if orders.is_empty() || queue_ai.data_is_empty() {
+        return Ok(0);
+    }
+    require!(queue_ai.is_writable, ErrorCode::BuyQueueNotWritable);
+    let mut queue = BuyQueue::try_deserialize(&mut queue_ai.try_borrow_data()?.as_ref())?;
+
+    let mut left = amount;
+    let mut id = queue.head;
+    for triple in orders.chunks(3) {
+        if left == 0 || id >= queue.tail {
+            break;
+        }
+        let (order_ai, recipient_ai, buyer_ai) = (&triple[0], &triple[1], &triple[2]);
+        let (order_key, _) =
+            Pubkey::find_program_address(&[b"buy_order", &id.to_le_bytes()], &crate::ID);
+        require_keys_eq!(order_ai.key(), order_key, ErrorCode::BadQueueAccounts);
+
+        // Cancelled (closed) order: skip
+        if order_ai.owner != &crate::ID || order_ai.data_is_empty() {
+            id += 1;
+            continue;
+        }
+        let mut order = BuyOrder::try_deserialize(&mut order_ai.try_borrow_data()?.as_ref())?;
+        if order.remaining == 0 {
+            id += 1;
+            continue;
+        }
+        require_keys_eq!(
+            recipient_ai.key(),
+            order.recipient_token_account,
+            ErrorCode::BadQueueAccounts
+        );
+
+        // Recipient closed or frozen: refund the order instead of blocking the queue
+        if !can_receive_tokens(recipient_ai) {
+            require_keys_eq!(buyer_ai.key(), order.buyer, ErrorCode::BadQueueAccounts);
+            close_program_account(order_ai, buyer_ai)?;
+            queue.escrowed_lamports = queue.escrowed_lamports.saturating_sub(order.remaining);
+            queue.open_orders = queue.open_orders.saturating_sub(1);
+            events.emit(BuyOrderCancelledEvent {
+                seq: next_seq(&mut queue.event_seq)?,
+                id,
+                buyer: order.buyer,
+                refunded: order.remaining,
+            })?;
+            id += 1;
+            continue;
+        }
+
+        let fill = left.min(order.remaining);
+        let cpi_ctx = CpiContext::new_with_signer(
+            token_program.clone(),
+            Transfer {
+                from: from.clone(),
+                to: recipient_ai.clone(),
+                authority: authority.clone(),
+            },
+            signer_seeds,
+        );
+        token::transfer(cpi_ctx, fill)?;
+
+        // escrow -> SOL vault (program-owned order PDA, direct lamport debit)
+        **order_ai.try_borrow_mut_lamports()? -= fill;
+        **sol_vault.try_borrow_mut_lamports()? += fill;
+        add_checked(&mut ledger.lamports_in, fill)?;
+
+        order.remaining -= fill;
+        order.try_serialize(&mut order_ai.try_borrow_mut_data()?.as_mut())?;
+        queue.escrowed_lamports = queue.escrowed_lamports.saturating_sub(fill);
+        left -= fill;
+
+        events.emit(BuyOrderFilledEvent {
+            seq: next_seq(&mut queue.event_seq)?,
+            id,
+            buyer: order.buyer,
+            redeemer,
+            amount: fill,
+            remaining: order.remaining,
+        })?;
+        if order.remaining == 0 {
+            queue.open_orders = queue.open_orders.saturating_sub(1);
+            id += 1;
+        }
+    }
+
+    queue.head = id;
+    queue.try_serialize(&mut queue_ai.try_borrow_mut_data()?.as_mut())?;
+    Ok(amount - left)
+}
+
+/// Whether `account` is a live, unfrozen SPL token account (a queued order's recipient
+/// may have been closed or frozen since the order was placed).
+fn can_receive_tokens(account: &AccountInfo) -> bool {
+    if account.owner != &token::ID {
+        return false;
+    }
+    let Ok(data) = account.try_borrow_data() else {
+        return false;
+    };
+    matches!(TokenAccount::try_deserialize(&mut data.as_ref()), Ok(acc) if !acc.is_frozen())
+}
+
+/// Moves the pending challenge bond out of `distribution` to `to` (SOL vault when
+/// forfeited, else the challenger) and returns it. The caller clears `challenger`.
+fn settle_challenge_bond<'info>(
+    distribution: &mut Account<'info, Distribution>,
+    to: &AccountInfo<'info>,
+) -> Result<u64> {
+    let bond = distribution.challenge_bond;
+    **distribution.to_account_info().try_borrow_mut_lamports()? -= bond;
+    **to.try_borrow_mut_lamports()? += bond;
+    distribution.challenge_bond = 0;
+    distribution.challenged_root = [0; 32];
+    Ok(bond)
+}
+
+/// Closes a program-owned account: all lamports → `destination`, data released.
+fn close_program_account<'info>(
+    account: &AccountInfo<'info>,
+    destination: &AccountInfo<'info>,
+) -> Result<()> {
+    let lamports = account.lamports();
+    **account.try_borrow_mut_lamports()? = 0;
+    **destination.try_borrow_mut_lamports()? += lamports;
+    account.assign(&system_program::ID);
+    account.realloc(0, false)?;
+    Ok(())
+}
+
+/// Post-transaction snapshot for buy/redeem events. CPIs don't refresh the deserialized
+/// token accounts, so balances are read straight from the account data (no `reload`),
+/// and takes the next event sequence number.
+fn post_state<'info>(
+    ledger: &mut VaultLedger,
+    vault_lamports: u64,
+    mint: &AccountInfo<'info>,
+    token_vault_account: &AccountInfo<'info>,
+    user_token_account: Option<&AccountInfo<'info>>,
+) -> Result<PostState> {
+    let user_balance = match user_token_account {
+        Some(account) => token::accessor::amount(account)?,
+        None => 0, // closed in this instruction
+    };
+    let clock = Clock::get()?;
+    Ok(PostState {
+        vault: ledger.index,
+        seq: next_seq(&mut ledger.event_seq)?,
+        slot: clock.slot,
+        unix_timestamp: clock.unix_timestamp,
+        vault_lamports,
+        token_vault_amount: token::accessor::amount(token_vault_account)?,
+        mint_supply: mint_supply(mint)?,
+        user_balance,
+    })
+}
+
+/// `Mint::supply` read from the account data (bytes 36..44: after the `COption` mint
+/// authority), like `token::accessor::amount` does for token accounts.
+fn mint_supply(mint: &AccountInfo) -> Result<u64> {
+    let data = mint.try_borrow_data()?;
+    let mut supply_bytes = [0u8; 8];
+    supply_bytes.copy_from_slice(&data[36..44]);
+    Ok(u64::from_le_bytes(supply_bytes))
+}
+
+/// Next number of an event sequence (`EventStream`, a `VaultLedger` or the `BuyQueue`;
+/// gap detection for indexers).
+fn next_seq(event_seq: &mut u64) -> Result<u64> {
+    let seq = *event_seq;
+    *event_seq = seq.checked_add(1).ok_or(ErrorCode::MathOverflow)?;
+    Ok(seq)
+}
+
+/// Buys (and migrations) no longer auto-finalize (that would write `config`): once the
+/// sale has ended, they wait for the permissionless `finalize`. Redeems never wait; until
+/// `finalize` runs they take the pre-finalization (burn) path.
+fn check_finalize_not_pending(config: &Config) -> Result<()> {
+    require!(
+        config.is_finalized() || Clock::get()?.unix_timestamp <= config.sale_end,
+        ErrorCode::FinalizationPending
+    );
+    Ok(())
+}
+
+/// Lifetime counter update; overflow is an error, never a silent wrap or clamp.
+fn add_checked(counter: &mut u64, amount: u64) -> Result<()> {
+    *counter = counter.checked_add(amount).ok_or(ErrorCode::MathOverflow)?;
+    Ok(())
+}
+
+/// Backing of a new sale: the only thing the initializers do differently.
+enum VaultKind {
+    /// `initialize`: system-owned SOL vault PDA
+    SystemSol { sol_vault: Pubkey, bump: u8 },
+    /// `initialize_program_vault`: program-owned SOL vault PDA with a `SolVault` header
+    ProgramSol { sol_vault: Pubkey, bump: u8 },
+    /// `initialize_token_backed`: SPL backing mint and its vault token account
+    Token {
+        backing_mint: Pubkey,
+        backing_vault: Pubkey,
+        bump: u8,
+        decimals: u8,
+    },
+}
+
+/// Accounts and bumps shared by the initializers.
+struct SaleInit<'a, 'info> {
+    admin: &'a Signer<'info>,
+    mint: &'a mut Account<'info, Mint>,
+    mint_authority: Pubkey,
+    mint_authority_bump: u8,
+    config: &'a AccountLoader<'info, Config>,
+    config_bump: u8,
+    token_vault: Pubkey,
+    token_vault_bump: u8,
+    token_vault_account: Pubkey,
+    metadata: &'a AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+    event_stream: &'a mut Account<'info, EventStream>,
+    event_stream_bump: u8,
+    ledger: &'a mut Account<'info, VaultLedger>,
+    ledger_bump: u8,
+    events: EventSink<'info>,
+}
+
+/// Body of every initializer: validates the sale parameters, hands the mint authority
+/// to the PDA, checks the metadata lock, writes `config` for the given vault kind,
+/// sets up the event stream and primary ledger, and emits `InitializedEvent`.
+fn init_sale(
+    accounts: SaleInit,
+    kind: VaultKind,
+    sale_end: i64,
+    vault_ceiling: Option<u64>,
+    creator_allocation: Option<CreatorAllocation>,
+    referral_rewards: Option<ReferralRewards>,
+    distribution_crank: Option<Pubkey>,
+) -> Result<()> {
+    let min_supply_base_units = check_sale_params(sale_end)?;
+    if let VaultKind::Token { decimals, .. } = kind {
+        require!(
+            10u64
+                .checked_pow(decimals.abs_diff(DECIMALS) as u32)
+                .is_some(),
+            ErrorCode::BackingDecimalsUnsupported
+        );
+    }
+
+    // ---- one-time init guard ----
+    let mut config = accounts.config.load_init()?;
+    require!(!config.is_initialized(), ErrorCode::AlreadyInitialized);
+
+    handoff_mint_authority(
+        accounts.mint,
+        accounts.admin.to_account_info(),
+        accounts.mint_authority,
+        accounts.token_program,
+    )?;
+    check_metadata_locked(accounts.mint.key(), accounts.metadata)?;
+
+    // ---- persist config ----
+    config.initialized = 1;
+    config.finalized = 0;
+    config.mint = accounts.mint.key();
+    config.token_vault = accounts.token_vault;
+    config.token_vault_account = accounts.token_vault_account;
+    config.sale_end = sale_end;
+    config.min_supply_base_units = min_supply_base_units;
+    config.vault_ceiling = vault_ceiling.unwrap_or(u64::MAX);
+    set_referral_rewards(&mut config, referral_rewards)?;
+    match kind {
+        // Native SOL backing: no backing mint / backing vault.
+        VaultKind::SystemSol { sol_vault, bump } | VaultKind::ProgramSol { sol_vault, bump } => {
+            config.sol_vault = sol_vault;
+            config.sol_vault_bump = bump;
+            config.sol_vault_program_owned = matches!(kind, VaultKind::ProgramSol { .. }) as u8;
+            config.backing_mint = Pubkey::default();
+            config.backing_vault = Pubkey::default();
+            config.backing_decimals = DECIMALS;
+        }
+        // No SOL vault; `config.sol_vault` stays default so the SOL paths reject.
+        VaultKind::Token {
+            backing_mint,
+            backing_vault,
+            bump,
+            decimals,
+        } => {
+            config.sol_vault = Pubkey::default();
+            config.backing_mint = backing_mint;
+            config.backing_vault = backing_vault;
+            config.backing_vault_bump = bump;
+            config.backing_decimals = decimals;
+        }
+    }
+    set_creator_allocation(&mut config, creator_allocation)?;
+    if let Some(crank) = distribution_crank {
+        require!(
+            config.backing_mint == Pubkey::default(),
+            ErrorCode::DistributionRequiresSolBacking
+        );
+        config.distribution_crank = crank;
+    }
+    config.config_bump = accounts.config_bump;
+    config.mint_authority_bump = accounts.mint_authority_bump;
+    config.token_vault_bump = accounts.token_vault_bump;
+    config.buy_queue_bump = Pubkey::find_program_address(&[b"buy_queue"], &crate::ID).1;
+    accounts.event_stream.bump = accounts.event_stream_bump;
+    accounts.ledger.index = PRIMARY_VAULT;
+    accounts.ledger.bump = accounts.ledger_bump;
+
+    accounts.events.emit(InitializedEvent {
+        seq: next_seq(&mut accounts.event_stream.seq)?,
+        admin: accounts.admin.key(),
+        mint: config.mint,
+        sol_vault: config.sol_vault,
+        token_vault: config.token_vault,
+        backing_mint: config.backing_mint,
+        sale_end,
+        creator: config.creator,
+        creator_allocation_bps: config.creator_allocation_bps,
+        referral_reward_bps: config.referral_reward_bps,
+        referral_reward_cap: config.referral_reward_cap,
+        distribution_crank: config.distribution_crank,
+    })
+}
+
+/// Validates `sale_end` against [MIN_WINDOW, MAX_WINDOW] days from now and returns
+/// the minimum supply in base units.
+fn check_sale_params(sale_end: i64) -> Result<u64> {
+    // ---- time window checks ----
+    let now = Clock::get()?.unix_timestamp;
+    let max_sale_end = now + 60 * 60 * 24 * MAX_WINDOW;
+    let min_sale_end = now + 60 * 60 * 24 * MIN_WINDOW;
+    require!(sale_end <= max_sale_end, ErrorCode::SaleEndNotInRange);
+    require!(sale_end >= min_sale_end, ErrorCode::SaleEndNotInRange);
+
+    // ---- constants / overflow guard ----
+    let base_units_per_token: u64 = 10u64.pow(DECIMALS as u32);
+    require!(
+        MIN_SUPPLY_TOKENS <= u64::MAX / base_units_per_token,
+        ErrorCode::MinSupplyTooLarge
+    );
+    Ok(MIN_SUPPLY_TOKENS * base_units_per_token)
+}
+
+/// Validates and records the optional creator allocation fixed at initialize. Releases
+/// are backed in SOL, so the config must be native SOL (backing kind already set).
+fn set_creator_allocation(
+    config: &mut Config,
+    creator_allocation: Option<CreatorAllocation>,
+) -> Result<()> {
+    let Some(alloc) = creator_allocation else {
+        return Ok(());
+    };
+    require!(
+        config.backing_mint == Pubkey::default(),
+        ErrorCode::CreatorAllocationRequiresSolBacking
+    );
+    require!(
+        alloc.bps > 0 && alloc.bps <= MAX_CREATOR_ALLOCATION_BPS,
+        ErrorCode::InvalidCreatorAllocation
+    );
+    require!(
+        alloc.beneficiary != Pubkey::default(),
+        ErrorCode::InvalidCreatorAllocation
+    );
+    require!(
+        alloc.cliff_secs >= 0 && alloc.vesting_secs > 0 && alloc.cliff_secs <= alloc.vesting_secs,
+        ErrorCode::InvalidCreatorAllocation
+    );
+    config.creator = alloc.beneficiary;
+    config.creator_allocation_bps = alloc.bps;
+    config.creator_cliff_secs = alloc.cliff_secs;
+    config.creator_vesting_secs = alloc.vesting_secs;
+    Ok(())
+}
+
+/// Validates and records the optional referral reward terms fixed at initialize.
+fn set_referral_rewards(
+    config: &mut Config,
+    referral_rewards: Option<ReferralRewards>,
+) -> Result<()> {
+    config.referral_pool_bump = Pubkey::find_program_address(&[b"referral_pool"], &crate::ID).1;
+    let Some(rewards) = referral_rewards else {
+        return Ok(());
+    };
+    require!(
+        rewards.reward_bps > 0 && rewards.reward_bps <= MAX_REFERRAL_REWARD_BPS,
+        ErrorCode::InvalidReferralRewards
+    );
+    require!(
+        rewards.max_reward_per_referrer > 0,
+        ErrorCode::InvalidReferralRewards
+    );
+    config.referral_reward_bps = rewards.reward_bps;
+    config.referral_reward_cap = rewards.max_reward_per_referrer;
+    Ok(())
+}
+
+/// SPL Mint authority handoff (admin -> PDA).
+/// (Preconditions are enforced by the caller's account constraints.)
+fn handoff_mint_authority<'info>(
+    mint: &mut Account<'info, Mint>,
+    admin: AccountInfo<'info>,
+    pda: Pubkey,
+    token_program: AccountInfo<'info>,
+) -> Result<()> {
+    let cpi_ctx = CpiContext::new(
+        token_program,
+        anchor_spl::token::SetAuthority {
+            account_or_mint: mint.to_account_info(),
+            current_authority: admin,
+        },
+    );
+    token::set_authority(cpi_ctx, AuthorityType::MintTokens, Some(pda))?;
+
+    // Confirm authority actually changed
+    mint.reload()?;
+    require!(
+        matches!(mint.mint_authority, COption::Some(x) if x == pda),
+        ErrorCode::WrongMintAuthority
+    );
+    Ok(())
+}
+
+/// Metaplex metadata immutability check (off-chain lock enforced here).
+fn check_metadata_locked(mint: Pubkey, metadata: &AccountInfo) -> Result<()> {
+    let (expected_meta_pda, _) = Pubkey::find_program_address(
+        &[b"metadata", TOKEN_METADATA_ID.as_ref(), mint.as_ref()],
+        &TOKEN_METADATA_ID,
+    );
+    require_keys_eq!(expected_meta_pda, metadata.key(), ErrorCode::BadMetadataPda);
+    // Redundant with #[account(owner = TOKEN_METADATA_ID)], but explicit for auditors:
+    require_keys_eq!(
+        metadata.owner.key(),
+        TOKEN_METADATA_ID,
+        ErrorCode::BadMetadataOwner
+    );
+
+    let data = metadata.try_borrow_data()?;
+    let meta =
+        MetadataAccount::deserialize(&mut data.as_ref()).map_err(|_| ErrorCode::BadMetadataData)?;
+    // Enforce that the off-chain script already revoked update authority
+    require!(
+        meta.update_authority == system_program::ID,
+        ErrorCode::MetadataStillMutable
+    );
+    Ok(())
+}
+
+/// Backing base units → fair token base units (1 backing token == 1 fair token).
+/// Fails rather than rounds when the amount has no exact representation.
+fn backing_to_fair(backing_amount: u64, backing_decimals: u8) -> Result<u64> {
+    let factor = 10u64
+        .checked_pow(backing_decimals.abs_diff(DECIMALS) as u32)
+        .ok_or(ErrorCode::MathOverflow)?;
+    if backing_decimals <= DECIMALS {
+        Ok(backing_amount
+            .checked_mul(factor)
+            .ok_or(ErrorCode::MathOverflow)?)
+    } else {
+        require!(
+            backing_amount % factor == 0,
+            ErrorCode::AmountNotRepresentable
+        );
+        Ok(backing_amount / factor)
+    }
+}
+
+/// Like `backing_to_fair`, but rounds down instead of failing (valuation only).
+fn backing_to_fair_floor(backing_amount: u64, backing_decimals: u8) -> Result<u64> {
+    if backing_decimals <= DECIMALS {
+        return backing_to_fair(backing_amount, backing_decimals);
+    }
+    let factor = 10u64
+        .checked_pow((backing_decimals - DECIMALS) as u32)
+        .ok_or(ErrorCode::MathOverflow)?;
+    Ok(backing_amount / factor)
+}
+
+/// Fair token base units → backing base units (inverse of `backing_to_fair`).
+fn fair_to_backing(amount: u64, backing_decimals: u8) -> Result<u64> {
+    let factor = 10u64
+        .checked_pow(backing_decimals.abs_diff(DECIMALS) as u32)
+        .ok_or(ErrorCode::MathOverflow)?;
+    if backing_decimals <= DECIMALS {
+        require!(amount % factor == 0, ErrorCode::AmountNotRepresentable);
+        Ok(amount / factor)
+    } else {
+        Ok(amount.checked_mul(factor).ok_or(ErrorCode::MathOverflow)?)
+    }
+}
+
+/// Self-CPI event emission: events are written as an inner instruction to this program
+/// (signed by the `#[event_cpi]` event authority PDA), so indexers can decode them even
+/// when logs are truncated or dropped. Same encoding as `emit_cpi!`, which needs `ctx`
+/// in scope; the sink lets helpers emit too. The `log-events` feature additionally logs
+/// every event with `emit!` for log-based consumers.
+#[derive(Clone)]
+pub struct EventSink<'info> {
+    authority: AccountInfo<'info>,
+    bump: u8,
+}
+
+impl<'info> EventSink<'info> {
+    pub fn new(authority: AccountInfo<'info>, bump: u8) -> Self {
+        Self { authority, bump }
+    }
+
+    pub fn emit<E: anchor_lang::Event>(&self, event: E) -> Result<()> {
+        #[cfg(feature = "log-events")]
+        emit!(event);
+
+        let ix_data: Vec<u8> = anchor_lang::event::EVENT_IX_TAG_LE
+            .iter()
+            .copied()
+            .chain(event.data())
+            .collect();
+        let ix = anchor_lang::solana_program::instruction::Instruction::new_with_bytes(
+            crate::ID,
+            &ix_data,
+            vec![AccountMeta::new_readonly(self.authority.key(), true)],
+        );
+        anchor_lang::solana_program::program::invoke_signed(
+            &ix,
+            &[self.authority.clone()],
+            &[&[b"__event_authority", &[self.bump]]],
+        )
+        .map_err(Into::into)
+    }
+}
+
+// ------------------------- Accounts -------------------------
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    /// Admin signer (hard-gated; remove `address = ADMIN` if you don't want gating)
+    #[account(mut, address = ADMIN)]
+    pub admin: Signer<'info>,
+
+    /// Pre-created SPL mint (vanity). Must be: decimals=DECIMALS, freeze=None, supply=0, mint_authority=admin.
+    /// We will handoff authority to the PDA inside this instruction.
+    #[account(
+        mut,
+        constraint = mint.decimals == DECIMALS                       @ ErrorCode::WrongDecimals,
+        constraint = mint.freeze_authority.is_none()                 @ ErrorCode::FreezeNotRevoked,
+        constraint = mint.supply == 0                                @ ErrorCode::NonZeroInitialSupply,
+        constraint = mint.mint_authority == Some(admin.key()).into() @ ErrorCode::MintAuthorityMustBeAdmin,
+    )]
+    pub mint: Account<'info, Mint>,
+
+    /// CHECK: PDA that will become the new mint authority (no data needed).
+    /// Only the key is used; seeds prove the PDA.
+    #[account(
+        seeds = [b"mint_authority"],
+        bump
+    )]
+    pub mint_authority: AccountInfo<'info>,
+
+    /// Global singleton config PDA
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + Config::SIZE,
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: AccountLoader<'info, Config>,
+
+    /// Config-wide event sequence (`EventStream`)
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + EventStream::SIZE,
+        seeds = [b"event_stream"],
+        bump
+    )]
+    pub event_stream: Account<'info, EventStream>,
+
+    /// Primary vault ledger (`open_vault_ledger` only opens shard ledgers)
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + VaultLedger::SIZE,
+        seeds = [b"vault_ledger".as_ref(), &[PRIMARY_VAULT]],
+        bump
+    )]
+    pub ledger: Account<'info, VaultLedger>,
+
+    /// CHECK: System-owned, zero-data PDA used as a SOL vault.
+    /// Using AccountInfo to avoid Anchor's SystemAccount "try_from_unchecked" quirk during init.
+    #[account(
+        init,
+        payer = admin,
+        space = 0,
+        owner = system_program::ID,
+        seeds = [b"sol_vault"],
+        bump
+    )]
+    pub sol_vault: AccountInfo<'info>,
+
+    /// Program-owned marker PDA for token vault state
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + TokenVault::SIZE,
+        seeds = [b"token_vault"],
+        bump
+    )]
+    pub token_vault: Account<'info, TokenVault>,
+
+    /// SPL token account controlled by `token_vault` PDA
+    #[account(
+        init,
+        payer = admin,
+        token::mint = mint,
+        token::authority = token_vault
+    )]
+    pub token_vault_account: Account<'info, TokenAccount>,
+
+    // ───────────── Metaplex Token Metadata (auditor-friendly) ─────────────
+    /// CHECK: Must be the real Token Metadata program ID, validated by address constraint
+    #[account(address = TOKEN_METADATA_ID)]
+    pub token_metadata_program: AccountInfo<'info>,
+
+    /// CHECK: Metadata PDA for mint, validated by owner constraint and re-derived at runtime
+    #[account(mut, owner = TOKEN_METADATA_ID)]
+    pub metadata: AccountInfo<'info>,
+
+    // Canonical programs
+    #[account(address = token::ID)]
+    pub token_program: Program<'info, Token>,
+    #[account(address = system_program::ID)]
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct InitializeProgramVault<'info> {
+    /// Admin signer (hard-gated; remove `address = ADMIN` if you don't want gating)
+    #[account(mut, address = ADMIN)]
+    pub admin: Signer<'info>,
+
+    /// Pre-created SPL mint (vanity). Must be: decimals=DECIMALS, freeze=None, supply=0, mint_authority=admin.
+    /// We will handoff authority to the PDA inside this instruction.
+    #[account(
+        mut,
+        constraint = mint.decimals == DECIMALS                       @ ErrorCode::WrongDecimals,
+        constraint = mint.freeze_authority.is_none()                 @ ErrorCode::FreezeNotRevoked,
+        constraint = mint.supply == 0                                @ ErrorCode::NonZeroInitialSupply,
+        constraint = mint.mint_authority == Some(admin.key()).into() @ ErrorCode::MintAuthorityMustBeAdmin,
+    )]
+    pub mint: Account<'info, Mint>,
+
+    /// CHECK: PDA that will become the new mint authority (no data needed).
+    /// Only the key is used; seeds prove the PDA.
+    #[account(
+        seeds = [b"mint_authority"],
+        bump
+    )]
+    pub mint_authority: AccountInfo<'info>,
+
+    /// Global singleton config PDA
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + Config::SIZE,
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: AccountLoader<'info, Config>,
+
+    /// Config-wide event sequence (`EventStream`)
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + EventStream::SIZE,
+        seeds = [b"event_stream"],
+        bump
+    )]
+    pub event_stream: Account<'info, EventStream>,
+
+    /// Primary vault ledger (`open_vault_ledger` only opens shard ledgers)
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + VaultLedger::SIZE,
+        seeds = [b"vault_ledger".as_ref(), &[PRIMARY_VAULT]],
+        bump
+    )]
+    pub ledger: Account<'info, VaultLedger>,
+
+    /// Program-owned SOL vault with a `SolVault` header (backing recorded apart from rent)
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + SolVault::SIZE,
+        seeds = [b"sol_vault"],
+        bump
+    )]
+    pub sol_vault: Account<'info, SolVault>,
+
+    /// Program-owned marker PDA for token vault state
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + TokenVault::SIZE,
+        seeds = [b"token_vault"],
+        bump
+    )]
+    pub token_vault: Account<'info, TokenVault>,
+
+    /// SPL token account controlled by `token_vault` PDA
+    #[account(
+        init,
+        payer = admin,
+        token::mint = mint,
+        token::authority = token_vault
+    )]
+    pub token_vault_account: Account<'info, TokenAccount>,
+
+    // ───────────── Metaplex Token Metadata (auditor-friendly) ─────────────
+    /// CHECK: Must be the real Token Metadata program ID, validated by address constraint
+    #[account(address = TOKEN_METADATA_ID)]
+    pub token_metadata_program: AccountInfo<'info>,
+
+    /// CHECK: Metadata PDA for mint, validated by owner constraint and re-derived at runtime
+    #[account(mut, owner = TOKEN_METADATA_ID)]
+    pub metadata: AccountInfo<'info>,
+
+    // Canonical programs
+    #[account(address = token::ID)]
+    pub token_program: Program<'info, Token>,
+    #[account(address = system_program::ID)]
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct BuyFairToken<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(mut)]
+    pub mint: Account<'info, Mint>,
+
+    /// CHECK: PDA signer derived from static seed; no deserialization needed
+    #[account(seeds = [b"mint_authority"], bump = config.load()?.mint_authority_bump)]
+    pub mint_authority: AccountInfo<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.load()?.config_bump,
+        has_one = mint @ ErrorCode::WrongMint,
+        has_one = sol_vault @ ErrorCode::WrongSolVault,
+        has_one = token_vault @ ErrorCode::WrongTokenVault,
+        has_one = token_vault_account @ ErrorCode::WrongTokenVaultAccount,
+    )]
+    pub config: AccountLoader<'info, Config>,
+
+    #[account(mut, seeds = [b"vault_ledger".as_ref(), &[PRIMARY_VAULT]], bump = ledger.bump)]
+    pub ledger: Account<'info, VaultLedger>,
+
+    /// CHECK: SOL vault is a PDA used to hold SOL payments.
+    /// Pinned by config `has_one = sol_vault`; system-owned (signs with the stored
+    /// `sol_vault_bump`) or program-owned (`SolVault` header), per config.
+    #[account(mut)]
+    pub sol_vault: UncheckedAccount<'info>,
+
+    /// CHECK: pinned by config `has_one = token_vault`; signs with the stored `token_vault_bump`.
+    #[account(mut)]
+    pub token_vault: UncheckedAccount<'info>,
+
+    /// Pinned by config `has_one = token_vault_account` (mint and owner set at init).
+    #[account(mut)]
+    pub token_vault_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = user_token_account.mint == mint.key(),
+        constraint = user_token_account.owner == buyer.key(),
+    )]
+    pub user_token_account: Account<'info, TokenAccount>,
+
+    #[account(address = token::ID)]
+    pub token_program: Program<'info, Token>,
+    #[account(address = system_program::ID)]
+    pub system_program: Program<'info, System>,
+
+    /// Optional referrer attribution (`open_referral`); accrues the referral reward.
+    #[account(mut, seeds = [b"referral", referral.referrer.as_ref()], bump = referral.bump)]
+    pub referral: Option<Account<'info, Referral>>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RedeemFairToken<'info> {
+    #[account(mut)]
+    pub redeemer: Signer<'info>,
+
+    #[account(mut)]
+    pub mint: Account<'info, Mint>,
+
+    /// CHECK: PDA signer derived from static seed; no deserialization needed
+    #[account(seeds = [b"mint_authority"], bump = config.load()?.mint_authority_bump)]
+    pub mint_authority: AccountInfo<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.load()?.config_bump,
+        has_one = mint @ ErrorCode::WrongMint,
+        has_one = sol_vault @ ErrorCode::WrongSolVault,
+        has_one = token_vault @ ErrorCode::WrongTokenVault,
+        has_one = token_vault_account @ ErrorCode::WrongTokenVaultAccount,
+    )]
+    pub config: AccountLoader<'info, Config>,
+
+    #[account(mut, seeds = [b"vault_ledger".as_ref(), &[PRIMARY_VAULT]], bump = ledger.bump)]
+    pub ledger: Account<'info, VaultLedger>,
+
+    /// CHECK: `["buy_queue"]` PDA, empty until the first order; readonly unless
+    /// waiting orders are passed to fill (see `fill_buy_orders`).
+    #[account(seeds = [b"buy_queue"], bump = config.load()?.buy_queue_bump)]
+    pub buy_queue: UncheckedAccount<'info>,
+
+    /// CHECK: SOL vault is a PDA used to hold SOL payments.
+    /// Pinned by config `has_one = sol_vault`; system-owned (signs with the stored
+    /// `sol_vault_bump`) or program-owned (`SolVault` header), per config.
+    #[account(mut)]
+    pub sol_vault: UncheckedAccount<'info>,
+
+    /// CHECK: pinned by config `has_one = token_vault`; signs with the stored `token_vault_bump`.
+    #[account(mut)]
+    pub token_vault: UncheckedAccount<'info>,
+
+    /// Pinned by config `has_one = token_vault_account` (mint and owner set at init).
+    #[account(mut)]
+    pub token_vault_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = user_token_account.mint == mint.key(),
+        constraint = user_token_account.owner == redeemer.key(),
+    )]
+    pub user_token_account: Account<'info, TokenAccount>,
+
+    #[account(address = token::ID)]
+    pub token_program: Program<'info, Token>,
+    #[account(address = system_program::ID)]
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RedeemTo<'info> {
+    #[account(mut)]
+    pub redeemer: Signer<'info>,
+
+    /// CHECK: Any writable account receiving the SOL (cold wallet, escrow, PDA)
+    #[account(mut)]
+    pub recipient: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub mint: Account<'info, Mint>,
+
+    /// CHECK: PDA signer derived from static seed; no deserialization needed
+    #[account(seeds = [b"mint_authority"], bump = config.load()?.mint_authority_bump)]
+    pub mint_authority: AccountInfo<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.load()?.config_bump,
+        has_one = mint @ ErrorCode::WrongMint,
+        has_one = sol_vault @ ErrorCode::WrongSolVault,
+        has_one = token_vault @ ErrorCode::WrongTokenVault,
+        has_one = token_vault_account @ ErrorCode::WrongTokenVaultAccount,
+    )]
+    pub config: AccountLoader<'info, Config>,
+
+    #[account(mut, seeds = [b"vault_ledger".as_ref(), &[PRIMARY_VAULT]], bump = ledger.bump)]
+    pub ledger: Account<'info, VaultLedger>,
+
+    /// CHECK: `["buy_queue"]` PDA, empty until the first order; readonly unless
+    /// waiting orders are passed to fill (see `fill_buy_orders`).
+    #[account(seeds = [b"buy_queue"], bump = config.load()?.buy_queue_bump)]
+    pub buy_queue: UncheckedAccount<'info>,
+
+    /// CHECK: SOL vault is a PDA used to hold SOL payments.
+    /// Pinned by config `has_one = sol_vault`; system-owned (signs with the stored
+    /// `sol_vault_bump`) or program-owned (`SolVault` header), per config.
+    #[account(mut)]
+    pub sol_vault: UncheckedAccount<'info>,
+
+    /// CHECK: pinned by config `has_one = token_vault`; signs with the stored `token_vault_bump`.
+    #[account(mut)]
+    pub token_vault: UncheckedAccount<'info>,
+
+    /// Pinned by config `has_one = token_vault_account` (mint and owner set at init).
+    #[account(mut)]
+    pub token_vault_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = user_token_account.mint == mint.key(),
+        constraint = user_token_account.owner == redeemer.key(),
+    )]
+    pub user_token_account: Account<'info, TokenAccount>,
+
+    #[account(address = token::ID)]
+    pub token_program: Program<'info, Token>,
+    #[account(address = system_program::ID)]
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetRedeemDestination<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + RedeemDestination::SIZE,
+        seeds = [b"redeem_destination", owner.key().as_ref()],
+        bump
+    )]
+    pub redeem_destination: Account<'info, RedeemDestination>,
+
+    #[account(address = system_program::ID)]
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RedeemDelegated<'info> {
+    /// SPL delegate of `user_token_account`
+    pub delegate: Signer<'info>,
+
+    /// CHECK: Receives the SOL; validated at runtime against the owner's destination record
+    #[account(mut)]
+    pub destination: AccountInfo<'info>,
+
+    /// CHECK: Owner's destination record PDA. Always required (so an existing record
+    /// can't be skipped); may be uninitialized if the owner never set one.
+    #[account(
+        seeds = [b"redeem_destination", user_token_account.owner.as_ref()],
+        bump
+    )]
+    pub redeem_destination: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub mint: Account<'info, Mint>,
+
+    /// CHECK: PDA signer derived from static seed; no deserialization needed
+    #[account(seeds = [b"mint_authority"], bump = config.load()?.mint_authority_bump)]
+    pub mint_authority: AccountInfo<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.load()?.config_bump,
+        has_one = mint @ ErrorCode::WrongMint,
+        has_one = sol_vault @ ErrorCode::WrongSolVault,
+        has_one = token_vault @ ErrorCode::WrongTokenVault,
+        has_one = token_vault_account @ ErrorCode::WrongTokenVaultAccount,
+    )]
+    pub config: AccountLoader<'info, Config>,
+
+    #[account(mut, seeds = [b"vault_ledger".as_ref(), &[PRIMARY_VAULT]], bump = ledger.bump)]
+    pub ledger: Account<'info, VaultLedger>,
+
+    /// CHECK: `["buy_queue"]` PDA, empty until the first order; readonly unless
+    /// waiting orders are passed to fill (see `fill_buy_orders`).
+    #[account(seeds = [b"buy_queue"], bump = config.load()?.buy_queue_bump)]
+    pub buy_queue: UncheckedAccount<'info>,
+
+    /// CHECK: SOL vault is a PDA used to hold SOL payments.
+    /// Pinned by config `has_one = sol_vault`; system-owned (signs with the stored
+    /// `sol_vault_bump`) or program-owned (`SolVault` header), per config.
+    #[account(mut)]
+    pub sol_vault: UncheckedAccount<'info>,
+
+    /// CHECK: pinned by config `has_one = token_vault`; signs with the stored `token_vault_bump`.
+    #[account(mut)]
+    pub token_vault: UncheckedAccount<'info>,
+
+    /// Pinned by config `has_one = token_vault_account` (mint and owner set at init).
+    #[account(mut)]
+    pub token_vault_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = user_token_account.mint == mint.key(),
+        constraint = user_token_account.delegate == COption::Some(delegate.key()) @ ErrorCode::NotDelegate,
+    )]
+    pub user_token_account: Account<'info, TokenAccount>,
+
+    #[account(address = token::ID)]
+    pub token_program: Program<'info, Token>,
+    #[account(address = system_program::ID)]
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct PlaceBuyOrder<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.load()?.config_bump, has_one = mint @ ErrorCode::WrongMint)]
+    pub config: AccountLoader<'info, Config>,
+
+    pub mint: Account<'info, Mint>,
+
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = 8 + BuyQueue::SIZE,
+        seeds = [b"buy_queue"],
+        bump
+    )]
+    pub buy_queue: Account<'info, BuyQueue>,
+
+    #[account(
+        init,
+        payer = buyer,
+        space = 8 + BuyOrder::SIZE,
+        seeds = [b"buy_order", buy_queue.tail.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub buy_order: Account<'info, BuyOrder>,
+
+    /// Token account the order is filled into
+    #[account(
+        constraint = user_token_account.mint == mint.key(),
+        constraint = user_token_account.owner == buyer.key(),
+    )]
+    pub user_token_account: Account<'info, TokenAccount>,
+
+    #[account(address = system_program::ID)]
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CancelBuyOrder<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.load()?.config_bump)]
+    pub config: AccountLoader<'info, Config>,
+
+    #[account(mut, seeds = [b"buy_queue"], bump)]
+    pub buy_queue: Account<'info, BuyQueue>,
+
+    #[account(
+        mut,
+        close = buyer,
+        has_one = buyer @ ErrorCode::NotOrderOwner,
+        seeds = [b"buy_order", buy_order.id.to_le_bytes().as_ref()],
+        bump = buy_order.bump
+    )]
+    pub buy_order: Account<'info, BuyOrder>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct FillBuyOrdersFromVault<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.load()?.config_bump,
+        has_one = sol_vault @ ErrorCode::WrongSolVault,
+        has_one = token_vault @ ErrorCode::WrongTokenVault,
+        has_one = token_vault_account @ ErrorCode::WrongTokenVaultAccount,
+    )]
+    pub config: AccountLoader<'info, Config>,
+
+    #[account(mut, seeds = [b"vault_ledger".as_ref(), &[PRIMARY_VAULT]], bump = ledger.bump)]
+    pub ledger: Account<'info, VaultLedger>,
+
+    /// CHECK: `["buy_queue"]` PDA; the orders to fill follow as remaining accounts
+    /// (see `fill_buy_orders`).
+    #[account(mut, seeds = [b"buy_queue"], bump = config.load()?.buy_queue_bump)]
+    pub buy_queue: UncheckedAccount<'info>,
+
+    /// CHECK: pinned by config `has_one = sol_vault`; receives the orders' escrow.
+    #[account(mut)]
+    pub sol_vault: UncheckedAccount<'info>,
+
+    /// CHECK: pinned by config `has_one = token_vault`; signs with the stored `token_vault_bump`.
+    pub token_vault: UncheckedAccount<'info>,
+
+    /// Pinned by config `has_one = token_vault_account` (mint and owner set at init).
+    #[account(mut)]
+    pub token_vault_account: Account<'info, TokenAccount>,
+
+    #[account(address = token::ID)]
+    pub token_program: Program<'info, Token>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct BuyFor<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    /// CHECK: Any wallet or PDA; only used as the ATA authority and in events
+    pub recipient: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub mint: Account<'info, Mint>,
+
+    /// CHECK: PDA signer derived from static seed; no deserialization needed
+    #[account(seeds = [b"mint_authority"], bump = config.load()?.mint_authority_bump)]
+    pub mint_authority: AccountInfo<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.load()?.config_bump,
+        has_one = mint @ ErrorCode::WrongMint,
+        has_one = sol_vault @ ErrorCode::WrongSolVault,
+        has_one = token_vault @ ErrorCode::WrongTokenVault,
+        has_one = token_vault_account @ ErrorCode::WrongTokenVaultAccount,
+    )]
+    pub config: AccountLoader<'info, Config>,
+
+    #[account(mut, seeds = [b"vault_ledger".as_ref(), &[PRIMARY_VAULT]], bump = ledger.bump)]
+    pub ledger: Account<'info, VaultLedger>,
+
+    /// CHECK: SOL vault is a PDA used to hold SOL payments.
+    /// Pinned by config `has_one = sol_vault`; system-owned (signs with the stored
+    /// `sol_vault_bump`) or program-owned (`SolVault` header), per config.
+    #[account(mut)]
+    pub sol_vault: UncheckedAccount<'info>,
+
+    /// CHECK: pinned by config `has_one = token_vault`; signs with the stored `token_vault_bump`.
+    #[account(mut)]
+    pub token_vault: UncheckedAccount<'info>,
+
+    /// Pinned by config `has_one = token_vault_account` (mint and owner set at init).
+    #[account(mut)]
+    pub token_vault_account: Account<'info, TokenAccount>,
+
+    /// Recipient's ATA, created (rent paid by buyer) if it doesn't exist yet
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        associated_token::mint = mint,
+        associated_token::authority = recipient,
+    )]
+    pub recipient_token_account: Account<'info, TokenAccount>,
+
+    #[account(address = token::ID)]
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    #[account(address = system_program::ID)]
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct BatchBuy<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(mut)]
+    pub mint: Account<'info, Mint>,
+
+    /// CHECK: PDA signer derived from static seed; no deserialization needed
+    #[account(seeds = [b"mint_authority"], bump = config.load()?.mint_authority_bump)]
+    pub mint_authority: AccountInfo<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.load()?.config_bump,
+        has_one = mint @ ErrorCode::WrongMint,
+        has_one = sol_vault @ ErrorCode::WrongSolVault,
+        has_one = token_vault @ ErrorCode::WrongTokenVault,
+        has_one = token_vault_account @ ErrorCode::WrongTokenVaultAccount,
+    )]
+    pub config: AccountLoader<'info, Config>,
+
+    #[account(mut, seeds = [b"vault_ledger".as_ref(), &[PRIMARY_VAULT]], bump = ledger.bump)]
+    pub ledger: Account<'info, VaultLedger>,
+
+    /// CHECK: pinned by config `has_one = sol_vault` (system- or program-owned)
+    #[account(mut)]
+    pub sol_vault: UncheckedAccount<'info>,
+
+    /// CHECK: pinned by config `has_one = token_vault`; signs with the stored `token_vault_bump`.
+    #[account(mut)]
+    pub token_vault: UncheckedAccount<'info>,
+
+    /// Pinned by config `has_one = token_vault_account` (mint and owner set at init).
+    #[account(mut)]
+    pub token_vault_account: Account<'info, TokenAccount>,
+
+    #[account(address = token::ID)]
+    pub token_program: Program<'info, Token>,
+    #[account(address = system_program::ID)]
+    pub system_program: Program<'info, System>,
+    // remaining accounts: recipient token accounts, one per `amounts` entry
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct BuyFairTokenWsol<'info> {
+    /// Pays in wSOL only; no native lamports leave the buyer
+    pub buyer: Signer<'info>,
+
+    #[account(mut)]
+    pub mint: Account<'info, Mint>,
+
+    /// CHECK: PDA signer derived from static seed; no deserialization needed
+    #[account(seeds = [b"mint_authority"], bump = config.load()?.mint_authority_bump)]
+    pub mint_authority: AccountInfo<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.load()?.config_bump,
+        has_one = mint @ ErrorCode::WrongMint,
+        has_one = sol_vault @ ErrorCode::WrongSolVault,
+        has_one = token_vault @ ErrorCode::WrongTokenVault,
+        has_one = token_vault_account @ ErrorCode::WrongTokenVaultAccount,
+    )]
+    pub config: AccountLoader<'info, Config>,
+
+    #[account(mut, seeds = [b"vault_ledger".as_ref(), &[PRIMARY_VAULT]], bump = ledger.bump)]
+    pub ledger: Account<'info, VaultLedger>,
+
+    /// CHECK: SOL vault is a PDA used to hold SOL payments.
+    /// Pinned by config `has_one = sol_vault`; system-owned (signs with the stored
+    /// `sol_vault_bump`) or program-owned (`SolVault` header), per config.
+    #[account(mut)]
+    pub sol_vault: UncheckedAccount<'info>,
+
+    /// CHECK: pinned by config `has_one = token_vault`; signs with the stored `token_vault_bump`.
+    #[account(mut)]
+    pub token_vault: UncheckedAccount<'info>,
+
+    /// Pinned by config `has_one = token_vault_account` (mint and owner set at init).
+    #[account(mut)]
+    pub token_vault_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = user_token_account.mint == mint.key(),
+        constraint = user_token_account.owner == buyer.key(),
+    )]
+    pub user_token_account: Account<'info, TokenAccount>,
+
+    /// Buyer's wSOL token account the purchase is paid from
+    #[account(
+        mut,
+        constraint = user_wsol_account.mint == native_mint::ID          @ ErrorCode::NotWsol,
+        constraint = user_wsol_account.owner == buyer.key(),
+    )]
+    pub user_wsol_account: Account<'info, TokenAccount>,
+
+    #[account(address = native_mint::ID)]
+    pub wsol_mint: Account<'info, Mint>,
+
+    /// CHECK: per-buyer scratch wSOL account (PDA); created (rent advanced by the SOL
+    /// vault) and closed into the SOL vault within the instruction
+    #[account(mut, seeds = [b"wsol_scratch", buyer.key().as_ref()], bump)]
+    pub wsol_scratch: UncheckedAccount<'info>,
+
+    #[account(address = token::ID)]
+    pub token_program: Program<'info, Token>,
+    #[account(address = system_program::ID)]
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RedeemFairTokenWsol<'info> {
+    #[account(mut)]
+    pub redeemer: Signer<'info>,
+
+    #[account(mut)]
+    pub mint: Account<'info, Mint>,
+
+    /// CHECK: PDA signer derived from static seed; no deserialization needed
+    #[account(seeds = [b"mint_authority"], bump = config.load()?.mint_authority_bump)]
+    pub mint_authority: AccountInfo<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.load()?.config_bump,
+        has_one = mint @ ErrorCode::WrongMint,
+        has_one = sol_vault @ ErrorCode::WrongSolVault,
+        has_one = token_vault @ ErrorCode::WrongTokenVault,
+        has_one = token_vault_account @ ErrorCode::WrongTokenVaultAccount,
+    )]
+    pub config: AccountLoader<'info, Config>,
+
+    #[account(mut, seeds = [b"vault_ledger".as_ref(), &[PRIMARY_VAULT]], bump = ledger.bump)]
+    pub ledger: Account<'info, VaultLedger>,
+
+    /// CHECK: `["buy_queue"]` PDA, empty until the first order; readonly unless
+    /// waiting orders are passed to fill (see `fill_buy_orders`).
+    #[account(seeds = [b"buy_queue"], bump = config.load()?.buy_queue_bump)]
+    pub buy_queue: UncheckedAccount<'info>,
+
+    /// CHECK: SOL vault is a PDA used to hold SOL payments.
+    /// Pinned by config `has_one = sol_vault`; system-owned (signs with the stored
+    /// `sol_vault_bump`) or program-owned (`SolVault` header), per config.
+    #[account(mut)]
+    pub sol_vault: UncheckedAccount<'info>,
+
+    /// CHECK: pinned by config `has_one = token_vault`; signs with the stored `token_vault_bump`.
+    #[account(mut)]
+    pub token_vault: UncheckedAccount<'info>,
+
+    /// Pinned by config `has_one = token_vault_account` (mint and owner set at init).
+    #[account(mut)]
+    pub token_vault_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = user_token_account.mint == mint.key(),
+        constraint = user_token_account.owner == redeemer.key(),
+    )]
+    pub user_token_account: Account<'info, TokenAccount>,
+
+    /// Redeemer's wSOL token account the SOL is wrapped into
+    #[account(
+        mut,
+        constraint = user_wsol_account.mint == native_mint::ID          @ ErrorCode::NotWsol,
+        constraint = user_wsol_account.owner == redeemer.key(),
+    )]
+    pub user_wsol_account: Account<'info, TokenAccount>,
+
+    #[account(address = token::ID)]
+    pub token_program: Program<'info, Token>,
+    #[account(address = system_program::ID)]
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct InitializeTokenBacked<'info> {
+    /// Admin signer (hard-gated; remove `address = ADMIN` if you don't want gating)
+    #[account(mut, address = ADMIN)]
+    pub admin: Signer<'info>,
+
+    /// Pre-created SPL mint (vanity). Same preconditions as `Initialize::mint`.
+    #[account(
+        mut,
+        constraint = mint.decimals == DECIMALS                       @ ErrorCode::WrongDecimals,
+        constraint = mint.freeze_authority.is_none()                 @ ErrorCode::FreezeNotRevoked,
+        constraint = mint.supply == 0                                @ ErrorCode::NonZeroInitialSupply,
+        constraint = mint.mint_authority == Some(admin.key()).into() @ ErrorCode::MintAuthorityMustBeAdmin,
+    )]
+    pub mint: Account<'info, Mint>,
+
+    /// CHECK: PDA that will become the new mint authority (no data needed).
+    #[account(
+        seeds = [b"mint_authority"],
+        bump
+    )]
+    pub mint_authority: AccountInfo<'info>,
+
+    /// Global singleton config PDA
+    #[account(
+        init,
         payer = admin,
         space = 8 + Config::SIZE,
         seeds = [b"config"],
         bump
     )]
-    pub config: Account<'info, Config>,
+    pub config: AccountLoader<'info, Config>,
+
+    /// Config-wide event sequence (`EventStream`)
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + EventStream::SIZE,
+        seeds = [b"event_stream"],
+        bump
+    )]
+    pub event_stream: Account<'info, EventStream>,
+
+    /// Primary vault ledger (`open_vault_ledger` only opens shard ledgers)
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + VaultLedger::SIZE,
+        seeds = [b"vault_ledger".as_ref(), &[PRIMARY_VAULT]],
+        bump
+    )]
+    pub ledger: Account<'info, VaultLedger>,
+
+    /// Program-owned marker PDA; authority of both the token vault and the backing vault
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + TokenVault::SIZE,
+        seeds = [b"token_vault"],
+        bump
+    )]
+    pub token_vault: Account<'info, TokenVault>,
+
+    /// SPL token account controlled by `token_vault` PDA
+    #[account(
+        init,
+        payer = admin,
+        token::mint = mint,
+        token::authority = token_vault
+    )]
+    pub token_vault_account: Account<'info, TokenAccount>,
+
+    /// Backing asset mint (e.g. USDC). SPL Token, or Token-2022 without extensions:
+    /// transfer fees, hooks or a permanent delegate would let the backing vault receive
+    /// or keep less than the amounts booked 1:1.
+    #[account(
+        constraint = *backing_mint.to_account_info().owner == token::ID
+            || backing_mint.to_account_info().data_len() == Mint::LEN
+            @ ErrorCode::BackingMintExtensionsUnsupported,
+    )]
+    pub backing_mint: InterfaceAccount<'info, InterfaceMint>,
+
+    /// PDA token account holding the backing asset, controlled by `token_vault` PDA
+    #[account(
+        init,
+        payer = admin,
+        seeds = [b"backing_vault"],
+        bump,
+        token::mint = backing_mint,
+        token::authority = token_vault,
+        token::token_program = backing_token_program,
+    )]
+    pub backing_vault: InterfaceAccount<'info, InterfaceTokenAccount>,
+
+    // ───────────── Metaplex Token Metadata (auditor-friendly) ─────────────
+    /// CHECK: Must be the real Token Metadata program ID, validated by address constraint
+    #[account(address = TOKEN_METADATA_ID)]
+    pub token_metadata_program: AccountInfo<'info>,
+
+    /// CHECK: Metadata PDA for mint, validated by owner constraint and re-derived at runtime
+    #[account(mut, owner = TOKEN_METADATA_ID)]
+    pub metadata: AccountInfo<'info>,
+
+    // Canonical programs
+    #[account(address = token::ID)]
+    pub token_program: Program<'info, Token>,
+    pub backing_token_program: Interface<'info, TokenInterface>,
+    #[account(address = system_program::ID)]
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct BuyWithBacking<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(mut)]
+    pub mint: Account<'info, Mint>,
+
+    /// CHECK: PDA signer derived from static seed; no deserialization needed
+    #[account(seeds = [b"mint_authority"], bump = config.load()?.mint_authority_bump)]
+    pub mint_authority: AccountInfo<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.load()?.config_bump,
+        has_one = mint @ ErrorCode::WrongMint,
+        has_one = token_vault @ ErrorCode::WrongTokenVault,
+        has_one = token_vault_account @ ErrorCode::WrongTokenVaultAccount,
+        has_one = backing_mint @ ErrorCode::WrongBackingMint,
+        has_one = backing_vault @ ErrorCode::WrongBackingVault,
+    )]
+    pub config: AccountLoader<'info, Config>,
+
+    #[account(mut, seeds = [b"vault_ledger".as_ref(), &[PRIMARY_VAULT]], bump = ledger.bump)]
+    pub ledger: Account<'info, VaultLedger>,
+
+    /// CHECK: pinned by config `has_one = token_vault`; signs with the stored `token_vault_bump`.
+    #[account(mut)]
+    pub token_vault: UncheckedAccount<'info>,
+
+    /// Pinned by config `has_one = token_vault_account` (mint and owner set at init).
+    #[account(mut)]
+    pub token_vault_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = user_token_account.mint == mint.key(),
+        constraint = user_token_account.owner == buyer.key(),
+    )]
+    pub user_token_account: Account<'info, TokenAccount>,
+
+    pub backing_mint: InterfaceAccount<'info, InterfaceMint>,
+
+    #[account(mut, seeds = [b"backing_vault"], bump = config.load()?.backing_vault_bump)]
+    pub backing_vault: InterfaceAccount<'info, InterfaceTokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = backing_mint,
+        token::authority = buyer,
+        token::token_program = backing_token_program,
+    )]
+    pub user_backing_account: InterfaceAccount<'info, InterfaceTokenAccount>,
+
+    #[account(address = token::ID)]
+    pub token_program: Program<'info, Token>,
+    pub backing_token_program: Interface<'info, TokenInterface>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RedeemForBacking<'info> {
+    #[account(mut)]
+    pub redeemer: Signer<'info>,
+
+    #[account(mut)]
+    pub mint: Account<'info, Mint>,
+
+    /// CHECK: PDA signer derived from static seed; no deserialization needed
+    #[account(seeds = [b"mint_authority"], bump = config.load()?.mint_authority_bump)]
+    pub mint_authority: AccountInfo<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.load()?.config_bump,
+        has_one = mint @ ErrorCode::WrongMint,
+        has_one = token_vault @ ErrorCode::WrongTokenVault,
+        has_one = token_vault_account @ ErrorCode::WrongTokenVaultAccount,
+        has_one = backing_mint @ ErrorCode::WrongBackingMint,
+        has_one = backing_vault @ ErrorCode::WrongBackingVault,
+    )]
+    pub config: AccountLoader<'info, Config>,
+
+    #[account(mut, seeds = [b"vault_ledger".as_ref(), &[PRIMARY_VAULT]], bump = ledger.bump)]
+    pub ledger: Account<'info, VaultLedger>,
+
+    /// CHECK: pinned by config `has_one = token_vault`; signs with the stored `token_vault_bump`.
+    #[account(mut)]
+    pub token_vault: UncheckedAccount<'info>,
+
+    /// Pinned by config `has_one = token_vault_account` (mint and owner set at init).
+    #[account(mut)]
+    pub token_vault_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = user_token_account.mint == mint.key(),
+        constraint = user_token_account.owner == redeemer.key(),
+    )]
+    pub user_token_account: Account<'info, TokenAccount>,
+
+    pub backing_mint: InterfaceAccount<'info, InterfaceMint>,
+
+    #[account(mut, seeds = [b"backing_vault"], bump = config.load()?.backing_vault_bump)]
+    pub backing_vault: InterfaceAccount<'info, InterfaceTokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = backing_mint,
+        token::authority = redeemer,
+        token::token_program = backing_token_program,
+    )]
+    pub user_backing_account: InterfaceAccount<'info, InterfaceTokenAccount>,
+
+    #[account(address = token::ID)]
+    pub token_program: Program<'info, Token>,
+    pub backing_token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct RefreshPrice<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.load()?.config_bump,
+        has_one = mint @ ErrorCode::WrongMint,
+        has_one = token_vault_account @ ErrorCode::WrongTokenVaultAccount,
+    )]
+    pub config: AccountLoader<'info, Config>,
+
+    pub mint: Account<'info, Mint>,
+
+    pub token_vault_account: Account<'info, TokenAccount>,
+
+    /// CHECK: SOL vault (native) or backing vault (token-backed), pinned by `Config::reserve_vault`
+    #[account(address = config.load()?.reserve_vault() @ ErrorCode::InvalidVault)]
+    pub reserve_vault: AccountInfo<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + PriceFeed::SIZE,
+        seeds = [b"price_feed"],
+        bump
+    )]
+    pub price_feed: Account<'info, PriceFeed>,
+
+    #[account(address = system_program::ID)]
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(index: u8)]
+pub struct OpenVaultShard<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.load()?.config_bump,
+        has_one = mint @ ErrorCode::WrongMint,
+        has_one = token_vault @ ErrorCode::WrongTokenVault,
+    )]
+    pub config: AccountLoader<'info, Config>,
+
+    #[account(mut, seeds = [b"event_stream"], bump = event_stream.bump)]
+    pub event_stream: Account<'info, EventStream>,
+
+    pub mint: Account<'info, Mint>,
+
+    /// CHECK: pinned by config `has_one = token_vault`; signs with the stored `token_vault_bump`.
+    pub token_vault: UncheckedAccount<'info>,
+
+    /// Shard SOL vault, funded with its rent reserve here
+    #[account(mut, seeds = [b"sol_vault".as_ref(), &[index]], bump)]
+    pub sol_vault_shard: SystemAccount<'info>,
+
+    #[account(
+        init,
+        payer = payer,
+        seeds = [b"token_vault_account".as_ref(), &[index]],
+        bump,
+        token::mint = mint,
+        token::authority = token_vault
+    )]
+    pub shard_token_account: Account<'info, TokenAccount>,
+
+    #[account(address = token::ID)]
+    pub token_program: Program<'info, Token>,
+    #[account(address = system_program::ID)]
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(shard: u8)]
+pub struct BuySharded<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    /// CHECK: pinned by config `has_one = mint`. Writable only when this instruction
+    /// mints or burns (see `buy_sharded` / `redeem_sharded`; `MintNotWritable` otherwise),
+    /// so post-finalization shard traffic does not write-lock it.
+    pub mint: UncheckedAccount<'info>,
+
+    /// CHECK: PDA signer derived from static seed; no deserialization needed
+    #[account(seeds = [b"mint_authority"], bump = config.load()?.mint_authority_bump)]
+    pub mint_authority: AccountInfo<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.load()?.config_bump,
+        has_one = mint @ ErrorCode::WrongMint,
+        has_one = token_vault @ ErrorCode::WrongTokenVault,
+    )]
+    pub config: AccountLoader<'info, Config>,
+
+    #[account(mut, seeds = [b"vault_ledger".as_ref(), &[shard]], bump = ledger.bump)]
+    pub ledger: Account<'info, VaultLedger>,
+
+    #[account(mut, seeds = [b"sol_vault".as_ref(), &[shard]], bump = ledger.sol_vault_bump)]
+    pub sol_vault_shard: SystemAccount<'info>,
+
+    /// CHECK: pinned by config `has_one = token_vault`; signs with the stored `token_vault_bump`.
+    pub token_vault: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"token_vault_account".as_ref(), &[shard]],
+        bump = ledger.token_account_bump
+    )]
+    pub shard_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = user_token_account.mint == mint.key(),
+        constraint = user_token_account.owner == buyer.key(),
+    )]
+    pub user_token_account: Account<'info, TokenAccount>,
+
+    #[account(address = token::ID)]
+    pub token_program: Program<'info, Token>,
+    #[account(address = system_program::ID)]
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(shard: u8)]
+pub struct RedeemSharded<'info> {
+    #[account(mut)]
+    pub redeemer: Signer<'info>,
+
+    /// CHECK: pinned by config `has_one = mint`. Writable only when this instruction
+    /// mints or burns (see `buy_sharded` / `redeem_sharded`; `MintNotWritable` otherwise),
+    /// so post-finalization shard traffic does not write-lock it.
+    pub mint: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.load()?.config_bump,
+        has_one = mint @ ErrorCode::WrongMint,
+        has_one = token_vault @ ErrorCode::WrongTokenVault,
+    )]
+    pub config: AccountLoader<'info, Config>,
+
+    #[account(mut, seeds = [b"vault_ledger".as_ref(), &[shard]], bump = ledger.bump)]
+    pub ledger: Account<'info, VaultLedger>,
+
+    /// CHECK: `["buy_queue"]` PDA, empty until the first order; readonly unless
+    /// waiting orders are passed to fill (see `fill_buy_orders`).
+    #[account(seeds = [b"buy_queue"], bump = config.load()?.buy_queue_bump)]
+    pub buy_queue: UncheckedAccount<'info>,
+
+    #[account(mut, seeds = [b"sol_vault".as_ref(), &[shard]], bump = ledger.sol_vault_bump)]
+    pub sol_vault_shard: SystemAccount<'info>,
+
+    /// CHECK: pinned by config `has_one = token_vault`; signs with the stored `token_vault_bump`.
+    pub token_vault: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"token_vault_account".as_ref(), &[shard]],
+        bump = ledger.token_account_bump
+    )]
+    pub shard_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = user_token_account.mint == mint.key(),
+        constraint = user_token_account.owner == redeemer.key(),
+    )]
+    pub user_token_account: Account<'info, TokenAccount>,
+
+    #[account(address = token::ID)]
+    pub token_program: Program<'info, Token>,
+    #[account(address = system_program::ID)]
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RebalanceVaultShards<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.load()?.config_bump,
+        has_one = token_vault @ ErrorCode::WrongTokenVault,
+    )]
+    pub config: AccountLoader<'info, Config>,
+
+    #[account(mut, seeds = [b"event_stream"], bump = event_stream.bump)]
+    pub event_stream: Account<'info, EventStream>,
+
+    /// CHECK: pinned by config `has_one = token_vault`; signs with the stored `token_vault_bump`.
+    pub token_vault: UncheckedAccount<'info>,
+
+    /// CHECK: addresses are checked against the `from` / `to` indices in the handler
+    /// (the primary SOL vault may be program-owned).
+    #[account(mut)]
+    pub from_sol_vault: UncheckedAccount<'info>,
+    /// CHECK: see `from_sol_vault`
+    #[account(mut)]
+    pub to_sol_vault: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub from_token_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub to_token_account: Account<'info, TokenAccount>,
+
+    #[account(address = token::ID)]
+    pub token_program: Program<'info, Token>,
+    #[account(address = system_program::ID)]
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CheckSolvency<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.load()?.config_bump,
+        has_one = mint @ ErrorCode::WrongMint,
+        has_one = token_vault_account @ ErrorCode::WrongTokenVaultAccount,
+    )]
+    pub config: AccountLoader<'info, Config>,
+
+    #[account(mut, seeds = [b"event_stream"], bump = event_stream.bump)]
+    pub event_stream: Account<'info, EventStream>,
+
+    pub mint: Account<'info, Mint>,
+
+    /// CHECK: SOL vault (native) or backing vault (token-backed), pinned by `Config::reserve_vault`
+    #[account(address = config.load()?.reserve_vault() @ ErrorCode::InvalidVault)]
+    pub reserve_vault: AccountInfo<'info>,
+
+    pub token_vault_account: Account<'info, TokenAccount>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct Donate<'info> {
+    #[account(mut)]
+    pub donor: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.load()?.config_bump,
+        has_one = sol_vault @ ErrorCode::WrongSolVault,
+    )]
+    pub config: AccountLoader<'info, Config>,
+
+    #[account(mut, seeds = [b"event_stream"], bump = event_stream.bump)]
+    pub event_stream: Account<'info, EventStream>,
+
+    /// CHECK: pinned by config `has_one = sol_vault` (system- or program-owned)
+    #[account(mut)]
+    pub sol_vault: UncheckedAccount<'info>,
+
+    #[account(address = system_program::ID)]
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct OpenReferral<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: any wallet; only its key is recorded
+    pub referrer: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + Referral::SIZE,
+        seeds = [b"referral", referrer.key().as_ref()],
+        bump
+    )]
+    pub referral: Account<'info, Referral>,
+
+    #[account(address = system_program::ID)]
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimReferralRewards<'info> {
+    #[account(mut)]
+    pub referrer: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.load()?.config_bump)]
+    pub config: AccountLoader<'info, Config>,
+
+    #[account(mut, seeds = [b"event_stream"], bump = event_stream.bump)]
+    pub event_stream: Account<'info, EventStream>,
+
+    #[account(
+        mut,
+        has_one = referrer @ ErrorCode::InvalidOwner,
+        seeds = [b"referral", referrer.key().as_ref()],
+        bump = referral.bump
+    )]
+    pub referral: Account<'info, Referral>,
+
+    /// System-owned reward pool, funded by plain transfers
+    #[account(mut, seeds = [b"referral_pool"], bump = config.load()?.referral_pool_bump)]
+    pub referral_pool: SystemAccount<'info>,
+
+    #[account(address = system_program::ID)]
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(epoch: u64)]
+pub struct SnapshotSurplus<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.load()?.config_bump,
+        has_one = mint @ ErrorCode::WrongMint,
+        has_one = sol_vault @ ErrorCode::WrongSolVault,
+        has_one = token_vault_account @ ErrorCode::WrongTokenVaultAccount,
+    )]
+    pub config: AccountLoader<'info, Config>,
+
+    #[account(mut, seeds = [b"event_stream"], bump = event_stream.bump)]
+    pub event_stream: Account<'info, EventStream>,
+
+    pub mint: Account<'info, Mint>,
+
+    /// CHECK: pinned by config `has_one = sol_vault` (system- or program-owned)
+    #[account(mut)]
+    pub sol_vault: UncheckedAccount<'info>,
+
+    pub token_vault_account: Account<'info, TokenAccount>,
 
-    /// CHECK: System-owned, zero-data PDA used as a SOL vault.
-    /// Using AccountInfo to avoid Anchor's SystemAccount "try_from_unchecked" quirk during init.
     #[account(
         init,
-        payer = admin,
-        space = 0,
-        owner = system_program::ID,
-        seeds = [b"sol_vault"],
+        payer = payer,
+        space = 8 + Distribution::SIZE,
+        seeds = [b"distribution", epoch.to_le_bytes().as_ref()],
         bump
     )]
-    pub sol_vault: AccountInfo<'info>,
+    pub distribution: Account<'info, Distribution>,
+
+    #[account(address = system_program::ID)]
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct PublishDistributionRoot<'info> {
+    /// The distribution crank fixed at initialize; recorded on the distribution
+    #[account(
+        constraint = publisher.key() == config.load()?.distribution_crank @ ErrorCode::NotDistributionCrank
+    )]
+    pub publisher: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.load()?.config_bump,
+        has_one = sol_vault @ ErrorCode::WrongSolVault,
+    )]
+    pub config: AccountLoader<'info, Config>,
+
+    #[account(mut, seeds = [b"event_stream"], bump = event_stream.bump)]
+    pub event_stream: Account<'info, EventStream>,
+
+    /// CHECK: pinned by config `has_one = sol_vault`; receives a forfeited challenge bond
+    #[account(mut)]
+    pub sol_vault: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"distribution", distribution.epoch.to_le_bytes().as_ref()],
+        bump = distribution.bump
+    )]
+    pub distribution: Account<'info, Distribution>,
+
+    /// CHECK: the pending challenger (`distribution.challenger`), refunded its bond when
+    /// a different root is published; only needed then
+    #[account(mut)]
+    pub challenger: Option<UncheckedAccount<'info>>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(epoch: u64)]
+pub struct ChallengeDistributionRoot<'info> {
+    #[account(mut)]
+    pub challenger: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.load()?.config_bump)]
+    pub config: AccountLoader<'info, Config>,
+
+    #[account(mut, seeds = [b"event_stream"], bump = event_stream.bump)]
+    pub event_stream: Account<'info, EventStream>,
+
+    #[account(
+        mut,
+        seeds = [b"distribution", epoch.to_le_bytes().as_ref()],
+        bump = distribution.bump
+    )]
+    pub distribution: Account<'info, Distribution>,
+
+    /// Challenger's stake in the fair token
+    #[account(
+        constraint = challenger_token_account.mint == config.load()?.mint @ ErrorCode::WrongMint,
+        constraint = challenger_token_account.owner == challenger.key(),
+    )]
+    pub challenger_token_account: Account<'info, TokenAccount>,
+
+    #[account(address = system_program::ID)]
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(epoch: u64)]
+pub struct ClaimDistribution<'info> {
+    #[account(mut)]
+    pub claimant: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.load()?.config_bump)]
+    pub config: AccountLoader<'info, Config>,
+
+    #[account(mut, seeds = [b"event_stream"], bump = event_stream.bump)]
+    pub event_stream: Account<'info, EventStream>,
+
+    #[account(
+        mut,
+        seeds = [b"distribution", epoch.to_le_bytes().as_ref()],
+        bump = distribution.bump
+    )]
+    pub distribution: Account<'info, Distribution>,
 
-    /// Program-owned marker PDA for token vault state
     #[account(
         init,
-        payer = admin,
-        space = 8 + TokenVault::SIZE,
-        seeds = [b"token_vault"],
+        payer = claimant,
+        space = 8 + DistributionClaim::SIZE,
+        seeds = [b"distribution_claim", epoch.to_le_bytes().as_ref(), claimant.key().as_ref()],
+        bump
+    )]
+    pub claim: Account<'info, DistributionClaim>,
+
+    #[account(address = system_program::ID)]
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(epoch: u64)]
+pub struct CloseDistribution<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.load()?.config_bump,
+        has_one = sol_vault @ ErrorCode::WrongSolVault,
+    )]
+    pub config: AccountLoader<'info, Config>,
+
+    #[account(mut, seeds = [b"event_stream"], bump = event_stream.bump)]
+    pub event_stream: Account<'info, EventStream>,
+
+    /// CHECK: pinned by config `has_one = sol_vault` (system- or program-owned)
+    #[account(mut)]
+    pub sol_vault: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        close = payer,
+        has_one = payer,
+        seeds = [b"distribution", epoch.to_le_bytes().as_ref()],
+        bump = distribution.bump
+    )]
+    pub distribution: Account<'info, Distribution>,
+
+    /// CHECK: rent refund target, pinned by `has_one = payer`
+    #[account(mut)]
+    pub payer: UncheckedAccount<'info>,
+
+    /// CHECK: the pending challenger (`distribution.challenger`), refunded its bond;
+    /// only needed while a challenge is pending
+    #[account(mut)]
+    pub challenger: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+#[instruction(index: u8)]
+pub struct OpenVaultLedger<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.load()?.config_bump)]
+    pub config: AccountLoader<'info, Config>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + VaultLedger::SIZE,
+        seeds = [b"vault_ledger".as_ref(), &[index]],
+        bump
+    )]
+    pub ledger: Account<'info, VaultLedger>,
+
+    #[account(address = system_program::ID)]
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct Finalize<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.load()?.config_bump,
+        has_one = mint @ ErrorCode::WrongMint,
+        has_one = token_vault_account @ ErrorCode::WrongTokenVaultAccount,
+    )]
+    pub config: AccountLoader<'info, Config>,
+
+    #[account(mut, seeds = [b"event_stream"], bump = event_stream.bump)]
+    pub event_stream: Account<'info, EventStream>,
+
+    #[account(mut)]
+    pub mint: Account<'info, Mint>,
+
+    /// CHECK: PDA signer derived from static seed; no deserialization needed
+    #[account(seeds = [b"mint_authority"], bump = config.load()?.mint_authority_bump)]
+    pub mint_authority: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub token_vault_account: Account<'info, TokenAccount>,
+
+    /// Required iff `initialize` configured a creator allocation (`open_creator_vesting`).
+    #[account(mut, seeds = [b"creator_vesting"], bump = creator_vesting.bump)]
+    pub creator_vesting: Option<Account<'info, CreatorVesting>>,
+
+    #[account(mut, address = creator_vesting.as_ref().map(|v| v.escrow).unwrap_or_default())]
+    pub creator_escrow: Option<Account<'info, TokenAccount>>,
+
+    #[account(address = token::ID)]
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct OpenCreatorVesting<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.load()?.config_bump, has_one = mint @ ErrorCode::WrongMint)]
+    pub config: AccountLoader<'info, Config>,
+
+    pub mint: Account<'info, Mint>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + CreatorVesting::SIZE,
+        seeds = [b"creator_vesting"],
         bump
     )]
-    pub token_vault: Account<'info, TokenVault>,
+    pub creator_vesting: Account<'info, CreatorVesting>,
 
-    /// SPL token account controlled by `token_vault` PDA
     #[account(
         init,
-        payer = admin,
+        payer = payer,
+        seeds = [b"creator_escrow"],
+        bump,
         token::mint = mint,
-        token::authority = token_vault
+        token::authority = creator_vesting,
     )]
-    pub token_vault_account: Account<'info, TokenAccount>,
-
-    // ───────────── Metaplex Token Metadata (auditor-friendly) ─────────────
-    /// CHECK: Must be the real Token Metadata program ID, validated by address constraint
-    #[account(address = TOKEN_METADATA_ID)]
-    pub token_metadata_program: AccountInfo<'info>,
-
-    /// CHECK: Metadata PDA for mint, validated by owner constraint and re-derived at runtime
-    #[account(mut, owner = TOKEN_METADATA_ID)]
-    pub metadata: AccountInfo<'info>,
+    pub creator_escrow: Account<'info, TokenAccount>,
 
-    // Canonical programs
     #[account(address = token::ID)]
     pub token_program: Program<'info, Token>,
     #[account(address = system_program::ID)]
     pub system_program: Program<'info, System>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
-pub struct BuyFairToken<'info> {
-    #[account(mut)]
-    pub buyer: Signer<'info>,
-
+pub struct ReleaseCreatorVesting<'info> {
     #[account(mut)]
-    pub mint: Account<'info, Mint>,
+    pub backer: Signer<'info>,
 
-    /// CHECK: PDA signer derived from static seed; no deserialization needed
-    #[account(seeds = [b"mint_authority"], bump)]
-    pub mint_authority: AccountInfo<'info>,
     #[account(
         mut,
         seeds = [b"config"],
-        bump,
-        has_one = mint @ ErrorCode::WrongMint,
+        bump = config.load()?.config_bump,
         has_one = sol_vault @ ErrorCode::WrongSolVault,
-        has_one = token_vault @ ErrorCode::WrongTokenVault,
-        has_one = token_vault_account @ ErrorCode::WrongTokenVaultAccount,
     )]
-    pub config: Account<'info, Config>,
+    pub config: AccountLoader<'info, Config>,
 
-    /// CHECK: SOL vault is a PDA used to hold SOL payments.
-    /// Ownership and data length are validated in the initialize function.
-    #[account(
-        mut,
-        seeds = [b"sol_vault"],
-        bump
-    )]
-    pub sol_vault: SystemAccount<'info>,
+    /// CHECK: Pinned by config `has_one = sol_vault`; credited the release backing.
+    #[account(mut)]
+    pub sol_vault: UncheckedAccount<'info>,
 
-    #[account(mut, seeds = [b"token_vault"], bump)]
-    pub token_vault: Account<'info, TokenVault>,
+    #[account(mut, seeds = [b"event_stream"], bump = event_stream.bump)]
+    pub event_stream: Account<'info, EventStream>,
 
-    #[account(
-        mut,
-        constraint = token_vault_account.mint == mint.key()             @ ErrorCode::WrongMint,
-        constraint = token_vault_account.owner == token_vault.key()     @ ErrorCode::WrongVaultAuthority,
-    )]
-    pub token_vault_account: Account<'info, TokenAccount>,
+    #[account(mut, seeds = [b"creator_vesting"], bump = creator_vesting.bump)]
+    pub creator_vesting: Account<'info, CreatorVesting>,
+
+    #[account(mut, address = creator_vesting.escrow)]
+    pub creator_escrow: Account<'info, TokenAccount>,
 
+    /// The beneficiary's ATA: releases can go nowhere else
     #[account(
         mut,
-        constraint = user_token_account.mint == mint.key(),
-        constraint = user_token_account.owner == buyer.key(),
+        associated_token::mint = creator_escrow.mint,
+        associated_token::authority = creator_vesting.beneficiary,
     )]
-    pub user_token_account: Account<'info, TokenAccount>,
+    pub beneficiary_token_account: Account<'info, TokenAccount>,
 
     #[account(address = token::ID)]
     pub token_program: Program<'info, Token>,
+
     #[account(address = system_program::ID)]
     pub system_program: Program<'info, System>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
-pub struct RedeemFairToken<'info> {
+pub struct MigrateFromLegacy<'info> {
     #[account(mut)]
-    pub redeemer: Signer<'info>,
+    pub holder: Signer<'info>,
 
+    // ---- successor (this program) ----
     #[account(mut)]
     pub mint: Account<'info, Mint>,
 
     /// CHECK: PDA signer derived from static seed; no deserialization needed
-    #[account(seeds = [b"mint_authority"], bump)]
+    #[account(seeds = [b"mint_authority"], bump = config.load()?.mint_authority_bump)]
     pub mint_authority: AccountInfo<'info>,
     #[account(
-        mut,
         seeds = [b"config"],
-        bump,
+        bump = config.load()?.config_bump,
         has_one = mint @ ErrorCode::WrongMint,
         has_one = sol_vault @ ErrorCode::WrongSolVault,
         has_one = token_vault @ ErrorCode::WrongTokenVault,
         has_one = token_vault_account @ ErrorCode::WrongTokenVaultAccount,
     )]
-    pub config: Account<'info, Config>,
+    pub config: AccountLoader<'info, Config>,
 
-    /// CHECK: SOL vault is a PDA used to hold SOL payments.
-    #[account(
-        mut,
-        seeds = [b"sol_vault"],
-        bump
-    )]
-    pub sol_vault: SystemAccount<'info>,
+    #[account(mut, seeds = [b"vault_ledger".as_ref(), &[PRIMARY_VAULT]], bump = ledger.bump)]
+    pub ledger: Account<'info, VaultLedger>,
 
-    #[account(mut, seeds = [b"token_vault"], bump)]
-    pub token_vault: Account<'info, TokenVault>,
+    /// CHECK: pinned by config `has_one = sol_vault` (system- or program-owned)
+    #[account(mut)]
+    pub sol_vault: UncheckedAccount<'info>,
 
-    #[account(
-        mut,
-        constraint = token_vault_account.mint == mint.key()             @ ErrorCode::WrongMint,
-        constraint = token_vault_account.owner == token_vault.key()     @ ErrorCode::WrongVaultAuthority,
-    )]
+    /// CHECK: pinned by config `has_one = token_vault`; signs with the stored `token_vault_bump`.
+    #[account(mut)]
+    pub token_vault: UncheckedAccount<'info>,
+
+    /// Pinned by config `has_one = token_vault_account` (mint and owner set at init).
+    #[account(mut)]
     pub token_vault_account: Account<'info, TokenAccount>,
 
     #[account(
         mut,
         constraint = user_token_account.mint == mint.key(),
-        constraint = user_token_account.owner == redeemer.key(),
+        constraint = user_token_account.owner == holder.key(),
     )]
     pub user_token_account: Account<'info, TokenAccount>,
 
+    // ---- legacy deployment ----
+    /// System-owned PDA: legacy redeemer and transient holder of the redeemed SOL
+    /// (keeps a rent-exempt reserve, topped up by the holder when short)
+    #[account(mut, seeds = [b"migration"], bump)]
+    pub migration_authority: SystemAccount<'info>,
+
+    #[account(mut, constraint = legacy_mint.key() != mint.key() @ ErrorCode::WrongMint)]
+    pub legacy_mint: Account<'info, Mint>,
+
+    #[account(mut, token::mint = legacy_mint, token::authority = holder)]
+    pub holder_legacy_account: Account<'info, TokenAccount>,
+
+    /// Migration PDA's legacy ATA (deposit escrow; emptied by the legacy redeem)
+    #[account(
+        init_if_needed,
+        payer = holder,
+        associated_token::mint = legacy_mint,
+        associated_token::authority = migration_authority,
+    )]
+    pub migration_legacy_account: Account<'info, TokenAccount>,
+
+    /// CHECK: validated by the legacy program (its `["mint_authority"]` PDA)
+    pub legacy_mint_authority: UncheckedAccount<'info>,
+    /// CHECK: validated by the legacy program (its `["config"]` PDA, `has_one` checks)
+    #[account(mut)]
+    pub legacy_config: UncheckedAccount<'info>,
+    /// CHECK: validated by the legacy program (its `["sol_vault"]` PDA)
+    #[account(mut)]
+    pub legacy_sol_vault: UncheckedAccount<'info>,
+    /// CHECK: validated by the legacy program (its `["token_vault"]` PDA)
+    #[account(mut)]
+    pub legacy_token_vault: UncheckedAccount<'info>,
+    /// CHECK: validated by the legacy program (config `has_one = token_vault_account`)
+    #[account(mut)]
+    pub legacy_token_vault_account: UncheckedAccount<'info>,
+
+    /// CHECK: the audited legacy program
+    #[account(address = LEGACY_PROGRAM_ID @ ErrorCode::InvalidLegacyProgram)]
+    pub legacy_program: UncheckedAccount<'info>,
+
     #[account(address = token::ID)]
     pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
     #[account(address = system_program::ID)]
     pub system_program: Program<'info, System>,
 }
@@ -608,69 +5537,696 @@ impl TokenVault {
     pub const SIZE: usize = 1;
 }
 
+/// Header of a program-owned SOL vault (seeds `["sol_vault"]`, `initialize_program_vault`).
+/// The account's lamports are `backing_lamports` plus its rent-exempt minimum, so the
+/// backing is recorded exactly and redemptions debit lamports directly (no System
+/// Program CPI).
+#[account]
+pub struct SolVault {
+    pub backing_lamports: u64,
+}
+impl SolVault {
+    pub const SIZE: usize = 8;
+}
+
+/// Surplus of one distribution epoch (seeds `["distribution", epoch]`), created by
+/// `snapshot_surplus`. Its lamports are `lamports - claimed` plus rent; none of it
+/// backs the fair token any more.
+#[account]
+pub struct Distribution {
+    pub epoch: u64,
+    pub payer: Pubkey,         // snapshot rent payer, refunded on close
+    pub snapshot_slot: u64,    // holder balances for the Merkle tree are taken here
+    pub snapshot_ts: i64,      // claim window start
+    pub circulating: u64,      // circulating supply at the snapshot (pro-rata denominator)
+    pub lamports: u64,         // surplus moved out of the SOL vault
+    pub merkle_root: [u8; 32], // all zeros until `publish_distribution_root`
+    pub total_claimable: u64,  // sum of the published leaves (<= lamports)
+    pub claimed: u64,
+    pub publisher: Pubkey,         // crank that published `merkle_root`
+    pub root_published_ts: i64,    // claims open `DISTRIBUTION_CHALLENGE_SECS` later
+    pub challenger: Pubkey,        // pending challenge; Pubkey::default() == none
+    pub challenge_bond: u64,       // lamports the pending challenger escrowed here
+    pub challenged_root: [u8; 32], // root the pending challenge cleared
+    pub bump: u8,
+}
+impl Distribution {
+    pub const SIZE: usize = 8 + 32 + 8 * 4 + 32 + 8 * 2 + 32 + 8 + 32 + 8 + 32 + 1; // 233
+}
+
+/// Receipt of one claimed distribution leaf (seeds `["distribution_claim", epoch, claimant]`).
+#[account]
+pub struct DistributionClaim {
+    pub epoch: u64,
+    pub claimant: Pubkey,
+    pub amount: u64,
+}
+impl DistributionClaim {
+    pub const SIZE: usize = 8 + 32 + 8; // 48
+}
+
+/// Creator allocation fixed at initialize (optional `initialize*` argument).
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct CreatorAllocation {
+    pub beneficiary: Pubkey,
+    pub bps: u16,          // of the sale supply, <= MAX_CREATOR_ALLOCATION_BPS
+    pub cliff_secs: i64,   // nothing vests before finalization + cliff_secs
+    pub vesting_secs: i64, // fully vested at finalization + vesting_secs (>= cliff_secs)
+}
+
+/// Referral reward terms fixed at initialize (optional `initialize*` argument).
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct ReferralRewards {
+    pub reward_bps: u16, // of each referred purchase, <= MAX_REFERRAL_REWARD_BPS
+    pub max_reward_per_referrer: u64, // lifetime cap per referrer (lamports)
+}
+
+/// Per-referrer totals (seeds `["referral", referrer]`), written by referred buys.
+#[account]
+pub struct Referral {
+    pub referrer: Pubkey,
+    pub buys: u64,            // referred buys
+    pub lamports: u64,        // referred purchase volume
+    pub rewards_accrued: u64, // <= Config.referral_reward_cap
+    pub rewards_paid: u64,    // claimed from the reward pool
+    pub bump: u8,
+}
+impl Referral {
+    pub const SIZE: usize = 32 + 8 * 4 + 1; // 65
+}
+
+/// Creator allocation vesting state (seeds `["creator_vesting"]`); authority of the
+/// escrow token account `["creator_escrow"]` that `finalize` mints the allocation into.
+#[account]
+pub struct CreatorVesting {
+    pub beneficiary: Pubkey,
+    pub escrow: Pubkey,
+    pub total: u64,    // minted by `finalize`; 0 until then
+    pub released: u64, // transferred to the beneficiary so far
+    pub start_ts: i64, // finalization time
+    pub cliff_ts: i64,
+    pub end_ts: i64,
+    pub bump: u8,
+}
+impl CreatorVesting {
+    pub const SIZE: usize = 32 * 2 + 8 * 5 + 1; // 105
+
+    /// Vested amount at `now`: 0 before the cliff, linear from `start_ts` to `end_ts`.
+    pub fn vested_at(&self, now: i64) -> u64 {
+        if now < self.cliff_ts {
+            0
+        } else if now >= self.end_ts {
+            self.total
+        } else {
+            let elapsed = (now - self.start_ts) as u128;
+            let duration = (self.end_ts - self.start_ts) as u128;
+            (self.total as u128 * elapsed / duration) as u64
+        }
+    }
+}
+
+/// Per-owner destination for SOL from delegated redemptions.
+#[account]
+pub struct RedeemDestination {
+    pub owner: Pubkey,
+    pub destination: Pubkey,
+}
+impl RedeemDestination {
+    pub const SIZE: usize = 32 + 32;
+}
+
+/// FIFO buy-order queue (post-finalization). Orders live in `BuyOrder` PDAs
+/// seeded by their id; `head` is the oldest order that may still be open.
+#[account]
+pub struct BuyQueue {
+    pub head: u64,
+    pub tail: u64, // next order id
+    pub escrowed_lamports: u64,
+    pub event_seq: u64,   // next sequence number of buy-order events
+    pub open_orders: u64, // placed orders with escrow left, <= MAX_OPEN_BUY_ORDERS
+}
+impl BuyQueue {
+    pub const SIZE: usize = 8 + 8 + 8 + 8 + 8;
+}
+
+/// Queued buy order; the account holds `remaining` escrowed lamports on top of rent.
 #[account]
+pub struct BuyOrder {
+    pub id: u64,
+    pub buyer: Pubkey,
+    pub recipient_token_account: Pubkey,
+    pub remaining: u64, // lamports (== base units) still to fill
+    pub bump: u8,
+}
+impl BuyOrder {
+    pub const SIZE: usize = 8 + 32 + 32 + 8 + 1;
+}
+
+/// Global singleton (seeds `["config"]`). Zero-copy: the hot paths only borrow it, and
+/// the PDA bumps are stored here so they re-derive addresses with
+/// `create_program_address` instead of a bump search. Fields are ordered so the
+/// `repr(C)` layout has no padding; flags are `u8` (0/1) since `bool` is not `Pod`.
+#[account(zero_copy)]
 pub struct Config {
-    pub initialized: bool,
-    pub finalized: bool,
     pub mint: Pubkey,
     pub sol_vault: Pubkey,
     pub token_vault: Pubkey,
     pub token_vault_account: Pubkey,
+    pub backing_mint: Pubkey,  // Pubkey::default() == native SOL backing
+    pub backing_vault: Pubkey, // Pubkey::default() == native SOL backing
+    pub creator: Pubkey,       // creator allocation beneficiary; Pubkey::default() == none
+    pub distribution_crank: Pubkey, // sole distribution root publisher; Pubkey::default() == none
     pub sale_end: i64,
     pub min_supply_base_units: u64,
-    pub total_burned: u64, // informational; not used in logic
+    pub vault_ceiling: u64, // post-finalization inventory cap per vault token account; u64::MAX == none
+    // Buy/redeem accounting lives in the per-vault `VaultLedger`s, so `config` stays
+    // read-only on the hot path.
+    pub total_minted_shortfall: u64, // minted to the vault by `finalize`
+    pub donated_lamports: u64,       // recorded surplus: SOL given to the vault by `donate`
+    pub distribution_epoch: u64,     // epoch of the next `snapshot_surplus`
+    pub last_distribution_ts: i64,   // unix time of the last `snapshot_surplus` (0 == none)
+    pub distributed_lamports: u64,   // surplus moved into distributions, net of returns
+    pub creator_cliff_secs: i64,     // creator vesting cliff, from finalization
+    pub creator_vesting_secs: i64,   // creator vesting end (linear from finalization), >= cliff
+    pub creator_allocated: u64,      // unbacked creator supply still in escrow (not circulating)
+    pub referral_reward_cap: u64,    // lifetime reward cap per referrer (lamports)
+    pub creator_allocation_bps: u16, // of the sale supply; 0 == none
+    pub referral_reward_bps: u16,    // of referred purchases; 0 == no rewards
+    pub initialized: u8,
+    pub finalized: u8,
+    pub backing_decimals: u8, // == DECIMALS for native SOL backing
+    pub shard_count: u8,      // open vault shards (native SOL backing only)
+    pub config_bump: u8,
+    pub mint_authority_bump: u8,
+    pub sol_vault_bump: u8, // 0 if token-backed (no SOL vault)
+    pub token_vault_bump: u8,
+    pub sol_vault_program_owned: u8, // 1 == `SolVault` header (`initialize_program_vault`)
+    pub referral_pool_bump: u8,
+    pub buy_queue_bump: u8,
+    pub backing_vault_bump: u8, // 0 unless token-backed
 }
 impl Config {
-    pub const SIZE: usize = 1 + 1 + 32 + 32 + 32 + 32 + 8 + 8 + 8; // 154
+    pub const SIZE: usize = 32 * 8 + 8 * 12 + 16; // 368
+
+    pub fn is_initialized(&self) -> bool {
+        self.initialized != 0
+    }
+
+    pub fn is_finalized(&self) -> bool {
+        self.finalized != 0
+    }
+
+    pub fn sol_vault_is_program_owned(&self) -> bool {
+        self.sol_vault_program_owned != 0
+    }
+
+    /// Account holding the backing: SOL vault, or backing vault if token-backed.
+    pub fn reserve_vault(&self) -> Pubkey {
+        if self.backing_mint == Pubkey::default() {
+            self.sol_vault
+        } else {
+            self.backing_vault
+        }
+    }
+}
+
+/// Per-vault accounting (seeds `["vault_ledger", index]`, index = shard or `PRIMARY_VAULT`).
+/// Buys and redeems write only the ledger of the vault they touch, which also numbers
+/// that vault's events (`event_seq`). Lifetime totals are
+/// the sum over all ledgers (fair base units; SOL in lamports, backing tokens in backing
+/// base units).
+#[account]
+pub struct VaultLedger {
+    pub index: u8,
+    pub total_minted_sale: u64,       // minted to buyers pre-finalization
+    pub total_bought_from_vault: u64, // transferred vault → buyers post-finalization
+    pub total_redeemed_burn: u64,     // burned by pre-finalization redemptions
+    pub total_redeemed_to_vault: u64, // restocked into the vault by redemptions
+    pub total_queue_filled: u64,      // redeemed tokens delivered to queued buy orders
+    pub total_burned: u64,            // pre-finalization redeem burns + vault ceiling burns
+    pub lamports_in: u64,             // SOL received by the vault
+    pub lamports_out: u64,            // SOL paid out by redemptions
+    pub backing_in: u64,              // backing tokens received (token-backed configs)
+    pub backing_out: u64,             // backing tokens paid out (token-backed configs)
+    pub event_seq: u64,               // next sequence number of this vault's events
+    pub bump: u8,
+    pub sol_vault_bump: u8, // shard SOL vault `["sol_vault", index]` (0 on the primary)
+    pub token_account_bump: u8, // shard `["token_vault_account", index]` (0 on the primary)
+}
+impl VaultLedger {
+    pub const SIZE: usize = 1 + 8 * 11 + 1 + 2; // 92
+}
+
+/// Config-wide event sequence (seeds `["event_stream"]`, created by the initializers).
+/// Config-level events take their `seq` from here. Buy/redeem events number from their
+/// vault's `VaultLedger::event_seq` and buy-order events from `BuyQueue::event_seq`, so
+/// the hot path never write-locks this account; indexers order events by (stream, seq).
+#[account]
+pub struct EventStream {
+    pub seq: u64, // next event sequence number
+    pub bump: u8,
+}
+impl EventStream {
+    pub const SIZE: usize = 8 + 1;
+}
+
+/// Published fixed-price feed for oracle consumers (seeds `["price_feed"]`),
+/// refreshed by the permissionless `refresh_price`. The layout is stable: fields
+/// are only ever appended, and `version` is bumped when they are.
+#[account]
+pub struct PriceFeed {
+    pub version: u8,
+    pub mint: Pubkey,           // fair token mint
+    pub quote_mint: Pubkey,     // backing mint; Pubkey::default() == native SOL
+    pub price: u64,             // redemption price: quote base units per whole fair token
+    pub price_decimals: u8,     // quote decimals (price / 10^price_decimals quote tokens)
+    pub token_decimals: u8,     // fair token decimals
+    pub backing_ratio_bps: u64, // backing / circulating, 10_000 == 1.0; u64::MAX if none circulating
+    pub backing: u64,           // vault backing, in fair token base units
+    pub circulating: u64,       // `circulating_supply`: outside all vaults and the creator escrow
+    pub last_updated_slot: u64,
+    pub last_updated_ts: i64,
+}
+impl PriceFeed {
+    pub const VERSION: u8 = 1;
+    pub const SIZE: usize = 1 + 32 + 32 + 8 + 1 + 1 + 8 + 8 + 8 + 8 + 8; // 115
+}
+
+// ------------------------- Return data -------------------------
+
+/// Which token path a buy took.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BuyPath {
+    Mint,  // pre-finalization: minted to the buyer
+    Vault, // post-finalization: transferred from the token vault
+}
+
+/// Which token path a redemption took.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RedeemPath {
+    Burn,    // pre-finalization: burned
+    Restock, // post-finalization: moved to the token vault (or to queued buy orders)
+}
+
+/// Return data of `buy_fair_token` / `buy_fair_token_fill`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct BuyResult {
+    pub path: BuyPath,
+    pub amount: u64,   // base units delivered
+    pub lamports: u64, // lamports paid into the SOL vault
+    pub state: PostState,
+}
+
+/// Return data of `redeem_fair_token`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct RedeemResult {
+    pub path: RedeemPath,
+    pub amount: u64,       // base units taken from the user
+    pub lamports: u64,     // lamports paid out of the SOL vault
+    pub queue_filled: u64, // part of `amount` delivered to queued buy orders
+    pub closed: bool,      // user token account closed
+    pub state: PostState,
+}
+
+// ------------------------- PDA helpers -------------------------
+//
+// For CPI callers (`cpi` feature) and off-chain clients. Anchor generates
+// `fair_token::cpi::accounts` itself and it cannot hold extra items, so the static
+// PDAs live here and `cpi_accounts` re-exports both.
+
+pub mod pda {
+    use super::*;
+
+    /// All static (seed-only) PDAs of this program.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct StaticPdas {
+        pub config: Pubkey,
+        pub mint_authority: Pubkey,
+        pub sol_vault: Pubkey,
+        pub token_vault: Pubkey,
+        pub event_authority: Pubkey, // `#[event_cpi]` signer, needed by every emitting instruction
+        pub price_feed: Pubkey,
+        pub primary_ledger: Pubkey, // `VaultLedger` written by primary-vault buys/redeems
+        pub event_stream: Pubkey,   // `EventStream`, written by config-level emitting instructions
+        pub buy_queue: Pubkey,      // `BuyQueue`, served first by post-finalization redeems
+    }
+
+    fn find(seed: &[u8]) -> Pubkey {
+        Pubkey::find_program_address(&[seed], &crate::ID).0
+    }
+
+    pub fn config() -> Pubkey {
+        find(b"config")
+    }
+    pub fn mint_authority() -> Pubkey {
+        find(b"mint_authority")
+    }
+    pub fn sol_vault() -> Pubkey {
+        find(b"sol_vault")
+    }
+    pub fn token_vault() -> Pubkey {
+        find(b"token_vault")
+    }
+    pub fn event_authority() -> Pubkey {
+        find(b"__event_authority")
+    }
+    pub fn price_feed() -> Pubkey {
+        find(b"price_feed")
+    }
+    pub fn event_stream() -> Pubkey {
+        find(b"event_stream")
+    }
+    pub fn buy_queue() -> Pubkey {
+        find(b"buy_queue")
+    }
+    /// Legacy redeemer used by `migrate_from_legacy` (system-owned; holds the
+    /// migration's legacy ATA).
+    pub fn migration_authority() -> Pubkey {
+        find(b"migration")
+    }
+
+    /// SOL vault of vault shard `index`.
+    pub fn sol_vault_shard(index: u8) -> Pubkey {
+        Pubkey::find_program_address(&[b"sol_vault", &[index]], &crate::ID).0
+    }
+    /// `VaultLedger` of vault `index` (shard or `PRIMARY_VAULT`).
+    pub fn vault_ledger(index: u8) -> Pubkey {
+        Pubkey::find_program_address(&[b"vault_ledger", &[index]], &crate::ID).0
+    }
+    /// Token vault account of vault shard `index`.
+    pub fn token_vault_shard(index: u8) -> Pubkey {
+        Pubkey::find_program_address(&[b"token_vault_account", &[index]], &crate::ID).0
+    }
+
+    pub fn static_pdas() -> StaticPdas {
+        StaticPdas {
+            config: config(),
+            mint_authority: mint_authority(),
+            sol_vault: sol_vault(),
+            token_vault: token_vault(),
+            event_authority: event_authority(),
+            price_feed: price_feed(),
+            primary_ledger: vault_ledger(PRIMARY_VAULT),
+            event_stream: event_stream(),
+            buy_queue: buy_queue(),
+        }
+    }
+}
+
+/// Everything a CPI caller needs in one place: Anchor's generated
+/// `fair_token::cpi::accounts` structs plus the static PDA helpers (`pda`), e.g.
+/// `use fair_token::cpi_accounts::{pda, BuyFairToken, RedeemFairToken};`.
+#[cfg(feature = "cpi")]
+pub mod cpi_accounts {
+    pub use crate::cpi::accounts::*;
+    pub use crate::pda;
 }
 
 // ------------------------- Events -------------------------
 
+/// Post-transaction state carried by buy/redeem events, so indexers can rebuild
+/// vault state without extra RPC calls.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct PostState {
+    pub vault: u8, // vault the event belongs to: shard index or `PRIMARY_VAULT`
+    pub seq: u64,  // that vault's `VaultLedger::event_seq` (monotonic per vault, gap detection)
+    pub slot: u64,
+    pub unix_timestamp: i64,
+    pub vault_lamports: u64, // SOL vault lamports (backing vault amount if token-backed)
+    pub token_vault_amount: u64,
+    pub mint_supply: u64,
+    pub user_balance: u64, // user's (or recipient's) token balance; 0 if closed
+}
+
 #[event]
 pub struct InitializedEvent {
+    pub seq: u64,
     pub admin: Pubkey,
     pub mint: Pubkey,
     pub sol_vault: Pubkey,
     pub token_vault: Pubkey,
+    pub backing_mint: Pubkey, // Pubkey::default() == native SOL backing
     pub sale_end: i64,
+    pub creator: Pubkey, // creator allocation beneficiary; Pubkey::default() == none
+    pub creator_allocation_bps: u16,
+    pub referral_reward_bps: u16,   // 0 == referral rewards off
+    pub referral_reward_cap: u64,   // lifetime reward cap per referrer
+    pub distribution_crank: Pubkey, // Pubkey::default() == distributions disabled
 }
 
 #[event]
 pub struct BuyMintEvent {
-    pub buyer: Pubkey,
-    pub amount: u64,   // base units minted
-    pub lamports: u64, // NOTE: amount == lamports (1:1 base units)
+    pub buyer: Pubkey,        // payer of the SOL
+    pub recipient: Pubkey,    // owner of the receiving token account (== buyer unless `buy_for`)
+    pub amount: u64,          // base units minted
+    pub lamports: u64,        // NOTE: amount == lamports (1:1 base units)
+    pub referrer: Pubkey,     // Pubkey::default() == not referred
+    pub referral_reward: u64, // reward accrued to the referrer by this buy
+    pub state: PostState,
 }
 
 #[event]
 pub struct BuyVaultEvent {
-    pub buyer: Pubkey,
-    pub amount: u64,   // base units transferred from vault
-    pub lamports: u64, // NOTE: amount == lamports (1:1 base units)
+    pub buyer: Pubkey,     // payer of the SOL
+    pub recipient: Pubkey, // owner of the receiving token account (== buyer unless `buy_for`)
+    pub amount: u64,       // base units transferred from vault
+    pub lamports: u64,     // NOTE: amount == lamports (1:1 base units)
+    pub requested: u64,    // lamports requested; > amount on a partial fill
     pub finalized: bool,
+    pub referrer: Pubkey,     // Pubkey::default() == not referred
+    pub referral_reward: u64, // reward accrued to the referrer by this buy
+    pub state: PostState,
 }
 
 #[event]
 pub struct RedeemBurnEvent {
     pub redeemer: Pubkey,
-    pub amount: u64,   // base units burned
-    pub lamports: u64, // NOTE: amount == lamports (1:1 base units)
+    pub recipient: Pubkey, // receiver of the SOL (== redeemer unless `redeem_to`)
+    pub amount: u64,       // base units burned
+    pub lamports: u64,     // NOTE: amount == lamports (1:1 base units)
+    pub closed: bool,      // user token account closed after a full redemption
+    pub state: PostState,
 }
 
 #[event]
 pub struct RedeemVaultEvent {
     pub redeemer: Pubkey,
-    pub amount: u64,   // base units moved to vault
-    pub lamports: u64, // NOTE: amount == lamports (1:1 base units)
+    pub recipient: Pubkey, // receiver of the SOL (== redeemer unless `redeem_to`)
+    pub amount: u64,       // base units moved to vault
+    pub lamports: u64,     // NOTE: amount == lamports (1:1 base units)
+    pub closed: bool,      // user token account closed after a full redemption
+    pub state: PostState,
+}
+
+#[event]
+pub struct BuyBackedEvent {
+    pub buyer: Pubkey,
+    pub amount: u64,         // fair base units minted or transferred from vault
+    pub backing_amount: u64, // backing base units paid in
+    pub finalized: bool,     // false: minted, true: transferred from vault
+    pub state: PostState,
+}
+
+#[event]
+pub struct RedeemBackedEvent {
+    pub redeemer: Pubkey,
+    pub amount: u64,         // fair base units burned or moved to vault
+    pub backing_amount: u64, // backing base units paid out
+    pub finalized: bool,     // false: burned, true: moved to vault
+    pub state: PostState,
+}
+
+#[event]
+pub struct BuyShardEvent {
+    pub shard: u8,
+    pub buyer: Pubkey,
+    pub amount: u64,      // base units minted or transferred from the shard
+    pub lamports: u64,    // paid into the shard's SOL vault
+    pub finalized: bool,  // false: minted, true: transferred from the shard
+    pub state: PostState, // vault fields refer to the shard
+}
+
+#[event]
+pub struct LegacyMigratedEvent {
+    pub holder: Pubkey,
+    pub legacy_mint: Pubkey,
+    pub amount: u64, // legacy base units redeemed == successor base units received
+    pub finalized: bool, // false: minted, true: transferred from the vault
+    pub state: PostState, // successor vault / mint / holder balance
+}
+
+#[event]
+pub struct RedeemShardEvent {
+    pub shard: u8,
+    pub redeemer: Pubkey,
+    pub amount: u64,      // base units burned or moved to the shard
+    pub lamports: u64,    // paid out of the shard's SOL vault
+    pub finalized: bool,  // false: burned, true: moved to the shard
+    pub state: PostState, // vault fields refer to the shard
+}
+
+#[event]
+pub struct VaultShardOpenedEvent {
+    pub seq: u64,
+    pub index: u8,
+    pub sol_vault: Pubkey,
+    pub token_vault_account: Pubkey,
+}
+
+#[event]
+pub struct VaultRebalancedEvent {
+    pub seq: u64,
+    pub from: u8, // shard index or PRIMARY_VAULT
+    pub to: u8,
+    pub lamports: u64,
+    pub tokens: u64,
+}
+
+#[event]
+pub struct SolvencyCheckedEvent {
+    pub seq: u64,
+    pub shard_count: u8,
+    pub vault_lamports: u64, // primary + all shards (token-backed: backing in fair base units)
+    pub vault_tokens: u64,   // primary + all shards
+    pub circulating: u64,    // mint supply outside all vaults and the creator escrow
+    pub surplus_lamports: u64, // vault_lamports - circulating (never redeemable 1:1)
+    pub backing_ratio_bps: u64, // vault_lamports / circulating, 10_000 == 1.0
+    pub donated_lamports: u64, // part of the surplus recorded by `donate`
+    pub creator_allocated: u64, // creator supply still in escrow (not circulating)
+}
+
+#[event]
+pub struct DonationEvent {
+    pub seq: u64,
+    pub donor: Pubkey,
+    pub lamports: u64,
+    pub donated_lamports: u64, // `Config.donated_lamports` after this donation
+    pub vault_lamports: u64,   // primary SOL vault backing after this donation
+}
+
+#[event]
+pub struct CreatorAllocationMintedEvent {
+    pub seq: u64,
+    pub beneficiary: Pubkey,
+    pub amount: u64, // unbacked; minted into the vesting escrow
+    pub cliff_ts: i64,
+    pub end_ts: i64,
+}
+
+#[event]
+pub struct CreatorVestingReleasedEvent {
+    pub seq: u64,
+    pub beneficiary: Pubkey,
+    pub backer: Pubkey, // paid `amount` lamports of backing into the SOL vault
+    pub amount: u64,
+    pub released: u64, // cumulative
+    pub total: u64,
+}
+
+#[event]
+pub struct ReferralRewardClaimedEvent {
+    pub seq: u64,
+    pub referrer: Pubkey,
+    pub amount: u64,
+    pub rewards_paid: u64, // cumulative
+    pub rewards_accrued: u64,
+}
+
+#[event]
+pub struct SurplusSnapshotEvent {
+    pub seq: u64,
+    pub epoch: u64,
+    pub lamports: u64,      // surplus moved into the distribution
+    pub circulating: u64,   // pro-rata denominator
+    pub snapshot_slot: u64, // holder balances are taken at this slot
+}
+
+#[event]
+pub struct DistributionRootPublishedEvent {
+    pub seq: u64,
+    pub epoch: u64,
+    pub publisher: Pubkey,
+    pub merkle_root: [u8; 32],
+    pub total_claimable: u64,
+}
+
+#[event]
+pub struct DistributionRootChallengedEvent {
+    pub seq: u64,
+    pub epoch: u64,
+    pub challenger: Pubkey,
+    pub merkle_root: [u8; 32], // the cleared root
+    pub bond: u64,             // lamports escrowed until the crank publishes again
+}
+
+#[event]
+pub struct ChallengeBondSettledEvent {
+    pub seq: u64,
+    pub epoch: u64,
+    pub challenger: Pubkey,
+    pub bond: u64,
+    pub forfeited: bool, // true: same root republished, bond → SOL vault; else refunded
+}
+
+#[event]
+pub struct DistributionClaimedEvent {
+    pub seq: u64,
+    pub epoch: u64,
+    pub claimant: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct DistributionClosedEvent {
+    pub seq: u64,
+    pub epoch: u64,
+    pub claimed: u64,
+    pub returned: u64, // unclaimed lamports returned to the SOL vault
+}
+
+#[event]
+pub struct BuyOrderPlacedEvent {
+    pub seq: u64,
+    pub id: u64,
+    pub buyer: Pubkey,
+    pub lamports: u64,
+}
+
+#[event]
+pub struct BuyOrderFilledEvent {
+    pub seq: u64,
+    pub id: u64,
+    pub buyer: Pubkey,
+    pub redeemer: Pubkey, // the `token_vault` PDA for `fill_buy_orders_from_vault`
+    pub amount: u64,      // base units delivered == lamports moved escrow → SOL vault
+    pub remaining: u64,   // lamports still escrowed
+}
+
+#[event]
+pub struct BuyOrderCancelledEvent {
+    pub seq: u64,
+    pub id: u64,
+    pub buyer: Pubkey,
+    pub refunded: u64, // unfilled escrow returned (plus rent)
+}
+
+#[event]
+pub struct SupplyContractedEvent {
+    pub seq: u64,
+    pub vault: u8,         // vault ledger index (shard or PRIMARY_VAULT)
+    pub burned: u64,       // base units burned from the vault above the ceiling
+    pub vault_amount: u64, // vault inventory after the burn (== ceiling)
 }
 
 #[event]
 pub struct SaleFinalizedEvent {
+    pub seq: u64,
     pub total_supply: u64, // post-finalization supply (pre + shortfall)
 }
 
 #[event]
 pub struct MinimumEnforcedEvent {
+    pub seq: u64,
     pub forced_minted: u64, // base units minted to meet min_supply
 }
 
@@ -734,4 +6290,118 @@ pub enum ErrorCode {
     BadMetadataData,
     #[msg("Token metadata update authority was not revoked (expected None)")]
     MetadataStillMutable,
+    #[msg("Backing mint decimals too far from fair token decimals.")]
+    BackingDecimalsUnsupported,
+    #[msg("Wrong backing mint for this config.")]
+    WrongBackingMint,
+    #[msg("Wrong backing vault for this config.")]
+    WrongBackingVault,
+    #[msg("No backing tokens sent.")]
+    NoBackingSent,
+    #[msg("Not enough backing tokens in vault.")]
+    VaultBackingInsufficient,
+    #[msg("Amount has no exact equivalent in the other token's base units.")]
+    AmountNotRepresentable,
+    #[msg("Arithmetic overflow.")]
+    MathOverflow,
+    #[msg("Token account is not a wSOL (native mint) account.")]
+    NotWsol,
+    #[msg("Insufficient wSOL balance.")]
+    InsufficientWsol,
+    #[msg("Signer is not the token account's delegate.")]
+    NotDelegate,
+    #[msg("Amount exceeds the delegated allowance.")]
+    DelegateAllowanceExceeded,
+    #[msg("SOL destination does not match the owner's redeem destination.")]
+    WrongRedeemDestination,
+    #[msg("min_fill must not exceed the requested amount.")]
+    InvalidMinFill,
+    #[msg("Vault inventory cannot fill the minimum requested amount.")]
+    FillBelowMinimum,
+    #[msg("Sale is not finalized yet.")]
+    SaleNotFinalized,
+    #[msg("Malformed buy queue remaining accounts.")]
+    BadQueueAccounts,
+    #[msg("Signer does not own this buy order.")]
+    NotOrderOwner,
+    #[msg("Vault shards require native SOL backing.")]
+    ShardsRequireSolBacking,
+    #[msg("Invalid or unopened vault shard.")]
+    InvalidShard,
+    #[msg("Vault shard accounts missing or out of order.")]
+    BadShardAccounts,
+    #[msg("Sale ended; call `finalize` first.")]
+    FinalizationPending,
+    #[msg("Vaults do not cover the circulating supply.")]
+    Insolvent,
+    #[msg("Sale already finalized.")]
+    AlreadyFinalized,
+    #[msg("Sale has not ended yet.")]
+    SaleNotEnded,
+    #[msg("Legacy program account is wrong (or is this program).")]
+    InvalidLegacyProgram,
+    #[msg("Legacy redemption paid a different amount of SOL.")]
+    LegacyRedeemMismatch,
+    #[msg("Migration requires native SOL backing.")]
+    MigrationRequiresSolBacking,
+    #[msg("Donations require native SOL backing.")]
+    DonationRequiresSolBacking,
+    #[msg("Surplus distribution requires native SOL backing.")]
+    DistributionRequiresSolBacking,
+    #[msg("Distribution epoch does not match config.")]
+    WrongDistributionEpoch,
+    #[msg("Last surplus snapshot is too recent.")]
+    DistributionTooSoon,
+    #[msg("No surplus above the 1:1 backing.")]
+    NoSurplus,
+    #[msg("Distribution root already published.")]
+    DistributionRootPublished,
+    #[msg("Distribution root not published yet.")]
+    DistributionRootMissing,
+    #[msg("Claims exceed the snapshotted surplus.")]
+    DistributionExceedsSurplus,
+    #[msg("Invalid Merkle proof.")]
+    InvalidMerkleProof,
+    #[msg("Distribution claim window is still open.")]
+    DistributionClaimWindowOpen,
+    #[msg("Invalid creator allocation (share, beneficiary or vesting schedule).")]
+    InvalidCreatorAllocation,
+    #[msg("No creator allocation configured.")]
+    NoCreatorAllocation,
+    #[msg("Creator vesting accounts missing (run open_creator_vesting).")]
+    CreatorVestingMissing,
+    #[msg("Nothing vested to release.")]
+    NothingVested,
+    #[msg("Invalid referral rewards (rate or cap).")]
+    InvalidReferralRewards,
+    #[msg("Buyer cannot refer themselves.")]
+    SelfReferral,
+    #[msg("No referral rewards claimable from the pool.")]
+    NoReferralRewards,
+    #[msg("Batch amounts and recipient token accounts do not match.")]
+    BadBatchAccounts,
+    #[msg("Token-2022 backing mints with extensions are not supported.")]
+    BackingMintExtensionsUnsupported,
+    #[msg("The buy queue must be writable when buy orders are passed to fill.")]
+    BuyQueueNotWritable,
+    #[msg("Distribution root is still in its challenge window.")]
+    DistributionChallengeOpen,
+    #[msg("Distribution root challenge window has closed.")]
+    DistributionChallengeClosed,
+    #[msg("Challenger holds too little of the snapshot supply.")]
+    ChallengeStakeTooSmall,
+    #[msg("A creator allocation requires native SOL backing.")]
+    CreatorAllocationRequiresSolBacking,
+    #[msg("The mint must be writable when the instruction mints or burns.")]
+    MintNotWritable,
+    #[msg("Buy order is below the minimum size.")]
+    BuyOrderTooSmall,
+    #[msg("The buy queue is full.")]
+    BuyQueueFull,
+    #[msg("No distribution crank configured.")]
+    NoDistributionCrank,
+    #[msg("Only the distribution crank may publish distribution roots.")]
+    NotDistributionCrank,
+    #[msg("Pass the pending challenger's account to settle its bond.")]
+    WrongChallenger,
 }
@@ -0,0 +1,219 @@
+// SPDX-License-Identifier: CC0-1.0
+// fair_token_consumer/src/lib.rs
+//
+// Example consumer of the fair_token CPI API (`fair_token = { features = ["cpi"] }`).
+// Buys and redeems fair tokens atomically, either for a signing user or for
+// this program's `["treasury"]` PDA (e.g. a payroll treasury).
+#![allow(deprecated)]
+#![allow(unexpected_cfgs)]
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+use fair_token::cpi_accounts::{pda, BuyFairToken, RedeemFairToken};
+use fair_token::program::FairToken;
+use fair_token::{BuyResult, RedeemResult};
+
+declare_id!("EBWLVqm8gokdD2wdqm5HZc3e2yxvqr9AQbheFHzTv21d");
+
+#[program]
+pub mod fair_token_consumer {
+    use super::*;
+
+    /// Buy through CPI; the signing user is the fair_token buyer.
+    pub fn buy(ctx: Context<UserCpi>, lamports: u64) -> Result<()> {
+        let accounts = buy_accounts(
+            &ctx.accounts.fair_token,
+            ctx.accounts.user.to_account_info(),
+            ctx.accounts.user_token_account.to_account_info(),
+        );
+        let cpi_ctx = CpiContext::new(ctx.accounts.fair_token.program.to_account_info(), accounts);
+        let result = fair_token::cpi::buy_fair_token(cpi_ctx, lamports)?.get();
+        check_buy(&result, lamports)
+    }
+
+    /// Redeem through CPI; the signing user is the fair_token redeemer. `buy_queue`
+    /// goes readonly, so the redeem restocks the vault and queued buy orders are left
+    /// to `fill_buy_orders_from_vault`.
+    pub fn redeem(ctx: Context<UserCpi>, amount: u64) -> Result<()> {
+        let accounts = redeem_accounts(
+            &ctx.accounts.fair_token,
+            ctx.accounts.user.to_account_info(),
+            ctx.accounts.user_token_account.to_account_info(),
+        );
+        let cpi_ctx = CpiContext::new(ctx.accounts.fair_token.program.to_account_info(), accounts);
+        let result = fair_token::cpi::redeem_fair_token(cpi_ctx, amount)?.get();
+        check_redeem(&result, amount)
+    }
+
+    /// Buy through CPI with the treasury PDA as buyer (SOL from the treasury,
+    /// tokens to the treasury's token account).
+    pub fn treasury_buy(ctx: Context<TreasuryCpi>, lamports: u64) -> Result<()> {
+        let bump = [ctx.bumps.treasury];
+        let seeds: &[&[u8]] = &[b"treasury", &bump];
+        let signer = &[seeds];
+
+        let accounts = buy_accounts(
+            &ctx.accounts.fair_token,
+            ctx.accounts.treasury.to_account_info(),
+            ctx.accounts.treasury_token_account.to_account_info(),
+        );
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.fair_token.program.to_account_info(),
+            accounts,
+            signer,
+        );
+        let result = fair_token::cpi::buy_fair_token(cpi_ctx, lamports)?.get();
+        check_buy(&result, lamports)
+    }
+
+    /// Redeem through CPI with the treasury PDA as redeemer (SOL back to the treasury).
+    /// Queued buy orders are left to the crank, as in `redeem`.
+    pub fn treasury_redeem(ctx: Context<TreasuryCpi>, amount: u64) -> Result<()> {
+        let bump = [ctx.bumps.treasury];
+        let seeds: &[&[u8]] = &[b"treasury", &bump];
+        let signer = &[seeds];
+
+        let accounts = redeem_accounts(
+            &ctx.accounts.fair_token,
+            ctx.accounts.treasury.to_account_info(),
+            ctx.accounts.treasury_token_account.to_account_info(),
+        );
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.fair_token.program.to_account_info(),
+            accounts,
+            signer,
+        );
+        let result = fair_token::cpi::redeem_fair_token(cpi_ctx, amount)?.get();
+        check_redeem(&result, amount)
+    }
+}
+
+// ---------- CPI plumbing ----------
+
+fn buy_accounts<'info>(
+    ft: &FairTokenAccounts<'info>,
+    buyer: AccountInfo<'info>,
+    user_token_account: AccountInfo<'info>,
+) -> BuyFairToken<'info> {
+    BuyFairToken {
+        buyer,
+        mint: ft.mint.to_account_info(),
+        mint_authority: ft.mint_authority.to_account_info(),
+        config: ft.config.to_account_info(),
+        ledger: ft.ledger.to_account_info(),
+        sol_vault: ft.sol_vault.to_account_info(),
+        token_vault: ft.token_vault.to_account_info(),
+        token_vault_account: ft.token_vault_account.to_account_info(),
+        user_token_account,
+        token_program: ft.token_program.to_account_info(),
+        system_program: ft.system_program.to_account_info(),
+        referral: None,
+        event_authority: ft.event_authority.to_account_info(),
+        program: ft.program.to_account_info(),
+    }
+}
+
+fn redeem_accounts<'info>(
+    ft: &FairTokenAccounts<'info>,
+    redeemer: AccountInfo<'info>,
+    user_token_account: AccountInfo<'info>,
+) -> RedeemFairToken<'info> {
+    RedeemFairToken {
+        redeemer,
+        mint: ft.mint.to_account_info(),
+        mint_authority: ft.mint_authority.to_account_info(),
+        config: ft.config.to_account_info(),
+        ledger: ft.ledger.to_account_info(),
+        buy_queue: ft.buy_queue.to_account_info(),
+        sol_vault: ft.sol_vault.to_account_info(),
+        token_vault: ft.token_vault.to_account_info(),
+        token_vault_account: ft.token_vault_account.to_account_info(),
+        user_token_account,
+        token_program: ft.token_program.to_account_info(),
+        system_program: ft.system_program.to_account_info(),
+        event_authority: ft.event_authority.to_account_info(),
+        program: ft.program.to_account_info(),
+    }
+}
+
+// 1 lamport == 1 base unit on every path; the return data lets us assert it.
+fn check_buy(result: &BuyResult, lamports: u64) -> Result<()> {
+    require!(
+        result.amount == lamports && result.lamports == lamports,
+        ConsumerError::UnexpectedResult
+    );
+    Ok(())
+}
+
+fn check_redeem(result: &RedeemResult, amount: u64) -> Result<()> {
+    require!(
+        result.amount == amount && result.lamports == amount,
+        ConsumerError::UnexpectedResult
+    );
+    Ok(())
+}
+
+// ------------------------- Accounts -------------------------
+
+/// fair_token accounts shared by buy and redeem. fair_token validates them
+/// itself; the static PDAs are pinned here via `fair_token::pda` anyway.
+#[derive(Accounts)]
+pub struct FairTokenAccounts<'info> {
+    /// CHECK: validated by fair_token (config `has_one = mint`)
+    #[account(mut)]
+    pub mint: UncheckedAccount<'info>,
+    /// CHECK: static fair_token PDA
+    #[account(address = pda::mint_authority())]
+    pub mint_authority: UncheckedAccount<'info>,
+    /// CHECK: static fair_token PDA (read-only on buy/redeem)
+    #[account(address = pda::config())]
+    pub config: UncheckedAccount<'info>,
+    /// CHECK: static fair_token PDA (primary vault ledger)
+    #[account(mut, address = pda::vault_ledger(fair_token::PRIMARY_VAULT))]
+    pub ledger: UncheckedAccount<'info>,
+    /// CHECK: static fair_token PDA (buy queue; readonly, nothing is filled through here)
+    #[account(address = pda::buy_queue())]
+    pub buy_queue: UncheckedAccount<'info>,
+    /// CHECK: static fair_token PDA
+    #[account(mut, address = pda::sol_vault())]
+    pub sol_vault: UncheckedAccount<'info>,
+    /// CHECK: static fair_token PDA
+    #[account(mut, address = pda::token_vault())]
+    pub token_vault: UncheckedAccount<'info>,
+    /// CHECK: validated by fair_token (config `has_one = token_vault_account`)
+    #[account(mut)]
+    pub token_vault_account: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    /// CHECK: static fair_token PDA (`#[event_cpi]` signer)
+    #[account(address = pda::event_authority())]
+    pub event_authority: UncheckedAccount<'info>,
+    pub program: Program<'info, FairToken>,
+}
+
+#[derive(Accounts)]
+pub struct UserCpi<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(mut, token::authority = user)]
+    pub user_token_account: Account<'info, TokenAccount>,
+    pub fair_token: FairTokenAccounts<'info>,
+}
+
+#[derive(Accounts)]
+pub struct TreasuryCpi<'info> {
+    /// System-owned PDA holding the treasury's SOL; funded by plain transfers.
+    #[account(mut, seeds = [b"treasury"], bump)]
+    pub treasury: SystemAccount<'info>,
+    #[account(mut, token::authority = treasury)]
+    pub treasury_token_account: Account<'info, TokenAccount>,
+    pub fair_token: FairTokenAccounts<'info>,
+}
+
+// ------------------------- Errors -------------------------
+
+#[error_code]
+pub enum ConsumerError {
+    #[msg("fair_token returned an unexpected result")]
+    UnexpectedResult,
+}
@@ -0,0 +1,137 @@
+// scripts/distribution.ts
+// Reference crank for surplus distribution: snapshot the vault surplus, build the
+// holders' Merkle tree, publish its root (the wallet must be the distribution crank
+// fixed at initialize) and claim the wallet's share.
+// Holder balances are read right after the snapshot; run it promptly after
+// `snapshot_surplus` so they match `snapshot_slot`. Claims open only after the root's
+// challenge window (`DISTRIBUTION_CHALLENGE_SECS`): run `--claim` again later.
+// The claim / double-claim / bad-proof paths are tested in tests/local/distribution.spec.ts.
+// Usage: ts-node scripts/distribution.ts <MINT_PUBKEY> [--snapshot] [--claim] [--out tree.json]
+import fs from "fs";
+import path from "path";
+import * as anchor from "@coral-xyz/anchor";
+import { BN } from "bn.js";
+import { PublicKey, Keypair, AccountMeta } from "@solana/web3.js";
+import { TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import { leafHash, merkle } from "./merkle";
+
+const flag = (name: string) => process.argv.includes(name);
+function argStr(name: string) { const i = process.argv.indexOf(name); return (i>=0 && process.argv[i+1]) ? process.argv[i+1] : undefined; }
+
+const u64le = (n: bigint) => { const b = Buffer.alloc(8); b.writeBigUInt64LE(n); return b; };
+
+async function main() {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const connection = provider.connection;
+  const user = (provider.wallet as any).payer as Keypair;
+
+  const MINT = new PublicKey(process.argv[2] ?? process.env.MINT ?? (() => { throw new Error("Pass <MINT_PUBKEY>"); })());
+  const idl = JSON.parse(fs.readFileSync(path.resolve(__dirname, "../target/idl/fair_token.json"), "utf8"));
+  const program = new anchor.Program(idl, provider);
+  const pid = program.programId;
+  const pda = (...seeds: (Buffer | Uint8Array)[]) => PublicKey.findProgramAddressSync(seeds, pid)[0];
+  const configPda = pda(Buffer.from("config"));
+
+  let cfg: any = await (program.account as any).config.fetch(configPda);
+  // Token accounts held by fair_token PDAs (vault and shards, creator escrow, migration)
+  // are program inventory or escrow, not holders
+  const programOwners = new Set(["token_vault", "creator_vesting", "migration"].map(s => pda(Buffer.from(s)).toBase58()));
+  const shardPairs: AccountMeta[] = [];
+  const vaultAccounts = new Set<string>([cfg.tokenVaultAccount.toBase58()]);
+  for (let i = 0; i < cfg.shardCount; i++) {
+    const tokenShard = pda(Buffer.from("token_vault_account"), Buffer.from([i]));
+    vaultAccounts.add(tokenShard.toBase58());
+    shardPairs.push(
+      { pubkey: pda(Buffer.from("sol_vault"), Buffer.from([i])), isWritable: false, isSigner: false },
+      { pubkey: tokenShard, isWritable: false, isSigner: false },
+    );
+  }
+
+  // ---- 1) snapshot (permissionless) ----
+  if (flag("--snapshot")) {
+    const sig = await (program.methods as any).snapshotSurplus(cfg.distributionEpoch)
+      .accountsPartial({ payer: user.publicKey, config: configPda, mint: MINT, solVault: cfg.solVault, tokenVaultAccount: cfg.tokenVaultAccount })
+      .remainingAccounts(shardPairs)
+      .rpc();
+    console.log(`✅ snapshot epoch ${cfg.distributionEpoch}: ${sig}`);
+    cfg = await (program.account as any).config.fetch(configPda);
+  }
+  if (new BN(cfg.distributionEpoch).isZero()) throw new Error("No distribution yet (run with --snapshot)");
+  const epoch = BigInt(new BN(cfg.distributionEpoch).subn(1).toString());
+  const distributionPda = pda(Buffer.from("distribution"), u64le(epoch));
+  const dist: any = await (program.account as any).distribution.fetch(distributionPda);
+  const lamports = BigInt(dist.lamports.toString());
+  console.log(`epoch ${epoch}: ${lamports} lamports, circulating ${dist.circulating} @ slot ${dist.snapshotSlot}`);
+
+  // ---- 2) holder shares (vault inventory is not circulating) ----
+  const accounts = await connection.getParsedProgramAccounts(TOKEN_PROGRAM_ID, {
+    filters: [{ dataSize: 165 }, { memcmp: { offset: 0, bytes: MINT.toBase58() } }],
+  });
+  const balances = new Map<string, bigint>();
+  for (const { pubkey, account } of accounts) {
+    const info = (account.data as any).parsed.info;
+    if (vaultAccounts.has(pubkey.toBase58()) || programOwners.has(info.owner)) continue;
+    const amount = BigInt(info.tokenAmount.amount);
+    if (amount > 0n) balances.set(info.owner, (balances.get(info.owner) ?? 0n) + amount);
+  }
+  // Balances read after the snapshot can exceed its circulating supply; never promise more than the surplus.
+  let denom = BigInt(dist.circulating.toString());
+  const held = [...balances.values()].reduce((a, b) => a + b, 0n);
+  if (held > denom) denom = held;
+
+  const leaves = [...balances.entries()]
+    .map(([owner, bal]) => ({ claimant: new PublicKey(owner), amount: lamports * bal / denom }))
+    .filter(l => l.amount > 0n)
+    .sort((a, b) => a.claimant.toBase58().localeCompare(b.claimant.toBase58()));
+  if (!leaves.length) throw new Error("No holder share above zero");
+  const total = leaves.reduce((a, l) => a + l.amount, 0n);
+  if (total > lamports) throw new Error(`Shares ${total} exceed surplus ${lamports}`);
+  const { root, proofs } = merkle(leaves.map(l => leafHash(epoch, l.claimant, l.amount)));
+  console.log(`${leaves.length} holders, ${total} lamports claimable, root ${root.toString("hex")}`);
+
+  if (argStr("--out")) {
+    fs.writeFileSync(argStr("--out")!, JSON.stringify({
+      epoch: epoch.toString(), root: root.toString("hex"), total: total.toString(),
+      leaves: leaves.map((l, i) => ({
+        claimant: l.claimant.toBase58(), amount: l.amount.toString(), proof: proofs[i].map(p => p.toString("hex")),
+      })),
+    }, null, 2));
+  }
+
+  // ---- 3) publish the root (distribution crank only; challengeable for DISTRIBUTION_CHALLENGE_SECS) ----
+  const published = Buffer.from(dist.merkleRoot);
+  if (published.equals(Buffer.alloc(32)) && !cfg.distributionCrank.equals(user.publicKey)) {
+    console.log(`⏳ no root yet; waiting for the distribution crank ${cfg.distributionCrank.toBase58()}`);
+  } else if (published.equals(Buffer.alloc(32))) {
+    await (program.methods as any).publishDistributionRoot([...root], new BN(total.toString()))
+      .accountsPartial({
+        publisher: user.publicKey, config: configPda, solVault: cfg.solVault, distribution: distributionPda,
+        // a pending challenge is refunded when the rebuilt root differs from the one it cleared
+        challenger: dist.challenger.equals(PublicKey.default) ? null : dist.challenger,
+      })
+      .rpc();
+    console.log("✅ root published");
+  } else if (!published.equals(root)) {
+    console.log("⚠️  published root differs from the rebuilt tree (balances changed since the snapshot?)");
+  }
+
+  // ---- 4) claim the wallet's share ----
+  if (flag("--claim")) {
+    const i = leaves.findIndex(l => l.claimant.equals(user.publicKey));
+    if (i < 0) throw new Error("Wallet has no share in this epoch");
+    const before = BigInt(await connection.getBalance(user.publicKey, "confirmed"));
+    const claimPda = pda(Buffer.from("distribution_claim"), u64le(epoch), user.publicKey.toBuffer());
+    await (program.methods as any).claimDistribution(new BN(epoch.toString()), new BN(leaves[i].amount.toString()), proofs[i].map(p => [...p]))
+      .accountsPartial({ claimant: user.publicKey, config: configPda, distribution: distributionPda, claim: claimPda })
+      .rpc();
+    const rent = BigInt(await connection.getMinimumBalanceForRentExemption(8 + 48));
+    const after = BigInt(await connection.getBalance(user.publicKey, "confirmed"));
+    // Fee is the only other debit; the claim must be within a fee of amount - receipt rent.
+    const net = after - before + rent;
+    if (net > leaves[i].amount || leaves[i].amount - net > 100_000n) throw new Error(`Claimed ${net}, want ~${leaves[i].amount}`);
+    console.log(`✅ claimed ${leaves[i].amount} lamports`);
+  }
+}
+
+main().catch((e) => { console.error(e); process.exit(1); });
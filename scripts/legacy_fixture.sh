@@ -0,0 +1,22 @@
+#!/usr/bin/env bash
+# Fetch the legacy (audited v1) program binary for the local migration tests.
+#   scripts/legacy_fixture.sh           dump the deployed binary (mainnet-beta; CLUSTER=... to override)
+#   scripts/legacy_fixture.sh --build   rebuild it from the audited source (baseline commit)
+# Output: tests/fixtures/legacy_fair_token.so (gitignored)
+set -euo pipefail
+
+LEGACY_PROGRAM_ID=EGxd8LCM8Y1uMyXrWWapEMh9tH2whZaNBYhaV29Mq9fb
+AUDITED_REV=${AUDITED_REV:-70ae613}
+ROOT=$(git rev-parse --show-toplevel)
+OUT="$ROOT/tests/fixtures/legacy_fair_token.so"
+
+if [ "${1:-}" = "--build" ]; then
+  WORK=$(mktemp -d)
+  trap 'git -C "$ROOT" worktree remove --force "$WORK"' EXIT
+  git -C "$ROOT" worktree add --detach "$WORK" "$AUDITED_REV"
+  (cd "$WORK" && anchor build --verifiable)
+  cp "$WORK/target/verifiable/fair_token.so" "$OUT"
+else
+  solana program dump -u "${CLUSTER:-mainnet-beta}" "$LEGACY_PROGRAM_ID" "$OUT"
+fi
+shasum -a 256 "$OUT"
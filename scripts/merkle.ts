@@ -0,0 +1,31 @@
+// scripts/merkle.ts
+// Distribution Merkle tree, same encoding as `verify_distribution_proof` in lib.rs.
+// Shared by the reference crank (scripts/distribution.ts) and tests/local.
+import crypto from "crypto";
+import { PublicKey } from "@solana/web3.js";
+
+const sha256 = (...parts: Buffer[]) => crypto.createHash("sha256").update(Buffer.concat(parts)).digest();
+const u64le = (n: bigint) => { const b = Buffer.alloc(8); b.writeBigUInt64LE(n); return b; };
+
+export const leafHash = (epoch: bigint, claimant: PublicKey, amount: bigint) =>
+  sha256(Buffer.from([0]), u64le(epoch), claimant.toBuffer(), u64le(amount));
+const nodeHash = (a: Buffer, b: Buffer) =>
+  Buffer.compare(a, b) <= 0 ? sha256(Buffer.from([1]), a, b) : sha256(Buffer.from([1]), b, a);
+
+// Sorted-pair tree; an odd node is carried up unchanged. Returns root and per-leaf proofs.
+export function merkle(leaves: Buffer[]): { root: Buffer; proofs: Buffer[][] } {
+  const proofs: Buffer[][] = leaves.map(() => []);
+  let level = leaves.map((h, i) => ({ h, members: [i] }));
+  while (level.length > 1) {
+    const next: typeof level = [];
+    for (let i = 0; i < level.length; i += 2) {
+      const a = level[i], b = level[i + 1];
+      if (!b) { next.push(a); continue; }
+      a.members.forEach(m => proofs[m].push(b.h));
+      b.members.forEach(m => proofs[m].push(a.h));
+      next.push({ h: nodeHash(a.h, b.h), members: [...a.members, ...b.members] });
+    }
+    level = next;
+  }
+  return { root: level[0].h, proofs };
+}
@@ -27,6 +27,10 @@ const PDAS = (pid: PublicKey) => ({
   mintAuthority: PublicKey.findProgramAddressSync([Buffer.from("mint_authority")], pid)[0],
   solVault:      PublicKey.findProgramAddressSync([Buffer.from("sol_vault")],      pid)[0],
   tokenVault:    PublicKey.findProgramAddressSync([Buffer.from("token_vault")],    pid)[0],
+  eventAuthority: PublicKey.findProgramAddressSync([Buffer.from("__event_authority")], pid)[0],
+  buyQueue:      PublicKey.findProgramAddressSync([Buffer.from("buy_queue")],      pid)[0],
+  // primary-vault ledger (PRIMARY_VAULT = 255)
+  ledger:        PublicKey.findProgramAddressSync([Buffer.from("vault_ledger"), Buffer.from([255])], pid)[0],
 });
 
 // ---- raw-instruction helpers ----
@@ -76,8 +80,8 @@ async function main() {
   const coder  = new anchor.BorshCoder(idlRaw);
 
   // Find instructions (by fuzzy name)
-  const buyIxIdl    = idlRaw.instructions.find((i: any) => /buy/i.test(i.name)) as IdlInstr | undefined;
-  const redeemIxIdl = idlRaw.instructions.find((i: any) => /redeem/i.test(i.name)) as IdlInstr | undefined;
+  const buyIxIdl    = idlRaw.instructions.find((i: any) => norm(i.name) === "buyfairtoken") as IdlInstr | undefined;
+  const redeemIxIdl = idlRaw.instructions.find((i: any) => norm(i.name) === "redeemfairtoken") as IdlInstr | undefined;
   if (!buyIxIdl || !redeemIxIdl) throw new Error(`buy/redeem not found in IDL. Found: ${idlRaw.instructions.map((i:any)=>i.name).join(", ")}`);
 
   const p = PDAS(PROGRAM_ID);
@@ -128,7 +132,10 @@ async function main() {
     mint: { pubkey: MINT, isWritable: true },
     mint_authority: { pubkey: p.mintAuthority },
     mintAuthority: { pubkey: p.mintAuthority },
-    config: { pubkey: p.config, isWritable: true },
+    config: { pubkey: p.config },
+    ledger: { pubkey: p.ledger, isWritable: true },
+    buy_queue: { pubkey: p.buyQueue }, // readonly: no queued orders are passed to fill
+    buyQueue: { pubkey: p.buyQueue },
     sol_vault: { pubkey: cfgSolVault, isWritable: true },
     solVault: { pubkey: cfgSolVault, isWritable: true },
     token_vault: { pubkey: cfgTokenVault, isWritable: true },
@@ -141,6 +148,10 @@ async function main() {
     tokenProgram: { pubkey: TOKEN_PROGRAM_ID },
     system_program: { pubkey: SystemProgram.programId },
     systemProgram: { pubkey: SystemProgram.programId },
+    event_authority: { pubkey: p.eventAuthority },
+    eventAuthority: { pubkey: p.eventAuthority },
+    program: { pubkey: PROGRAM_ID },
+    referral: { pubkey: PROGRAM_ID, isWritable: false }, // optional account omitted (program id placeholder)
   } as Record<string, { pubkey: PublicKey; isWritable?: boolean; isSigner?: boolean }>;
 
   const nameMap = (idlAccs: any[]) => {
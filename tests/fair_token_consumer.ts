@@ -0,0 +1,107 @@
+// tests/fair_token_consumer.ts
+// Buy and redeem through CPI from the example consumer program, with a signing
+// user and with the consumer's `["treasury"]` PDA as buyer/redeemer.
+// Usage: ts-node tests/fair_token_consumer.ts <MINT_PUBKEY> [--buy 0.25] [--redeem 0.10]
+import fs from "fs";
+import path from "path";
+import * as anchor from "@coral-xyz/anchor";
+import { BN } from "bn.js";
+import { PublicKey, SystemProgram, Keypair, Transaction, sendAndConfirmTransaction } from "@solana/web3.js";
+import { TOKEN_PROGRAM_ID, getOrCreateAssociatedTokenAccount, getAccount } from "@solana/spl-token";
+
+const LAMPORTS_PER_TOKEN = 1_000_000_000;
+
+function argNum(name: string, def: number) { const i = process.argv.indexOf(name); return (i>=0 && process.argv[i+1]) ? parseFloat(process.argv[i+1]) : def; }
+
+function loadIdl(name: string) {
+  return JSON.parse(fs.readFileSync(path.resolve(__dirname, `../target/idl/${name}.json`), "utf8"));
+}
+
+// Same seeds as `fair_token::pda`
+const PDAS = (pid: PublicKey) => ({
+  config:         PublicKey.findProgramAddressSync([Buffer.from("config")],            pid)[0],
+  mintAuthority:  PublicKey.findProgramAddressSync([Buffer.from("mint_authority")],    pid)[0],
+  solVault:       PublicKey.findProgramAddressSync([Buffer.from("sol_vault")],         pid)[0],
+  tokenVault:     PublicKey.findProgramAddressSync([Buffer.from("token_vault")],       pid)[0],
+  eventAuthority: PublicKey.findProgramAddressSync([Buffer.from("__event_authority")], pid)[0],
+  ledger:         PublicKey.findProgramAddressSync([Buffer.from("vault_ledger"), Buffer.from([255])], pid)[0],
+  buyQueue:       PublicKey.findProgramAddressSync([Buffer.from("buy_queue")],         pid)[0],
+});
+
+function assertEq(label: string, got: bigint, want: bigint) {
+  if (got !== want) throw new Error(`${label}: got ${got}, want ${want}`);
+  console.log(`✅ ${label}: ${got}`);
+}
+
+async function main() {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const connection = provider.connection;
+  const user = (provider.wallet as any).payer as Keypair;
+
+  const MINT = new PublicKey(process.argv[2] ?? process.env.MINT ?? (() => { throw new Error("Pass <MINT_PUBKEY>"); })());
+  const buyLamports    = BigInt(Math.round(argNum("--buy", 0.25) * LAMPORTS_PER_TOKEN));
+  const redeemLamports = BigInt(Math.round(argNum("--redeem", 0.10) * LAMPORTS_PER_TOKEN));
+
+  const fairToken = new anchor.Program(loadIdl("fair_token"), provider);
+  const consumer  = new anchor.Program(loadIdl("fair_token_consumer"), provider);
+  const p = PDAS(fairToken.programId);
+
+  const cfg: any = await (fairToken.account as any).config.fetch(p.config);
+  const fairTokenAccounts = {
+    mint: MINT,
+    mintAuthority: p.mintAuthority,
+    config: p.config,
+    ledger: p.ledger,
+    buyQueue: p.buyQueue,
+    solVault: p.solVault,
+    tokenVault: p.tokenVault,
+    tokenVaultAccount: cfg.tokenVaultAccount as PublicKey,
+    tokenProgram: TOKEN_PROGRAM_ID,
+    systemProgram: SystemProgram.programId,
+    eventAuthority: p.eventAuthority,
+    program: fairToken.programId,
+  };
+
+  const balance = async (ta: PublicKey) => (await getAccount(connection, ta, "confirmed")).amount;
+
+  // ---- 1) signing user buys and redeems through the consumer ----
+  const userAta = await getOrCreateAssociatedTokenAccount(connection, user, MINT, user.publicKey);
+  const u0 = await balance(userAta.address);
+  await consumer.methods.buy(new BN(buyLamports.toString()))
+    .accountsStrict({ user: user.publicKey, userTokenAccount: userAta.address, fairToken: fairTokenAccounts })
+    .rpc();
+  assertEq("user balance after CPI buy", await balance(userAta.address), u0 + buyLamports);
+
+  await consumer.methods.redeem(new BN(redeemLamports.toString()))
+    .accountsStrict({ user: user.publicKey, userTokenAccount: userAta.address, fairToken: fairTokenAccounts })
+    .rpc();
+  assertEq("user balance after CPI redeem", await balance(userAta.address), u0 + buyLamports - redeemLamports);
+
+  // ---- 2) treasury PDA buys and redeems (PDA signs via invoke_signed) ----
+  const [treasury] = PublicKey.findProgramAddressSync([Buffer.from("treasury")], consumer.programId);
+  const treasuryAta = await getOrCreateAssociatedTokenAccount(connection, user, MINT, treasury, true);
+
+  // Fund the treasury with the purchase amount plus headroom (it stays system-owned)
+  await sendAndConfirmTransaction(connection, new Transaction().add(
+    SystemProgram.transfer({ fromPubkey: user.publicKey, toPubkey: treasury, lamports: Number(buyLamports) + 10_000_000 })
+  ), [user]);
+
+  const t0 = await balance(treasuryAta.address);
+  const sol0 = BigInt(await connection.getBalance(treasury, "confirmed"));
+  await consumer.methods.treasuryBuy(new BN(buyLamports.toString()))
+    .accountsStrict({ treasury, treasuryTokenAccount: treasuryAta.address, fairToken: fairTokenAccounts })
+    .rpc();
+  assertEq("treasury balance after CPI buy", await balance(treasuryAta.address), t0 + buyLamports);
+  assertEq("treasury SOL after CPI buy", BigInt(await connection.getBalance(treasury, "confirmed")), sol0 - buyLamports);
+
+  await consumer.methods.treasuryRedeem(new BN(redeemLamports.toString()))
+    .accountsStrict({ treasury, treasuryTokenAccount: treasuryAta.address, fairToken: fairTokenAccounts })
+    .rpc();
+  assertEq("treasury balance after CPI redeem", await balance(treasuryAta.address), t0 + buyLamports - redeemLamports);
+  assertEq("treasury SOL after CPI redeem", BigInt(await connection.getBalance(treasury, "confirmed")), sol0 - buyLamports + redeemLamports);
+
+  console.log("\nCPI buy/redeem OK");
+}
+
+main().catch((e) => { console.error(e); process.exit(1); });
@@ -65,6 +65,21 @@ function i64LeBuf(v: bigint | number): Buffer {
     [Buffer.from("token_vault")],
     PROGRAM_ID
   );
+  const [eventStreamPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("event_stream")],
+    PROGRAM_ID
+  );
+  // primary-vault ledger (PRIMARY_VAULT = 255)
+  const [ledgerPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("vault_ledger"), Buffer.from([255])],
+    PROGRAM_ID
+  );
+  // Metaplex metadata for the mint; must already exist with its update authority revoked
+  const TOKEN_METADATA_ID = new PublicKey("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+  const [metadataPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("metadata"), TOKEN_METADATA_ID.toBuffer(), mint.toBuffer()],
+    TOKEN_METADATA_ID
+  );
 
   // token_vault_account is created via `init` on-chain => must be a fresh signer here
   const tokenVaultAccount = Keypair.generate();
@@ -73,21 +88,32 @@ function i64LeBuf(v: bigint | number): Buffer {
   console.log("Mint :", mint.toBase58());
   console.log("PDA  (mint_authority):", mintAuthorityPda.toBase58());
   console.log("PDA  (config)        :", configPda.toBase58());
+  console.log("PDA  (event_stream)  :", eventStreamPda.toBase58());
+  console.log("PDA  (ledger)        :", ledgerPda.toBase58());
   console.log("PDA  (sol_vault)     :", solVaultPda.toBase58());
   console.log("PDA  (token_vault)   :", tokenVaultPda.toBase58());
   console.log("token_vault_account  :", tokenVaultAccount.publicKey.toBase58());
+  console.log("metadata             :", metadataPda.toBase58());
   console.log("sale_end (i64)       :", SALE_END.toString());
 
-  // data = discriminator || i64(sale_end, LE)
+  // data = discriminator || i64(sale_end, LE) || Option<u64>(vault_ceiling) = None
+  //        || Option<CreatorAllocation>(creator_allocation) = None
+  //        || Option<ReferralRewards>(referral_rewards) = None
+  //        || Option<Pubkey>(distribution_crank) = None
   const data = Buffer.concat([
     ixDiscriminator("initialize"),
     i64LeBuf(SALE_END),
+    Buffer.from([0]),
+    Buffer.from([0]),
+    Buffer.from([0]),
+    Buffer.from([0]),
   ]);
 
+  const [eventAuthorityPda] = PublicKey.findProgramAddressSync([Buffer.from("__event_authority")], PROGRAM_ID);
   const TOKEN_PROGRAM_ID = new PublicKey("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
   const SYSTEM_PROGRAM_ID = new PublicKey("11111111111111111111111111111111");
 
-  // Accounts MUST be in the same order as IDL.initialize.accounts[]
+  // Accounts MUST be in the same order as the `Initialize` struct (IDL.initialize.accounts[])
   const ix = new TransactionInstruction({
     programId: PROGRAM_ID,
     keys: [
@@ -95,11 +121,17 @@ function i64LeBuf(v: bigint | number): Buffer {
       { pubkey: mint,                   isSigner: false, isWritable: true  }, // mint
       { pubkey: mintAuthorityPda,       isSigner: false, isWritable: false }, // mint_authority (PDA)
       { pubkey: configPda,              isSigner: false, isWritable: true  }, // config (PDA)
+      { pubkey: eventStreamPda,         isSigner: false, isWritable: true  }, // event_stream (PDA)
+      { pubkey: ledgerPda,              isSigner: false, isWritable: true  }, // ledger (PDA, primary vault)
       { pubkey: solVaultPda,            isSigner: false, isWritable: true  }, // sol_vault (PDA)
       { pubkey: tokenVaultPda,          isSigner: false, isWritable: true  }, // token_vault (PDA)
       { pubkey: tokenVaultAccount.publicKey, isSigner: true, isWritable: true }, // token_vault_account (new account)
+      { pubkey: TOKEN_METADATA_ID,      isSigner: false, isWritable: false }, // token_metadata_program
+      { pubkey: metadataPda,            isSigner: false, isWritable: true  }, // metadata (PDA of the metadata program)
       { pubkey: TOKEN_PROGRAM_ID,       isSigner: false, isWritable: false }, // token_program
       { pubkey: SYSTEM_PROGRAM_ID,      isSigner: false, isWritable: false }, // system_program
+      { pubkey: eventAuthorityPda,      isSigner: false, isWritable: false }, // event_authority (#[event_cpi])
+      { pubkey: PROGRAM_ID,             isSigner: false, isWritable: false }, // program (#[event_cpi])
     ],
     data,
   });
@@ -0,0 +1,79 @@
+// tests/local/batch_buy.spec.ts
+// `batch_buy`: one SOL transfer for the whole batch, one buy event per recipient, each
+// carrying the state right after its own leg.
+import assert from "node:assert/strict";
+import { AccountMeta, Keypair, PublicKey } from "@solana/web3.js";
+import { Local, Sale, P, SOL, bn, big, Sent } from "./harness";
+
+describe("batch_buy", () => {
+  let local: Local;
+  let sale: Sale;
+  let buyer: Keypair;
+  let recipients: { owner: PublicKey; ata: PublicKey }[];
+
+  const AMOUNTS = [SOL, 2n * SOL, SOL / 2n];
+
+  const batchIx = (amounts: bigint[], accounts: PublicKey[]) => local.methods.batchBuy(amounts.map(bn))
+    .accountsPartial({ buyer: buyer.publicKey, ...local.vaultAccounts(sale) })
+    .remainingAccounts(accounts.map((pubkey): AccountMeta => ({ pubkey, isWritable: true, isSigner: false })))
+    .instruction();
+  const batch = async (amounts: bigint[]): Promise<Sent> =>
+    local.send(await batchIx(amounts, recipients.map(r => r.ata)), [buyer]);
+  const prefix = (i: number) => AMOUNTS.slice(0, i + 1).reduce((a, b) => a + b, 0n);
+
+  before(async () => {
+    local = await Local.start();
+    sale = await local.initialize();
+    buyer = await local.user();
+    recipients = [];
+    for (const _ of AMOUNTS) {
+      const owner = Keypair.generate().publicKey;
+      recipients.push({ owner, ata: await local.ata(sale.mint, owner) });
+    }
+  });
+
+  it("mints to every recipient and reports each leg's state", async () => {
+    const vault0 = await local.lamports(P.solVault);
+    const supply0 = await local.supply(sale.mint);
+    const buyer0 = await local.lamports(buyer.publicKey);
+
+    const sent = await batch(AMOUNTS);
+
+    const evs = sent.events.filter(e => e.name === "BuyMintEvent").map(e => e.data);
+    assert.equal(evs.length, AMOUNTS.length);
+    evs.forEach((ev, i) => {
+      assert.ok(ev.buyer.equals(buyer.publicKey));
+      assert.ok(ev.recipient.equals(recipients[i].owner));
+      assert.equal(big(ev.amount), AMOUNTS[i]);
+      assert.equal(big(ev.state.vaultLamports), vault0 + prefix(i));
+      assert.equal(big(ev.state.mintSupply), supply0 + prefix(i));
+      assert.equal(big(ev.state.userBalance), AMOUNTS[i]);
+      if (i > 0) assert.equal(big(ev.state.seq), big(evs[i - 1].state.seq) + 1n);
+    });
+    for (const [i, r] of recipients.entries()) assert.equal(await local.balance(r.ata), AMOUNTS[i]);
+    assert.equal(await local.lamports(P.solVault), vault0 + prefix(AMOUNTS.length - 1));
+    assert.equal(await local.lamports(buyer.publicKey), buyer0 - prefix(AMOUNTS.length - 1));
+  });
+
+  it("after finalization, sends from the vault with each leg's vault state", async () => {
+    await local.holder(sale, 10n * SOL); // inventory to restock from
+    await local.finalize(sale);
+    const vault0 = await local.lamports(P.solVault);
+    const inventory0 = await local.balance(sale.tokenVaultAccount);
+
+    const sent = await batch(AMOUNTS);
+
+    const evs = sent.events.filter(e => e.name === "BuyVaultEvent").map(e => e.data);
+    assert.equal(evs.length, AMOUNTS.length);
+    evs.forEach((ev, i) => {
+      assert.ok(ev.recipient.equals(recipients[i].owner));
+      assert.equal(big(ev.state.vaultLamports), vault0 + prefix(i));
+      assert.equal(big(ev.state.tokenVaultAmount), inventory0 - prefix(i));
+      assert.equal(big(ev.state.userBalance), 2n * AMOUNTS[i]);
+    });
+  });
+
+  it("rejects a batch whose amounts and accounts do not pair up", async () => {
+    await local.sendErr(await batchIx(AMOUNTS, recipients.slice(1).map(r => r.ata)), [buyer], "BadBatchAccounts");
+  });
+});
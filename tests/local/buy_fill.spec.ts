@@ -0,0 +1,57 @@
+// tests/local/buy_fill.spec.ts
+// `buy_fair_token_fill`: post-finalization buys fill what the vault holds, charge only
+// that, and honour `min_fill`.
+import assert from "node:assert/strict";
+import { Keypair, PublicKey } from "@solana/web3.js";
+import { Local, Sale, P, SOL, bn, big, eventOf } from "./harness";
+
+const MIN_SUPPLY = 100_000n * SOL; // MIN_SUPPLY_TOKENS in base units
+
+describe("buy_fair_token_fill", () => {
+  let local: Local;
+  let sale: Sale;
+  let whale: { kp: Keypair; ata: PublicKey };
+  let buyer: { kp: Keypair; ata: PublicKey };
+
+  const fill = (requested: bigint, minFill: bigint) => local.methods.buyFairTokenFill(bn(requested), bn(minFill))
+    .accountsPartial({ buyer: buyer.kp.publicKey, ...local.vaultAccounts(sale), userTokenAccount: buyer.ata, referral: null })
+    .instruction();
+
+  before(async () => {
+    // the sale reaches min_supply, so finalize mints no shortfall and the vault starts empty
+    local = await Local.start();
+    sale = await local.initialize();
+    whale = await local.holder(sale, MIN_SUPPLY, 100_100n);
+    buyer = await local.holder(sale, 0n);
+    await local.finalize(sale);
+    assert.equal(await local.balance(sale.tokenVaultAccount), 0n);
+    await local.send(await local.redeemIx(sale, whale.kp, whale.ata, 3n * SOL), [whale.kp]);
+  });
+
+  it("rejects min_fill above the request", async () => {
+    await local.sendErr(await fill(SOL, SOL + 1n), [buyer.kp], "InvalidMinFill");
+  });
+
+  it("all-or-nothing fails when the vault is short", async () => {
+    await local.sendErr(await fill(5n * SOL, 5n * SOL), [buyer.kp], "FillBelowMinimum");
+  });
+
+  it("fills what the vault holds and charges only that", async () => {
+    const sol0 = await local.lamports(buyer.kp.publicKey);
+    const vault0 = await local.lamports(P.solVault);
+
+    const ev = eventOf(await local.send(await fill(5n * SOL, SOL), [buyer.kp]), "BuyVaultEvent");
+
+    assert.equal(big(ev.amount), 3n * SOL);
+    assert.equal(big(ev.lamports), 3n * SOL);
+    assert.equal(big(ev.requested), 5n * SOL);
+    assert.equal(await local.balance(buyer.ata), 3n * SOL);
+    assert.equal(await local.lamports(buyer.kp.publicKey), sol0 - 3n * SOL);
+    assert.equal(await local.lamports(P.solVault), vault0 + 3n * SOL);
+    assert.equal(await local.balance(sale.tokenVaultAccount), 0n);
+  });
+
+  it("fails on an empty vault even with min_fill 0", async () => {
+    await local.sendErr(await fill(SOL, 0n), [buyer.kp], "FillBelowMinimum");
+  });
+});
@@ -0,0 +1,52 @@
+// tests/local/buy_for.spec.ts
+// `buy_for`: the buyer pays, the recipient's ATA is created if needed and receives the
+// tokens; events record both.
+import assert from "node:assert/strict";
+import { Keypair, PublicKey } from "@solana/web3.js";
+import { getAssociatedTokenAddressSync } from "@solana/spl-token";
+import { Local, Sale, SOL, bn, big, eventOf } from "./harness";
+
+describe("buy_for", () => {
+  let local: Local;
+  let sale: Sale;
+  let buyer: Keypair;
+  let recipient: PublicKey;
+
+  const buyFor = (lamports: bigint, recipientTokenAccount = getAssociatedTokenAddressSync(sale.mint, recipient, true)) =>
+    local.methods.buyFor(bn(lamports))
+      .accountsPartial({ buyer: buyer.publicKey, recipient, ...local.vaultAccounts(sale), recipientTokenAccount })
+      .instruction();
+
+  before(async () => {
+    local = await Local.start();
+    sale = await local.initialize();
+    buyer = await local.user();
+    recipient = Keypair.generate().publicKey;
+  });
+
+  it("creates the recipient ATA and records buyer and recipient", async () => {
+    const ata = getAssociatedTokenAddressSync(sale.mint, recipient, true);
+    assert.equal(await local.exists(ata), false);
+
+    const ev = eventOf(await local.send(await buyFor(SOL), [buyer]), "BuyMintEvent");
+
+    assert.ok(ev.buyer.equals(buyer.publicKey));
+    assert.ok(ev.recipient.equals(recipient));
+    assert.equal(await local.balance(ata), SOL);
+    assert.equal(big(ev.state.userBalance), SOL);
+  });
+
+  it("reuses an existing recipient ATA", async () => {
+    await local.send(await buyFor(SOL), [buyer]);
+    assert.equal(await local.balance(getAssociatedTokenAddressSync(sale.mint, recipient, true)), 2n * SOL);
+  });
+
+  it("rejects a zero buy", async () => {
+    await local.sendErr(await buyFor(0n), [buyer], "NoSOLSent");
+  });
+
+  it("rejects a token account that is not the recipient's ATA", async () => {
+    const other = await local.ata(sale.mint, buyer.publicKey);
+    await local.sendErr(await buyFor(SOL, other), [buyer], "AccountNotAssociatedTokenAccount");
+  });
+});
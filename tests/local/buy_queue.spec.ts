@@ -0,0 +1,220 @@
+// tests/local/buy_queue.spec.ts
+// Post-finalization buy queue: restocking redeems fill the orders they are passed, oldest
+// first, and restock the rest (never waiting on the queue); a permissionless crank fills
+// the head from vault inventory; closed or frozen recipients are refunded instead of
+// jamming the head; order size and queue length are bounded.
+import assert from "node:assert/strict";
+import { AccountMeta, Keypair, PublicKey } from "@solana/web3.js";
+import { AccountState, createCloseAccountInstruction } from "@solana/spl-token";
+import { Local, Sale, P, PROGRAM_ID, SOL, bn, big, pda, u64le, writable } from "./harness";
+
+describe("buy queue", () => {
+  let local: Local;
+  let sale: Sale;
+  let seller: { kp: Keypair; ata: PublicKey };
+  let orderRent: bigint;
+  let nextId = 0n;
+  const orders = new Map<bigint, { kp: Keypair; ata: PublicKey }>();
+
+  const orderPda = (id: bigint) => pda(PROGRAM_ID, Buffer.from("buy_order"), u64le(id));
+  const queue = () => local.fetch("buyQueue", P.buyQueue);
+
+  const placeIx = (buyer: { kp: Keypair; ata: PublicKey }, lamports: bigint, id: bigint) =>
+    local.methods.placeBuyOrder(bn(lamports))
+      .accountsPartial({
+        buyer: buyer.kp.publicKey, config: P.config, mint: sale.mint,
+        buyQueue: P.buyQueue, buyOrder: orderPda(id), userTokenAccount: buyer.ata,
+      })
+      .instruction();
+
+  /** New buyer with an order of `lamports`; returns the order id. */
+  async function place(lamports: bigint): Promise<bigint> {
+    const buyer = await local.holder(sale, 0n);
+    const id = nextId++;
+    await local.send(await placeIx(buyer, lamports, id), [buyer.kp]);
+    orders.set(id, buyer);
+    return id;
+  }
+
+  const redeemToIx = (amount: bigint, ...ids: bigint[]) =>
+    local.methods.redeemTo(bn(amount))
+      .accountsPartial({
+        redeemer: seller.kp.publicKey, recipient: seller.kp.publicKey, ...local.vaultAccounts(sale), userTokenAccount: seller.ata,
+      })
+      .remainingAccounts(triples(...ids))
+      .instruction();
+
+  /** `(buy_order, recipient_token_account, buyer)` per order id, as `redeem_fair_token` wants them. */
+  const triples = (...ids: bigint[]): AccountMeta[] => ids.flatMap(id => {
+    const o = orders.get(id)!;
+    return [
+      { pubkey: orderPda(id), isWritable: true, isSigner: false },
+      { pubkey: o.ata, isWritable: true, isSigner: false },
+      { pubkey: o.kp.publicKey, isWritable: true, isSigner: false },
+    ];
+  });
+
+  /** `redeem_fair_token` by the seller; the queue goes writable only when orders are passed. */
+  const redeemIx = async (amount: bigint, metas: AccountMeta[]) => {
+    const ix = await local.redeemIx(sale, seller.kp, seller.ata, amount, metas);
+    return metas.length ? writable(ix, P.buyQueue) : ix;
+  };
+  const redeem = async (amount: bigint, ...ids: bigint[]) =>
+    local.send(await redeemIx(amount, triples(...ids)), [seller.kp]);
+
+  const fillFromVaultIx = (...ids: bigint[]) =>
+    local.methods.fillBuyOrdersFromVault()
+      .accountsPartial({
+        config: P.config, ledger: P.ledger(), buyQueue: P.buyQueue, solVault: P.solVault,
+        tokenVault: P.tokenVault, tokenVaultAccount: sale.tokenVaultAccount,
+      })
+      .remainingAccounts(triples(...ids))
+      .instruction();
+
+  before(async () => {
+    local = await Local.start();
+    sale = await local.initialize();
+    seller = await local.holder(sale, 20n * SOL);
+    await local.finalize(sale);
+    orderRent = await local.rent(8 + 8 + 32 + 32 + 8 + 1);
+  });
+
+  it("fills orders oldest first and moves the escrow into the vault", async () => {
+    const a = await place(SOL);
+    const b = await place(SOL);
+    const vault0 = await local.lamports(P.solVault);
+
+    const sent = await redeem(3n * SOL / 2n, a, b);
+
+    const fills = sent.events.filter(e => e.name === "BuyOrderFilledEvent").map(e => e.data);
+    assert.deepEqual(fills.map(f => big(f.id)), [a, b]);
+    assert.deepEqual(fills.map(f => big(f.amount)), [SOL, SOL / 2n]);
+    assert.equal(await local.balance(orders.get(a)!.ata), SOL);
+    assert.equal(await local.balance(orders.get(b)!.ata), SOL / 2n);
+    // the seller's SOL came out of the vault and the filled escrow went in
+    assert.equal(await local.lamports(P.solVault), vault0);
+    const q = await queue();
+    assert.equal(big(q.head), b);
+    assert.equal(big(q.escrowedLamports), SOL / 2n);
+    assert.equal(big((await local.ledger()).totalQueueFilled), 3n * SOL / 2n);
+  });
+
+  it("restocks without waiting when no orders are passed", async () => {
+    const vaultTokens0 = await local.balance(sale.tokenVaultAccount);
+    const sent = await redeem(SOL / 4n);
+    assert.equal(sent.events.filter(e => e.name === "BuyOrderFilledEvent").length, 0);
+    assert.equal(await local.balance(sale.tokenVaultAccount), vaultTokens0 + SOL / 4n);
+    const q = await queue();
+    assert.equal(big(q.head), 1n);
+    assert.equal(big(q.escrowedLamports), SOL / 2n);
+  });
+
+  it("fills passed orders on redeem_to, with the queue passed writable", async () => {
+    // read-only queue (the IDL default) cannot take fills
+    await local.sendErr(await redeemToIx(SOL / 4n, 1n), [seller.kp], "BuyQueueNotWritable");
+
+    const sent = await local.send(writable(await redeemToIx(SOL / 4n, 1n), P.buyQueue), [seller.kp]);
+    const fills = sent.events.filter(e => e.name === "BuyOrderFilledEvent").map(e => e.data);
+    assert.deepEqual(fills.map(f => [big(f.id), big(f.amount)]), [[1n, SOL / 4n]]);
+    assert.equal(await local.balance(orders.get(1n)!.ata), 3n * SOL / 4n);
+    assert.equal(big((await queue()).escrowedLamports), SOL / 4n);
+  });
+
+  it("cancel refunds the unfilled escrow and rent and frees the order's slot", async () => {
+    const b = orders.get(1n)!;
+    const sol0 = await local.lamports(b.kp.publicKey);
+    const open0 = big((await queue()).openOrders);
+    await local.send(
+      await local.methods.cancelBuyOrder()
+        .accountsPartial({ buyer: b.kp.publicKey, config: P.config, buyQueue: P.buyQueue, buyOrder: orderPda(1n) })
+        .instruction(),
+      [b.kp],
+    );
+    assert.equal(await local.lamports(b.kp.publicKey), sol0 + SOL / 4n + orderRent);
+    assert.equal(await local.exists(orderPda(1n)), false);
+    const q = await queue();
+    assert.equal(big(q.escrowedLamports), 0n);
+    assert.equal(big(q.openOrders), open0 - 1n);
+  });
+
+  it("refunds and skips an order whose recipient was frozen", async () => {
+    const jammed = await place(SOL);
+    const next = await place(SOL);
+    await local.setTokenState(orders.get(jammed)!.ata, AccountState.Frozen);
+    const buyer = orders.get(jammed)!.kp.publicKey;
+    const sol0 = await local.lamports(buyer);
+
+    const sent = await redeem(SOL, 1n, jammed, next);
+
+    const cancelled = sent.events.filter(e => e.name === "BuyOrderCancelledEvent").map(e => e.data);
+    assert.deepEqual(cancelled.map(c => [big(c.id), big(c.refunded)]), [[jammed, SOL]]);
+    const fills = sent.events.filter(e => e.name === "BuyOrderFilledEvent").map(e => e.data);
+    assert.deepEqual(fills.map(f => [big(f.id), big(f.amount)]), [[next, SOL]]);
+    assert.equal(await local.lamports(buyer), sol0 + SOL + orderRent);
+    assert.equal(await local.exists(orderPda(jammed)), false);
+    assert.equal(await local.balance(orders.get(next)!.ata), SOL);
+    const q = await queue();
+    assert.equal(big(q.head), next + 1n);
+    assert.equal(big(q.escrowedLamports), 0n);
+  });
+
+  it("refunds a closed recipient and restocks the rest", async () => {
+    const id = await place(SOL / 2n);
+    const o = orders.get(id)!;
+    await local.send(createCloseAccountInstruction(o.ata, o.kp.publicKey, o.kp.publicKey), [o.kp]);
+    const sol0 = await local.lamports(o.kp.publicKey);
+    const vaultTokens0 = await local.balance(sale.tokenVaultAccount);
+
+    const sent = await redeem(SOL, id);
+
+    assert.equal(big(sent.events.find(e => e.name === "BuyOrderCancelledEvent")!.data.refunded), SOL / 2n);
+    assert.equal(await local.lamports(o.kp.publicKey), sol0 + SOL / 2n + orderRent);
+    // nothing filled: the whole amount was restocked
+    assert.equal(await local.balance(sale.tokenVaultAccount), vaultTokens0 + SOL);
+  });
+
+  it("lets anyone fill the head from vault inventory before a later vault buy", async () => {
+    const id = await place(SOL);
+    const vault0 = await local.lamports(P.solVault);
+    const vaultTokens0 = await local.balance(sale.tokenVaultAccount);
+    const bought0 = big((await local.ledger()).totalBoughtFromVault);
+    await local.sendErr(await fillFromVaultIx(), [], "BadQueueAccounts");
+
+    const sent = await local.send(await fillFromVaultIx(id)); // signed by the fee payer only
+
+    const fill = sent.events.find(e => e.name === "BuyOrderFilledEvent")!.data;
+    assert.equal(big(fill.amount), SOL);
+    assert.ok(fill.redeemer.equals(P.tokenVault));
+    assert.equal(await local.balance(orders.get(id)!.ata), SOL);
+    assert.equal(await local.balance(sale.tokenVaultAccount), vaultTokens0 - SOL);
+    assert.equal(await local.lamports(P.solVault), vault0 + SOL);
+    assert.equal(big((await local.ledger()).totalBoughtFromVault), bought0 + SOL);
+    const q = await queue();
+    assert.equal(big(q.head), id + 1n);
+    assert.equal(big(q.escrowedLamports), 0n);
+  });
+
+  it("requires the order's buyer to receive an auto-refund", async () => {
+    const id = await place(SOL);
+    await local.setTokenState(orders.get(id)!.ata, AccountState.Frozen);
+    const metas = triples(id);
+    metas[2] = { pubkey: seller.kp.publicKey, isWritable: true, isSigner: false };
+    await local.sendErr(await redeemIx(SOL, metas), [seller.kp], "BadQueueAccounts");
+  });
+
+  it("rejects orders below the minimum size", async () => {
+    const buyer = await local.holder(sale, 0n);
+    await local.sendErr(await placeIx(buyer, 10_000_000n - 1n, nextId), [buyer.kp], "BuyOrderTooSmall");
+  });
+
+  it("rejects orders once the queue holds MAX_OPEN_BUY_ORDERS", async () => {
+    // open_orders sits after the discriminator, head, tail, escrowed_lamports and event_seq
+    const acc = (await local.client.getAccount(P.buyQueue))!;
+    const data = Buffer.from(acc.data);
+    data.writeBigUInt64LE(256n, 40);
+    local.setAccount(P.buyQueue, acc.lamports, data, PROGRAM_ID);
+
+    const buyer = await local.holder(sale, 0n);
+    await local.sendErr(await placeIx(buyer, SOL, nextId), [buyer.kp], "BuyQueueFull");
+  });
+});
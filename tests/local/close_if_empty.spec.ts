@@ -0,0 +1,56 @@
+// tests/local/close_if_empty.spec.ts
+// `redeem_fair_token_and_close`: closes the user's token account only when the
+// redemption empties it, returning the rent in the same transaction; `redeem_fair_token`
+// never closes.
+import assert from "node:assert/strict";
+import { Keypair, PublicKey } from "@solana/web3.js";
+import { ACCOUNT_SIZE } from "@solana/spl-token";
+import { Local, Sale, SOL, bn, big, eventOf } from "./harness";
+
+describe("redeem_fair_token_and_close", () => {
+  let local: Local;
+  let sale: Sale;
+  let holder: { kp: Keypair; ata: PublicKey };
+  let ataRent: bigint;
+
+  const redeem = (who: { kp: Keypair; ata: PublicKey }, amount: bigint, close: boolean) =>
+    (close ? local.methods.redeemFairTokenAndClose(bn(amount)) : local.methods.redeemFairToken(bn(amount)))
+      .accountsPartial({ redeemer: who.kp.publicKey, ...local.vaultAccounts(sale), userTokenAccount: who.ata })
+      .instruction();
+
+  before(async () => {
+    local = await Local.start();
+    sale = await local.initialize();
+    holder = await local.holder(sale, 3n * SOL);
+    ataRent = await local.rent(ACCOUNT_SIZE);
+  });
+
+  it("keeps the account when the redemption leaves a balance", async () => {
+    const ev = eventOf(await local.send(await redeem(holder, SOL, true), [holder.kp]), "RedeemBurnEvent");
+    assert.equal(ev.closed, false);
+    assert.equal(big(ev.state.userBalance), 2n * SOL);
+    assert.equal(await local.exists(holder.ata), true);
+  });
+
+  it("rejects redeeming more than the balance", async () => {
+    await local.sendErr(await redeem(holder, 2n * SOL + 1n, true), [holder.kp], "InsufficientTokens");
+    assert.equal(await local.exists(holder.ata), true);
+  });
+
+  it("closes the emptied account and returns its rent with the SOL", async () => {
+    const sol0 = await local.lamports(holder.kp.publicKey);
+    const ev = eventOf(await local.send(await redeem(holder, 2n * SOL, true), [holder.kp]), "RedeemBurnEvent");
+    assert.equal(ev.closed, true);
+    assert.equal(big(ev.state.userBalance), 0n);
+    assert.equal(await local.exists(holder.ata), false);
+    assert.equal(await local.lamports(holder.kp.publicKey), sol0 + 2n * SOL + ataRent);
+  });
+
+  it("redeem_fair_token leaves an emptied account open", async () => {
+    const other = await local.holder(sale, SOL);
+    const ev = eventOf(await local.send(await redeem(other, SOL, false), [other.kp]), "RedeemBurnEvent");
+    assert.equal(ev.closed, false);
+    assert.equal(await local.exists(other.ata), true);
+    assert.equal(await local.balance(other.ata), 0n);
+  });
+});
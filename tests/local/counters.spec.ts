@@ -0,0 +1,68 @@
+// tests/local/counters.spec.ts
+// Lifetime counters: every path books into the vault ledger (and `finalize` into
+// `Config.total_minted_shortfall`), so one read reconciles supply and SOL.
+import assert from "node:assert/strict";
+import { Keypair, PublicKey } from "@solana/web3.js";
+import { Local, Sale, P, SOL, big } from "./harness";
+
+const MIN_SUPPLY = 100_000n * SOL; // MIN_SUPPLY_TOKENS in base units
+
+describe("lifetime counters", () => {
+  let local: Local;
+  let sale: Sale;
+  let holder: { kp: Keypair; ata: PublicKey };
+  let vault0: bigint;
+
+  const counters = async () => {
+    const l = await local.ledger();
+    return {
+      mintedSale: big(l.totalMintedSale), boughtFromVault: big(l.totalBoughtFromVault),
+      redeemedBurn: big(l.totalRedeemedBurn), redeemedToVault: big(l.totalRedeemedToVault),
+      burned: big(l.totalBurned), lamportsIn: big(l.lamportsIn), lamportsOut: big(l.lamportsOut),
+    };
+  };
+  const redeem = async (amount: bigint) =>
+    local.send(await local.redeemIx(sale, holder.kp, holder.ata, amount), [holder.kp]);
+
+  before(async () => {
+    local = await Local.start();
+    sale = await local.initialize();
+    vault0 = await local.lamports(P.solVault);
+    holder = await local.holder(sale, 5n * SOL);
+  });
+
+  it("books sale mints and burn redemptions", async () => {
+    await redeem(SOL);
+    assert.deepEqual(await counters(), {
+      mintedSale: 5n * SOL, boughtFromVault: 0n, redeemedBurn: SOL, redeemedToVault: 0n,
+      burned: SOL, lamportsIn: 5n * SOL, lamportsOut: SOL,
+    });
+  });
+
+  it("leaves every counter untouched when a redemption fails", async () => {
+    const before = await counters();
+    await local.sendErr(await local.redeemIx(sale, holder.kp, holder.ata, 5n * SOL), [holder.kp], "InsufficientTokens");
+    assert.deepEqual(await counters(), before);
+  });
+
+  it("books the finalize shortfall in config", async () => {
+    await local.finalize(sale);
+    assert.equal(big((await local.config()).totalMintedShortfall), MIN_SUPPLY - 4n * SOL);
+  });
+
+  it("books vault buys and restocks, and reconciles with the accounts", async () => {
+    await local.send(await local.buyIx(sale, holder.kp, holder.ata, 2n * SOL), [holder.kp]);
+    await redeem(SOL);
+
+    const c = await counters();
+    assert.deepEqual(c, {
+      mintedSale: 5n * SOL, boughtFromVault: 2n * SOL, redeemedBurn: SOL, redeemedToVault: SOL,
+      burned: SOL, lamportsIn: 7n * SOL, lamportsOut: 2n * SOL,
+    });
+    const shortfall = big((await local.config()).totalMintedShortfall);
+    assert.equal(await local.supply(sale.mint), c.mintedSale + shortfall - c.burned);
+    assert.equal(await local.balance(sale.tokenVaultAccount), shortfall - c.boughtFromVault + c.redeemedToVault);
+    assert.equal(await local.balance(holder.ata), c.mintedSale + c.boughtFromVault - c.redeemedBurn - c.redeemedToVault);
+    assert.equal(await local.lamports(P.solVault) - vault0, c.lamportsIn - c.lamportsOut);
+  });
+});
@@ -0,0 +1,139 @@
+// tests/local/creator.spec.ts
+// Creator allocation: `finalize` mints it into the vesting escrow, outside circulation;
+// each `release_creator_vesting` pays only the beneficiary's ATA and is backed 1:1 in
+// SOL, so solvency holds at every step, also once released tokens are redeemed, and a
+// surplus snapshot never takes the SOL that backs them.
+import assert from "node:assert/strict";
+import { Keypair, PublicKey } from "@solana/web3.js";
+import { Local, Sale, P, PROGRAM_ID, SOL, DAY, bn, big, pda, u64le, eventOf } from "./harness";
+
+const ALLOCATION = { bps: 1_000, cliffSecs: DAY, vestingSecs: 10 * DAY };
+
+describe("creator allocation", () => {
+  let local: Local;
+  let sale: Sale;
+  let beneficiary: Keypair;
+  let beneficiaryAta: PublicKey;
+
+  const vesting = pda(PROGRAM_ID, Buffer.from("creator_vesting"));
+  const escrow = pda(PROGRAM_ID, Buffer.from("creator_escrow"));
+
+  const release = (backer: Keypair, to = beneficiaryAta) => local.methods.releaseCreatorVesting()
+    .accountsPartial({
+      backer: backer.publicKey, config: P.config, solVault: P.solVault, eventStream: P.eventStream,
+      creatorVesting: vesting, creatorEscrow: escrow, beneficiaryTokenAccount: to,
+    })
+    .instruction();
+  const solvency = async () => eventOf(
+    await local.send(
+      await local.methods.checkSolvency()
+        .accountsPartial({ config: P.config, mint: sale.mint, reserveVault: P.solVault, tokenVaultAccount: sale.tokenVaultAccount })
+        .instruction(),
+    ),
+    "SolvencyCheckedEvent",
+  );
+
+  before(async () => {
+    // program-owned vault: `backing_lamports` is exact, so solvency figures are too
+    local = await Local.start();
+    beneficiary = await local.user();
+    sale = await local.initialize({
+      kind: "program",
+      creatorAllocation: { beneficiary: beneficiary.publicKey, ...ALLOCATION },
+      distributionCrank: local.payer.publicKey,
+    });
+    beneficiaryAta = await local.ata(sale.mint, beneficiary.publicKey);
+    await local.send(
+      await local.methods.openCreatorVesting()
+        .accountsPartial({ payer: local.payer.publicKey, config: P.config, mint: sale.mint, creatorVesting: vesting, creatorEscrow: escrow })
+        .instruction(),
+    );
+    await local.holder(sale, 10n * SOL);
+  });
+
+  it("rejects a creator allocation on a token-backed sale", async () => {
+    const other = await Local.start();
+    await assert.rejects(
+      other.initialize({ kind: "token", creatorAllocation: { beneficiary: beneficiary.publicKey, ...ALLOCATION } }),
+      /CreatorAllocationRequiresSolBacking/,
+    );
+  });
+
+  it("finalize mints the allocation into the escrow, outside circulation", async () => {
+    const sent = await local.finalize(sale, { creatorVesting: vesting, creatorEscrow: escrow });
+    assert.equal(big(eventOf(sent, "CreatorAllocationMintedEvent").amount), SOL); // 10% of the sale supply
+    assert.equal(await local.balance(escrow), SOL);
+    assert.equal(big((await local.config()).creatorAllocated), SOL);
+
+    const ev = await solvency();
+    assert.equal(big(ev.circulating), 10n * SOL);
+    assert.equal(big(ev.creatorAllocated), SOL);
+    assert.equal(big(ev.surplusLamports), 0n);
+    assert.equal(big(ev.backingRatioBps), 10_000n);
+  });
+
+  it("releases nothing before the cliff", async () => {
+    await local.sendErr(await release(beneficiary), [beneficiary], "NothingVested");
+  });
+
+  it("pays only the beneficiary's ATA", async () => {
+    await local.warp(4 * DAY);
+    const outsider = await local.user();
+    const outsiderAta = await local.ata(sale.mint, outsider.publicKey);
+    await local.sendErr(await release(outsider, outsiderAta), [outsider], "ConstraintTokenOwner");
+  });
+
+  it("backs each release 1:1 and takes it off creator_allocated", async () => {
+    const v = await local.fetch("creatorVesting", vesting);
+    const now = await local.now();
+    const vested = SOL * (now - big(v.startTs)) / (big(v.endTs) - big(v.startTs));
+    const vault0 = await local.lamports(P.solVault);
+    const backer = await local.user();
+
+    const sent = await local.send(await release(backer), [backer]);
+
+    const released = eventOf(sent, "CreatorVestingReleasedEvent");
+    assert.equal(big(released.amount), vested);
+    assert.ok(released.backer.equals(backer.publicKey));
+    assert.equal(await local.balance(beneficiaryAta), vested);
+    assert.equal(await local.lamports(P.solVault), vault0 + vested);
+    assert.equal(big((await local.config()).creatorAllocated), SOL - vested);
+
+    const ev = await solvency();
+    assert.equal(big(ev.circulating), 10n * SOL + vested);
+    assert.equal(big(ev.surplusLamports), 0n);
+  });
+
+  it("counts released tokens as redeemable when snapshotting surplus", async () => {
+    // the release's backing is theirs, not surplus: nothing to distribute
+    await local.sendErr(
+      await local.methods.snapshotSurplus(bn(0n))
+        .accountsPartial({
+          payer: local.payer.publicKey, config: P.config, eventStream: P.eventStream, mint: sale.mint,
+          solVault: P.solVault, tokenVaultAccount: sale.tokenVaultAccount,
+          distribution: pda(PROGRAM_ID, Buffer.from("distribution"), u64le(0n)),
+        })
+        .instruction(),
+      [],
+      "NoSurplus",
+    );
+  });
+
+  it("fully vested: creator_allocated reaches zero and the released supply redeems at the floor", async () => {
+    await local.warp(10 * DAY);
+    await local.send(await release(beneficiary), [beneficiary]);
+    assert.equal(await local.balance(escrow), 0n);
+    assert.equal(await local.balance(beneficiaryAta), SOL);
+    assert.equal(big((await local.config()).creatorAllocated), 0n);
+
+    const vault0 = await local.lamports(P.solVault);
+    await local.send(await local.redeemIx(sale, beneficiary, beneficiaryAta, SOL), [beneficiary]);
+    assert.equal(await local.lamports(P.solVault), vault0 - SOL);
+    // the buyers' 10 SOL of tokens keep their full 10 SOL of backing
+    const ev = await solvency();
+    assert.equal(big(ev.circulating), 10n * SOL);
+    assert.equal(big(ev.vaultLamports), 10n * SOL);
+    assert.equal(big(ev.surplusLamports), 0n);
+    assert.equal(big(ev.backingRatioBps), 10_000n);
+  });
+});
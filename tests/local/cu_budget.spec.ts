@@ -0,0 +1,444 @@
+// tests/local/cu_budget.spec.ts
+// Compute-unit budget: runs every fair_token instruction on the local validator and fails
+// if one errors or consumes more CUs (including its `#[event_cpi]` self-CPIs) than its
+// ceiling in tests/fixtures/cu_baseline.json.
+// `CU_BASELINE=update npm run test:local` rewrites that file from the measured CUs (plus
+// headroom), so a drop (or a deliberate rise) is a reviewable diff of the baseline.
+import fs from "fs";
+import path from "path";
+import assert from "node:assert/strict";
+import { AccountMeta, Keypair, PublicKey, SystemProgram } from "@solana/web3.js";
+import { NATIVE_MINT, createApproveInstruction, createSyncNativeInstruction } from "@solana/spl-token";
+import { IDL, Local, Sale, Sent, P, PROGRAM_ID, PRIMARY_VAULT, SOL, DAY, bn, pda, u64le, writable } from "./harness";
+import { leafHash, merkle } from "../../scripts/merkle";
+
+const BASELINE = path.resolve(__dirname, "../fixtures/cu_baseline.json");
+const USDC = 1_000_000n; // one backing token (6 decimals)
+// `init` bump searches vary with the generated keys, so an updated ceiling is the measured
+// CUs plus 10%, rounded up to 1,000.
+const ceiling = (cu: number) => Math.ceil(cu * 1.1 / 1_000) * 1_000;
+
+const baseline: Record<string, number> = JSON.parse(fs.readFileSync(BASELINE, "utf8"));
+const measured = new Map<string, number>();
+
+/** Books `sent` under instruction `name`; an instruction run several times keeps its highest CUs. */
+function measure(name: string, sent: Sent): Sent {
+  measured.set(name, Math.max(measured.get(name) ?? 0, sent.cu));
+  return sent;
+}
+
+describe("compute-unit budget", () => {
+  describe("system-owned SOL vault", () => {
+    let local: Local;
+    let sale: Sale;
+    let holder: { kp: Keypair; ata: PublicKey };
+
+    const referralPda = (key: PublicKey) => pda(PROGRAM_ID, Buffer.from("referral"), key.toBuffer());
+    const shardAccounts = () => ({
+      mint: sale.mint, mintAuthority: P.mintAuthority, config: P.config,
+      ledger: P.ledger(0), buyQueue: P.buyQueue, solVaultShard: P.solVaultShard(0),
+      tokenVault: P.tokenVault, shardTokenAccount: P.tokenVaultShard(0),
+    });
+    const shardPairs = (): AccountMeta[] => [
+      { pubkey: P.solVaultShard(0), isWritable: false, isSigner: false },
+      { pubkey: P.tokenVaultShard(0), isWritable: false, isSigner: false },
+    ];
+    const buy = async (lamports: bigint, referral: PublicKey | null = null) => measure("buy_fair_token",
+      await local.send(await local.buyIx(sale, holder.kp, holder.ata, lamports, referral), [holder.kp]));
+    const fill = async (lamports: bigint) => measure("buy_fair_token_fill", await local.send(
+      await local.methods.buyFairTokenFill(bn(lamports), bn(lamports))
+        .accountsPartial({ buyer: holder.kp.publicKey, ...local.vaultAccounts(sale), userTokenAccount: holder.ata, referral: null })
+        .instruction(),
+      [holder.kp],
+    ));
+    const redeem = async (amount: bigint) => measure("redeem_fair_token",
+      await local.send(await local.redeemIx(sale, holder.kp, holder.ata, amount), [holder.kp]));
+    const solvency = async (remaining: AccountMeta[] = []) => {
+      measure("refresh_price", await local.send(
+        await local.methods.refreshPrice()
+          .accountsPartial({
+            payer: local.payer.publicKey, config: P.config, mint: sale.mint,
+            tokenVaultAccount: sale.tokenVaultAccount, reserveVault: P.solVault, priceFeed: P.priceFeed,
+          })
+          .remainingAccounts(remaining)
+          .instruction(),
+      ));
+      measure("check_solvency", await local.send(
+        await local.methods.checkSolvency()
+          .accountsPartial({ config: P.config, mint: sale.mint, reserveVault: P.solVault, tokenVaultAccount: sale.tokenVaultAccount })
+          .remainingAccounts(remaining)
+          .instruction(),
+      ));
+    };
+
+    before(async () => {
+      local = await Local.start();
+      sale = await local.initialize({ referralRewards: { rewardBps: 100, maxRewardPerReferrer: SOL } });
+      measure("initialize", sale.init);
+      holder = await local.holder(sale, 0n);
+    });
+
+    it("buy, fill and redeem (closing an emptied account) during the sale", async () => {
+      await buy(5n * SOL);
+      await fill(SOL);
+      await redeem(SOL);
+      const emptied = await local.holder(sale, SOL);
+      measure("redeem_fair_token_and_close", await local.send(
+        await local.methods.redeemFairTokenAndClose(bn(SOL))
+          .accountsPartial({ redeemer: emptied.kp.publicKey, ...local.vaultAccounts(sale), userTokenAccount: emptied.ata })
+          .instruction(),
+        [emptied.kp],
+      ));
+      measure("redeem_to", await local.send(
+        await local.methods.redeemTo(bn(SOL))
+          .accountsPartial({
+            redeemer: holder.kp.publicKey, recipient: Keypair.generate().publicKey, ...local.vaultAccounts(sale),
+            userTokenAccount: holder.ata,
+          })
+          .instruction(),
+        [holder.kp],
+      ));
+    });
+
+    it("buy_for and batch_buy (3 recipients)", async () => {
+      const recipients = await Promise.all([0, 1, 2].map(() => local.holder(sale, 0n)));
+      measure("buy_for", await local.send(
+        await local.methods.buyFor(bn(SOL))
+          .accountsPartial({
+            buyer: holder.kp.publicKey, recipient: recipients[0].kp.publicKey, ...local.vaultAccounts(sale),
+            recipientTokenAccount: recipients[0].ata,
+          })
+          .instruction(),
+        [holder.kp],
+      ));
+      measure("batch_buy", await local.send(
+        await local.methods.batchBuy([SOL, SOL, SOL].map(bn))
+          .accountsPartial({ buyer: holder.kp.publicKey, ...local.vaultAccounts(sale) })
+          .remainingAccounts(recipients.map((r): AccountMeta => ({ pubkey: r.ata, isWritable: true, isSigner: false })))
+          .instruction(),
+        [holder.kp],
+      ));
+    });
+
+    it("wSOL buy and redeem", async () => {
+      const wsol = await local.ata(NATIVE_MINT, holder.kp.publicKey);
+      await local.send([
+        SystemProgram.transfer({ fromPubkey: holder.kp.publicKey, toPubkey: wsol, lamports: Number(5n * SOL) }),
+        createSyncNativeInstruction(wsol),
+      ], [holder.kp]);
+      measure("buy_fair_token_wsol", await local.send(
+        await local.methods.buyFairTokenWsol(bn(2n * SOL))
+          .accountsPartial({
+            buyer: holder.kp.publicKey, ...local.vaultAccounts(sale), userTokenAccount: holder.ata,
+            userWsolAccount: wsol, wsolMint: NATIVE_MINT,
+            wsolScratch: pda(PROGRAM_ID, Buffer.from("wsol_scratch"), holder.kp.publicKey.toBuffer()),
+          })
+          .instruction(),
+        [holder.kp],
+      ));
+      measure("redeem_fair_token_wsol", await local.send(
+        await local.methods.redeemFairTokenWsol(bn(SOL))
+          .accountsPartial({
+            redeemer: holder.kp.publicKey, ...local.vaultAccounts(sale), userTokenAccount: holder.ata, userWsolAccount: wsol,
+          })
+          .instruction(),
+        [holder.kp],
+      ));
+    });
+
+    it("delegated redeem and the redeem destination", async () => {
+      const bot = await local.user();
+      const record = pda(PROGRAM_ID, Buffer.from("redeem_destination"), holder.kp.publicKey.toBuffer());
+      await local.send(createApproveInstruction(holder.ata, bot.publicKey, holder.kp.publicKey, SOL), [holder.kp]);
+      measure("redeem_delegated", await local.send(
+        await local.methods.redeemDelegated(bn(SOL))
+          .accountsPartial({
+            delegate: bot.publicKey, destination: holder.kp.publicKey, redeemDestination: record,
+            ...local.vaultAccounts(sale), userTokenAccount: holder.ata,
+          })
+          .instruction(),
+        [bot],
+      ));
+      measure("set_redeem_destination", await local.send(
+        await local.methods.setRedeemDestination(Keypair.generate().publicKey)
+          .accountsPartial({ owner: holder.kp.publicKey, redeemDestination: record })
+          .instruction(),
+        [holder.kp],
+      ));
+    });
+
+    it("referral: open, referred buy, claim", async () => {
+      const referrer = await local.user();
+      const referral = referralPda(referrer.publicKey);
+      const pool = pda(PROGRAM_ID, Buffer.from("referral_pool"));
+      measure("open_referral", await local.send(
+        await local.methods.openReferral()
+          .accountsPartial({ payer: local.payer.publicKey, referrer: referrer.publicKey, referral })
+          .instruction(),
+      ));
+      await buy(2n * SOL, referral);
+      await local.send(SystemProgram.transfer({ fromPubkey: local.payer.publicKey, toPubkey: pool, lamports: Number(SOL) }));
+      measure("claim_referral_rewards", await local.send(
+        await local.methods.claimReferralRewards()
+          .accountsPartial({
+            referrer: referrer.publicKey, config: P.config, eventStream: P.eventStream, referral, referralPool: pool,
+          })
+          .instruction(),
+        [referrer],
+      ));
+    });
+
+    it("donate, refresh_price and check_solvency", async () => {
+      measure("donate", await local.send(
+        await local.methods.donate(bn(SOL))
+          .accountsPartial({ donor: local.payer.publicKey, config: P.config, eventStream: P.eventStream, solVault: P.solVault })
+          .instruction(),
+      ));
+      await solvency();
+    });
+
+    it("vault shards", async () => {
+      measure("open_vault_shard", await local.send(
+        await local.methods.openVaultShard(0)
+          .accountsPartial({
+            payer: local.payer.publicKey, config: P.config, eventStream: P.eventStream, mint: sale.mint,
+            tokenVault: P.tokenVault, solVaultShard: P.solVaultShard(0), shardTokenAccount: P.tokenVaultShard(0),
+          })
+          .instruction(),
+      ));
+      measure("open_vault_ledger", await local.send(
+        await local.methods.openVaultLedger(0)
+          .accountsPartial({ payer: local.payer.publicKey, config: P.config, ledger: P.ledger(0) })
+          .instruction(),
+      ));
+      // pre-finalization: both write the mint
+      measure("buy_sharded", await local.send(
+        writable(await local.methods.buySharded(0, bn(3n * SOL))
+          .accountsPartial({ buyer: holder.kp.publicKey, ...shardAccounts(), userTokenAccount: holder.ata })
+          .instruction(), sale.mint),
+        [holder.kp],
+      ));
+      measure("redeem_sharded", await local.send(
+        writable(await local.methods.redeemSharded(0, bn(SOL))
+          .accountsPartial({ redeemer: holder.kp.publicKey, ...shardAccounts(), userTokenAccount: holder.ata })
+          .instruction(), sale.mint),
+        [holder.kp],
+      ));
+      measure("rebalance_vault_shards", await local.send(
+        await local.methods.rebalanceVaultShards(PRIMARY_VAULT, 0, bn(SOL), bn(0))
+          .accountsPartial({
+            config: P.config, eventStream: P.eventStream, tokenVault: P.tokenVault,
+            fromSolVault: P.solVault, toSolVault: P.solVaultShard(0),
+            fromTokenAccount: sale.tokenVaultAccount, toTokenAccount: P.tokenVaultShard(0),
+          })
+          .instruction(),
+      ));
+      // the vault scans grow with the shard count
+      await solvency(shardPairs());
+    });
+
+    it("after finalization: vault buys, restocks and the buy queue", async () => {
+      measure("finalize", await local.finalize(sale));
+      await buy(SOL);
+      await fill(SOL);
+      await redeem(SOL);
+
+      const order = pda(PROGRAM_ID, Buffer.from("buy_order"), u64le(0n));
+      measure("place_buy_order", await local.send(
+        await local.methods.placeBuyOrder(bn(SOL))
+          .accountsPartial({
+            buyer: holder.kp.publicKey, config: P.config, mint: sale.mint,
+            buyQueue: P.buyQueue, buyOrder: order, userTokenAccount: holder.ata,
+          })
+          .instruction(),
+        [holder.kp],
+      ));
+      measure("fill_buy_orders_from_vault", await local.send(
+        await local.methods.fillBuyOrdersFromVault()
+          .accountsPartial({
+            config: P.config, ledger: P.ledger(), buyQueue: P.buyQueue, solVault: P.solVault,
+            tokenVault: P.tokenVault, tokenVaultAccount: sale.tokenVaultAccount,
+          })
+          .remainingAccounts([
+            { pubkey: order, isWritable: true, isSigner: false },
+            { pubkey: holder.ata, isWritable: true, isSigner: false },
+            { pubkey: holder.kp.publicKey, isWritable: true, isSigner: false },
+          ])
+          .instruction(),
+      ));
+      // the order is filled now: cancel closes it
+      measure("cancel_buy_order", await local.send(
+        await local.methods.cancelBuyOrder()
+          .accountsPartial({ buyer: holder.kp.publicKey, config: P.config, buyQueue: P.buyQueue, buyOrder: order })
+          .instruction(),
+        [holder.kp],
+      ));
+    });
+  });
+
+  describe("program-owned SOL vault: distribution and creator vesting", () => {
+    const EPOCH = 0n;
+    let local: Local;
+    let sale: Sale;
+    let beneficiary: Keypair;
+    let a: { kp: Keypair; ata: PublicKey };
+    let b: { kp: Keypair; ata: PublicKey };
+
+    const vesting = pda(PROGRAM_ID, Buffer.from("creator_vesting"));
+    const escrow = pda(PROGRAM_ID, Buffer.from("creator_escrow"));
+    const distribution = pda(PROGRAM_ID, Buffer.from("distribution"), u64le(EPOCH));
+    const publish = async (root: Buffer, challenger: PublicKey | null = null) => measure("publish_distribution_root", await local.send(
+      await local.methods.publishDistributionRoot([...root], bn(SOL))
+        .accountsPartial({
+          publisher: local.payer.publicKey, config: P.config, eventStream: P.eventStream, solVault: P.solVault, distribution, challenger,
+        })
+        .instruction(),
+    ));
+
+    before(async () => {
+      local = await Local.start();
+      beneficiary = await local.user();
+      sale = await local.initialize({
+        kind: "program",
+        creatorAllocation: { beneficiary: beneficiary.publicKey, bps: 1_000, cliffSecs: DAY, vestingSecs: 10 * DAY },
+        distributionCrank: local.payer.publicKey,
+      });
+      measure("initialize_program_vault", sale.init);
+      measure("open_creator_vesting", await local.send(
+        await local.methods.openCreatorVesting()
+          .accountsPartial({ payer: local.payer.publicKey, config: P.config, mint: sale.mint, creatorVesting: vesting, creatorEscrow: escrow })
+          .instruction(),
+      ));
+      a = await local.holder(sale, 3n * SOL);
+      b = await local.holder(sale, SOL);
+      measure("donate", await local.send(
+        await local.methods.donate(bn(SOL))
+          .accountsPartial({ donor: local.payer.publicKey, config: P.config, eventStream: P.eventStream, solVault: P.solVault })
+          .instruction(),
+      ));
+    });
+
+    it("snapshot, publish, challenge and claim", async () => {
+      measure("snapshot_surplus", await local.send(
+        await local.methods.snapshotSurplus(bn(EPOCH))
+          .accountsPartial({
+            payer: local.payer.publicKey, config: P.config, eventStream: P.eventStream, mint: sale.mint,
+            solVault: P.solVault, tokenVaultAccount: sale.tokenVaultAccount, distribution,
+          })
+          .instruction(),
+      ));
+      await publish(Buffer.alloc(32, 7));
+      measure("challenge_distribution_root", await local.send(
+        await local.methods.challengeDistributionRoot(bn(EPOCH))
+          .accountsPartial({
+            challenger: b.kp.publicKey, config: P.config, eventStream: P.eventStream, distribution,
+            challengerTokenAccount: b.ata,
+          })
+          .instruction(),
+        [b.kp],
+      ));
+
+      const leaves = [
+        { claimant: a.kp.publicKey, amount: 3n * SOL / 4n },
+        { claimant: b.kp.publicKey, amount: SOL / 4n },
+      ];
+      const tree = merkle(leaves.map(l => leafHash(EPOCH, l.claimant, l.amount)));
+      await publish(tree.root, b.kp.publicKey); // settles b's bond: refunded
+      await local.warp(3 * DAY); // DISTRIBUTION_CHALLENGE_SECS
+      measure("claim_distribution", await local.send(
+        await local.methods.claimDistribution(bn(EPOCH), bn(leaves[0].amount), tree.proofs[0].map(p => [...p]))
+          .accountsPartial({
+            claimant: a.kp.publicKey, config: P.config, eventStream: P.eventStream, distribution,
+            claim: pda(PROGRAM_ID, Buffer.from("distribution_claim"), u64le(EPOCH), a.kp.publicKey.toBuffer()),
+          })
+          .instruction(),
+        [a.kp],
+      ));
+    });
+
+    it("finalize into the creator escrow and release vested tokens", async () => {
+      measure("finalize", await local.finalize(sale, { creatorVesting: vesting, creatorEscrow: escrow }));
+      await local.warp(2 * DAY);
+      const beneficiaryAta = await local.ata(sale.mint, beneficiary.publicKey);
+      measure("release_creator_vesting", await local.send(
+        await local.methods.releaseCreatorVesting()
+          .accountsPartial({
+            backer: beneficiary.publicKey, config: P.config, solVault: P.solVault, eventStream: P.eventStream,
+            creatorVesting: vesting, creatorEscrow: escrow, beneficiaryTokenAccount: beneficiaryAta,
+          })
+          .instruction(),
+        [beneficiary],
+      ));
+    });
+
+    it("close the distribution after its claim window", async () => {
+      await local.warp(90 * DAY); // DISTRIBUTION_CLAIM_DAYS
+      measure("close_distribution", await local.send(
+        await local.methods.closeDistribution(bn(EPOCH))
+          .accountsPartial({
+            config: P.config, eventStream: P.eventStream, solVault: P.solVault, distribution, payer: local.payer.publicKey,
+          })
+          .instruction(),
+      ));
+    });
+  });
+
+  describe("token-backed sale", () => {
+    it("initialize, buy and redeem against the backing vault", async () => {
+      const local = await Local.start();
+      const sale = await local.initialize({ kind: "token" });
+      measure("initialize_token_backed", sale.init);
+      const user = await local.user();
+      const ata = await local.ata(sale.mint, user.publicKey);
+      const backingAta = await local.ata(sale.backingMint!, user.publicKey);
+      await local.mintTo(sale.backingMint!, backingAta, 10n * USDC);
+      const accounts = {
+        mint: sale.mint, mintAuthority: P.mintAuthority, config: P.config, ledger: P.ledger(),
+        tokenVault: P.tokenVault, tokenVaultAccount: sale.tokenVaultAccount, userTokenAccount: ata,
+        backingMint: sale.backingMint!, backingVault: P.backingVault, userBackingAccount: backingAta,
+        backingTokenProgram: sale.backingTokenProgram!,
+      };
+      measure("buy_with_backing", await local.send(
+        await local.methods.buyWithBacking(bn(5n * USDC)).accountsPartial({ buyer: user.publicKey, ...accounts }).instruction(),
+        [user],
+      ));
+      measure("redeem_for_backing", await local.send(
+        await local.methods.redeemForBacking(bn(2n * SOL)).accountsPartial({ redeemer: user.publicKey, ...accounts }).instruction(),
+        [user],
+      ));
+    });
+  });
+
+  describe("legacy migration", () => {
+    it("migrate_from_legacy, including the legacy redeem CPI", async () => {
+      const local = await Local.start({ legacy: true });
+      const sale = await local.initialize();
+      const holder = await local.user();
+      const ata = await local.ata(sale.mint, holder.publicKey);
+      const legacy = await local.seedLegacy(holder.publicKey, 5n * SOL);
+      measure("migrate_from_legacy", await local.send(await local.migrateIx(sale, legacy, holder, ata, SOL), [holder]));
+    });
+  });
+
+  describe("baseline", () => {
+    const names: string[] = IDL.instructions.map((ix: { name: string }) => ix.name);
+
+    it("has a ceiling for, and measured, every instruction in the IDL", () => {
+      if (process.env.CU_BASELINE !== "update") assert.deepEqual(Object.keys(baseline).sort(), [...names].sort());
+      assert.deepEqual([...measured.keys()].sort(), [...names].sort());
+    });
+
+    it("keeps every instruction within its ceiling", () => {
+      console.table(Object.fromEntries(names.map(n => [n, { measured: measured.get(n), ceiling: baseline[n] }])));
+      if (process.env.CU_BASELINE === "update") {
+        const next = Object.fromEntries(names.map(n => [n, ceiling(measured.get(n)!)]));
+        fs.writeFileSync(BASELINE, JSON.stringify(next, null, 2) + "\n");
+        return;
+      }
+      const over = names
+        .filter(n => measured.get(n)! > baseline[n])
+        .map(n => `${n}: ${measured.get(n)} CUs > ${baseline[n]}`);
+      assert.deepEqual(over, []);
+    });
+  });
+});
@@ -0,0 +1,78 @@
+// tests/local/delegated.spec.ts
+// `redeem_delegated`: an SPL delegate redeems up to its allowance, on the burn and the
+// vault path; the SOL goes to the owner or the owner's recorded destination.
+import assert from "node:assert/strict";
+import { Keypair, PublicKey } from "@solana/web3.js";
+import { createApproveInstruction } from "@solana/spl-token";
+import { Local, Sale, PROGRAM_ID, SOL, bn, big, pda, eventOf } from "./harness";
+
+describe("redeem_delegated", () => {
+  let local: Local;
+  let sale: Sale;
+  let owner: { kp: Keypair; ata: PublicKey };
+  let bot: Keypair;
+  let cold: PublicKey;
+
+  const record = () => pda(PROGRAM_ID, Buffer.from("redeem_destination"), owner.kp.publicKey.toBuffer());
+  const redeem = (delegate: Keypair, destination: PublicKey, amount: bigint) => local.methods.redeemDelegated(bn(amount))
+    .accountsPartial({
+      delegate: delegate.publicKey, destination, redeemDestination: record(), ...local.vaultAccounts(sale),
+      userTokenAccount: owner.ata,
+    })
+    .instruction();
+  const approve = (amount: bigint) =>
+    local.send(createApproveInstruction(owner.ata, bot.publicKey, owner.kp.publicKey, amount), [owner.kp]);
+
+  before(async () => {
+    local = await Local.start();
+    sale = await local.initialize();
+    owner = await local.holder(sale, 10n * SOL);
+    bot = await local.user();
+    cold = Keypair.generate().publicKey;
+    await approve(2n * SOL);
+  });
+
+  it("burns within the allowance and pays the owner", async () => {
+    const sol0 = await local.lamports(owner.kp.publicKey);
+    const ev = eventOf(await local.send(await redeem(bot, owner.kp.publicKey, SOL), [bot]), "RedeemBurnEvent");
+    assert.ok(ev.redeemer.equals(bot.publicKey));
+    assert.ok(ev.recipient.equals(owner.kp.publicKey));
+    assert.equal(await local.lamports(owner.kp.publicKey), sol0 + SOL);
+    assert.equal((await local.tokenAccount(owner.ata)).delegatedAmount, SOL);
+  });
+
+  it("rejects more than the remaining allowance", async () => {
+    await local.sendErr(await redeem(bot, owner.kp.publicKey, SOL + 1n), [bot], "DelegateAllowanceExceeded");
+  });
+
+  it("rejects a signer that is not the delegate", async () => {
+    const stranger = await local.user();
+    await local.sendErr(await redeem(stranger, owner.kp.publicKey, 1n), [stranger], "NotDelegate");
+  });
+
+  it("pays only the destination the owner recorded", async () => {
+    await local.send(
+      await local.methods.setRedeemDestination(cold)
+        .accountsPartial({ owner: owner.kp.publicKey, redeemDestination: record() })
+        .instruction(),
+      [owner.kp],
+    );
+    await local.sendErr(await redeem(bot, owner.kp.publicKey, SOL / 2n), [bot], "WrongRedeemDestination");
+    await local.sendErr(await redeem(bot, bot.publicKey, SOL / 2n), [bot], "WrongRedeemDestination");
+    await local.send(await redeem(bot, cold, SOL / 2n), [bot]);
+    assert.equal(await local.lamports(cold), SOL / 2n);
+  });
+
+  it("restocks within the allowance after finalization", async () => {
+    await local.finalize(sale);
+    await approve(SOL);
+    const inventory0 = await local.balance(sale.tokenVaultAccount);
+    await local.sendErr(await redeem(bot, cold, SOL + 1n), [bot], "DelegateAllowanceExceeded");
+
+    const sent = await local.send(await redeem(bot, cold, SOL), [bot]);
+
+    assert.equal(big(eventOf(sent, "RedeemVaultEvent").amount), SOL);
+    assert.equal(await local.balance(sale.tokenVaultAccount), inventory0 + SOL);
+    assert.equal((await local.tokenAccount(owner.ata)).delegatedAmount, 0n);
+  });
+});
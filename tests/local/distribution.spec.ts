@@ -0,0 +1,167 @@
+// tests/local/distribution.spec.ts
+// Surplus distribution: roots published by the crank fixed at initialize, with a challenge
+// window (challenges escrow a bond, lost if the crank republishes the same root), then
+// Merkle claims (once per leaf, proofs checked on chain).
+import assert from "node:assert/strict";
+import { Keypair, PublicKey } from "@solana/web3.js";
+import { Local, Sale, P, PROGRAM_ID, SOL, bn, big, pda, u64le, eventOf } from "./harness";
+import { leafHash, merkle } from "../../scripts/merkle";
+
+const CHALLENGE_SECS = 3 * 86_400; // DISTRIBUTION_CHALLENGE_SECS
+const BOND = SOL; // DISTRIBUTION_CHALLENGE_BOND
+
+describe("surplus distribution", () => {
+  let local: Local;
+  let sale: Sale;
+  let crank: Keypair;
+  let a: { kp: Keypair; ata: PublicKey };
+  let b: { kp: Keypair; ata: PublicKey };
+  let tree: { root: Buffer; proofs: Buffer[][] };
+  let leaves: { claimant: PublicKey; amount: bigint }[];
+
+  const EPOCH = 0n;
+  const distribution = pda(PROGRAM_ID, Buffer.from("distribution"), u64le(EPOCH));
+  const claimPda = (claimant: PublicKey) =>
+    pda(PROGRAM_ID, Buffer.from("distribution_claim"), u64le(EPOCH), claimant.toBuffer());
+
+  const snapshot = (l: Local, s: Sale) => l.methods.snapshotSurplus(bn(EPOCH))
+    .accountsPartial({
+      payer: l.payer.publicKey, config: P.config, eventStream: P.eventStream, mint: s.mint,
+      solVault: P.solVault, tokenVaultAccount: s.tokenVaultAccount, distribution,
+    })
+    .instruction();
+  const publish = (publisher: Keypair, root: Buffer, total: bigint, challenger: PublicKey | null = null) => local.methods
+    .publishDistributionRoot([...root], bn(total))
+    .accountsPartial({
+      publisher: publisher.publicKey, config: P.config, eventStream: P.eventStream, solVault: P.solVault, distribution, challenger,
+    })
+    .instruction();
+  const challenge = (holder: { kp: Keypair; ata: PublicKey }) => local.methods.challengeDistributionRoot(bn(EPOCH))
+    .accountsPartial({
+      challenger: holder.kp.publicKey, config: P.config, eventStream: P.eventStream, distribution,
+      challengerTokenAccount: holder.ata,
+    })
+    .instruction();
+  const claim = (claimant: Keypair, amount: bigint, proof: Buffer[]) => local.methods
+    .claimDistribution(bn(EPOCH), bn(amount), proof.map(p => [...p]))
+    .accountsPartial({
+      claimant: claimant.publicKey, config: P.config, eventStream: P.eventStream, distribution, claim: claimPda(claimant.publicKey),
+    })
+    .instruction();
+
+  before(async () => {
+    // program-owned vault: the surplus is exactly the donation
+    local = await Local.start();
+    crank = await local.user();
+    sale = await local.initialize({ kind: "program", distributionCrank: crank.publicKey });
+    a = await local.holder(sale, 3n * SOL);
+    b = await local.holder(sale, SOL);
+    await local.send(
+      await local.methods.donate(bn(SOL))
+        .accountsPartial({ donor: local.payer.publicKey, config: P.config, eventStream: P.eventStream, solVault: P.solVault })
+        .instruction(),
+    );
+    await local.send(await snapshot(local, sale));
+    leaves = [
+      { claimant: a.kp.publicKey, amount: 3n * SOL / 4n },
+      { claimant: b.kp.publicKey, amount: SOL / 4n },
+    ];
+    tree = merkle(leaves.map(l => leafHash(EPOCH, l.claimant, l.amount)));
+  });
+
+  it("snapshots the donated surplus against the circulating supply", async () => {
+    const d = await local.fetch("distribution", distribution);
+    assert.equal(big(d.lamports), SOL);
+    assert.equal(big(d.circulating), 4n * SOL);
+  });
+
+  it("snapshots nothing without a crank, and token-backed configs take none", async () => {
+    const other = await Local.start();
+    const otherSale = await other.initialize({ kind: "program" });
+    await other.sendErr(await snapshot(other, otherSale), [], "NoDistributionCrank");
+    await assert.rejects(
+      (await Local.start()).initialize({ kind: "token", distributionCrank: crank.publicKey }),
+      /DistributionRequiresSolBacking/,
+    );
+  });
+
+  it("lets only the crank publish, and never more than the snapshot", async () => {
+    const other = await local.user();
+    await local.sendErr(await publish(other, tree.root, SOL), [other], "NotDistributionCrank");
+    await local.sendErr(await publish(crank, tree.root, SOL + 1n), [crank], "DistributionExceedsSurplus");
+    const sent = await local.send(await publish(crank, Buffer.alloc(32, 7), SOL), [crank]);
+    assert.ok(eventOf(sent, "DistributionRootPublishedEvent").publisher.equals(crank.publicKey));
+  });
+
+  it("keeps claims closed during the challenge window", async () => {
+    await local.sendErr(await claim(a.kp, leaves[0].amount, tree.proofs[0]), [a.kp], "DistributionChallengeOpen");
+  });
+
+  it("lets a holder with enough stake clear a root by escrowing a bond", async () => {
+    const dust = await local.holder(sale, 1_000n); // < 0.1% of the snapshot supply
+    await local.sendErr(await challenge(dust), [dust.kp], "ChallengeStakeTooSmall");
+
+    const held0 = await local.lamports(distribution);
+    const sol0 = await local.lamports(b.kp.publicKey);
+    const sent = await local.send(await challenge(b), [b.kp]);
+    const ev = eventOf(sent, "DistributionRootChallengedEvent");
+    assert.deepEqual([...ev.merkleRoot], [...Buffer.alloc(32, 7)]);
+    assert.equal(big(ev.bond), BOND);
+    assert.equal(await local.lamports(b.kp.publicKey), sol0 - BOND);
+    assert.equal(await local.lamports(distribution), held0 + BOND);
+    const d = await local.fetch("distribution", distribution);
+    assert.deepEqual([...d.merkleRoot], [...Buffer.alloc(32)]);
+    assert.ok(d.challenger.equals(b.kp.publicKey));
+    await local.sendErr(await claim(a.kp, leaves[0].amount, tree.proofs[0]), [a.kp], "DistributionRootMissing");
+  });
+
+  it("forfeits the bond to the vault when the crank republishes the same root", async () => {
+    const vault0 = await local.lamports(P.solVault);
+    const donated0 = big((await local.config()).donatedLamports);
+    const sent = await local.send(await publish(crank, Buffer.alloc(32, 7), SOL), [crank]);
+    const ev = eventOf(sent, "ChallengeBondSettledEvent");
+    assert.ok(ev.forfeited);
+    assert.equal(big(ev.bond), BOND);
+    assert.equal(await local.lamports(P.solVault), vault0 + BOND);
+    assert.equal(big((await local.config()).donatedLamports), donated0 + BOND);
+    assert.ok((await local.fetch("distribution", distribution)).challenger.equals(PublicKey.default));
+  });
+
+  it("refunds the bond when the crank publishes a corrected root; challenges end with the window", async () => {
+    await local.send(await challenge(b), [b.kp]);
+    await local.sendErr(await publish(crank, tree.root, SOL), [crank], "WrongChallenger");
+    await local.sendErr(await publish(crank, tree.root, SOL, a.kp.publicKey), [crank], "WrongChallenger");
+
+    const sol0 = await local.lamports(b.kp.publicKey);
+    const sent = await local.send(await publish(crank, tree.root, SOL, b.kp.publicKey), [crank]);
+    assert.equal(eventOf(sent, "ChallengeBondSettledEvent").forfeited, false);
+    assert.equal(await local.lamports(b.kp.publicKey), sol0 + BOND);
+    await local.warp(CHALLENGE_SECS);
+    await local.sendErr(await challenge(b), [b.kp], "DistributionChallengeClosed");
+  });
+
+  it("pays a valid claim", async () => {
+    const sol0 = await local.lamports(a.kp.publicKey);
+    const sent = await local.send(await claim(a.kp, leaves[0].amount, tree.proofs[0]), [a.kp]);
+    assert.equal(big(eventOf(sent, "DistributionClaimedEvent").amount), 3n * SOL / 4n);
+    const receiptRent = await local.rent(8 + 8 + 32 + 8);
+    assert.equal(await local.lamports(a.kp.publicKey), sol0 + 3n * SOL / 4n - receiptRent);
+    assert.equal(big((await local.fetch("distribution", distribution)).claimed), 3n * SOL / 4n);
+  });
+
+  it("rejects a second claim of the same leaf", async () => {
+    await local.sendErr(await claim(a.kp, leaves[0].amount, tree.proofs[0]), [a.kp], "already in use");
+  });
+
+  it("rejects invalid proofs", async () => {
+    // wrong amount for a real claimant
+    await local.sendErr(await claim(b.kp, SOL / 2n, tree.proofs[1]), [b.kp], "InvalidMerkleProof");
+    // someone else's proof
+    await local.sendErr(await claim(b.kp, leaves[1].amount, tree.proofs[0]), [b.kp], "InvalidMerkleProof");
+    // not a leaf at all
+    const outsider = await local.user();
+    await local.sendErr(await claim(outsider, leaves[1].amount, tree.proofs[1]), [outsider], "InvalidMerkleProof");
+    // the real leaf still pays
+    await local.send(await claim(b.kp, leaves[1].amount, tree.proofs[1]), [b.kp]);
+  });
+});
@@ -0,0 +1,42 @@
+// tests/local/event_cpi.spec.ts
+// Self-CPI events: every event is an inner instruction to this program signed by the
+// `__event_authority` PDA, and nobody else can emit one.
+import assert from "node:assert/strict";
+import { Keypair, PublicKey, TransactionInstruction } from "@solana/web3.js";
+import { Local, Sale, PROGRAM_ID, SOL, pda } from "./harness";
+
+const EVENT_IX_TAG_LE = Buffer.from("e445a52e51cb9a1d", "hex");
+
+describe("self-CPI events", () => {
+  let local: Local;
+  let sale: Sale;
+  let holder: { kp: Keypair; ata: PublicKey };
+
+  const eventAuthority = pda(PROGRAM_ID, Buffer.from("__event_authority"));
+  /** A hand-made event instruction carrying a copy of a real event's payload. */
+  const forged = (authority: PublicKey, isSigner: boolean, payload: Buffer) => new TransactionInstruction({
+    programId: PROGRAM_ID,
+    keys: [{ pubkey: authority, isSigner, isWritable: false }],
+    data: Buffer.concat([EVENT_IX_TAG_LE, payload]),
+  });
+
+  before(async () => {
+    local = await Local.start();
+    sale = await local.initialize();
+    holder = await local.holder(sale, 0n);
+  });
+
+  it("emits each event as a self-invocation", async () => {
+    const sent = await local.send(await local.buyIx(sale, holder.kp, holder.ata, SOL), [holder.kp]);
+    const selfCalls = sent.logs.filter(l => l === `Program ${PROGRAM_ID.toBase58()} invoke [2]`);
+    assert.equal(selfCalls.length, sent.events.length);
+    assert.ok(selfCalls.length > 0);
+  });
+
+  it("rejects an event instruction not signed by the event authority", async () => {
+    const payload = Buffer.alloc(64, 1);
+    await local.sendErr(forged(eventAuthority, false, payload), [], "ConstraintSigner");
+    const impostor = await local.user();
+    await local.sendErr(forged(impostor.publicKey, true, payload), [impostor], "ConstraintSeeds");
+  });
+});
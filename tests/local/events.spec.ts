@@ -0,0 +1,47 @@
+// tests/local/events.spec.ts
+// Event post-state: slot, timestamp and balances as they stand after the instruction;
+// failed transactions leave no gap in the sequence.
+import assert from "node:assert/strict";
+import { Keypair, PublicKey } from "@solana/web3.js";
+import { Local, Sale, P, SOL, big, eventOf } from "./harness";
+
+describe("event post-state", () => {
+  let local: Local;
+  let sale: Sale;
+  let holder: { kp: Keypair; ata: PublicKey };
+
+  before(async () => {
+    local = await Local.start();
+    sale = await local.initialize();
+    holder = await local.holder(sale, 0n);
+  });
+
+  it("matches the clock and the accounts after a buy", async () => {
+    await local.warp(60);
+    const clock = await local.client.getClock();
+
+    const state = eventOf(await local.send(await local.buyIx(sale, holder.kp, holder.ata, 3n * SOL), [holder.kp]), "BuyMintEvent").state;
+
+    assert.equal(big(state.slot), clock.slot);
+    assert.equal(big(state.unixTimestamp), clock.unixTimestamp);
+    assert.equal(big(state.vaultLamports), await local.lamports(P.solVault));
+    assert.equal(big(state.tokenVaultAmount), await local.balance(sale.tokenVaultAccount));
+    assert.equal(big(state.mintSupply), await local.supply(sale.mint));
+    assert.equal(big(state.userBalance), 3n * SOL);
+  });
+
+  it("matches the accounts after a redeem", async () => {
+    const state = eventOf(await local.send(await local.redeemIx(sale, holder.kp, holder.ata, SOL), [holder.kp]), "RedeemBurnEvent").state;
+    assert.equal(big(state.vaultLamports), await local.lamports(P.solVault));
+    assert.equal(big(state.mintSupply), await local.supply(sale.mint));
+    assert.equal(big(state.userBalance), 2n * SOL);
+  });
+
+  it("does not consume a sequence number on a failed instruction", async () => {
+    const seq0 = await local.vaultSeq();
+    await local.sendErr(await local.redeemIx(sale, holder.kp, holder.ata, 3n * SOL), [holder.kp], "InsufficientTokens");
+    assert.equal(await local.vaultSeq(), seq0);
+    const state = eventOf(await local.send(await local.redeemIx(sale, holder.kp, holder.ata, SOL), [holder.kp]), "RedeemBurnEvent").state;
+    assert.equal(big(state.seq), seq0);
+  });
+});
@@ -0,0 +1,428 @@
+// tests/local/harness.ts
+// In-process local validator (solana-bankrun) shared by the tests/local suites.
+// Build first: `npm run build:local` (fair_token with the `localnet` + `log-events` features;
+// `localnet` swaps ADMIN for tests/fixtures/admin.json, `log-events` puts events in the logs).
+// The migration suite also needs tests/fixtures/legacy_fair_token.so (`npm run legacy-fixture`).
+import fs from "fs";
+import path from "path";
+import crypto from "crypto";
+import assert from "node:assert/strict";
+import * as anchor from "@coral-xyz/anchor";
+import { BN } from "bn.js";
+import {
+  PublicKey, Keypair, SystemProgram, Transaction, TransactionInstruction,
+  ComputeBudgetProgram, AccountMeta, LAMPORTS_PER_SOL,
+} from "@solana/web3.js";
+import {
+  TOKEN_PROGRAM_ID, MINT_SIZE, ACCOUNT_SIZE, MintLayout, AccountLayout, AccountState,
+  createInitializeMint2Instruction, createAssociatedTokenAccountIdempotentInstruction, createMintToInstruction,
+  getAssociatedTokenAddressSync,
+} from "@solana/spl-token";
+import { start, ProgramTestContext, BanksClient, Clock } from "solana-bankrun";
+import { BankrunProvider } from "anchor-bankrun";
+
+const ROOT = path.resolve(__dirname, "../..");
+process.env.SBF_OUT_DIR ??= path.join(ROOT, "target/deploy");
+
+export const IDL = JSON.parse(fs.readFileSync(path.join(ROOT, "target/idl/fair_token.json"), "utf8"));
+export const PROGRAM_ID = new PublicKey(IDL.address);
+export const LEGACY_PROGRAM_ID = new PublicKey("EGxd8LCM8Y1uMyXrWWapEMh9tH2whZaNBYhaV29Mq9fb");
+export const TOKEN_METADATA_ID = new PublicKey("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+export const PRIMARY_VAULT = 255;
+export const DAY = 86_400;
+export const SOL = BigInt(LAMPORTS_PER_SOL);
+export const ADMIN = Keypair.fromSecretKey(
+  Uint8Array.from(JSON.parse(fs.readFileSync(path.join(ROOT, "tests/fixtures/admin.json"), "utf8"))),
+);
+
+export const bn = (n: bigint | number) => new BN(n.toString());
+export const u64le = (n: bigint | number) => { const b = Buffer.alloc(8); b.writeBigUInt64LE(BigInt(n)); return b; };
+
+// Same seeds as `fair_token::pda`
+export const pda = (pid: PublicKey, ...seeds: (Buffer | Uint8Array)[]) => PublicKey.findProgramAddressSync(seeds, pid)[0];
+export const PDAS = (pid: PublicKey) => ({
+  config:         pda(pid, Buffer.from("config")),
+  mintAuthority:  pda(pid, Buffer.from("mint_authority")),
+  solVault:       pda(pid, Buffer.from("sol_vault")),
+  tokenVault:     pda(pid, Buffer.from("token_vault")),
+  eventAuthority: pda(pid, Buffer.from("__event_authority")),
+  priceFeed:      pda(pid, Buffer.from("price_feed")),
+  eventStream:    pda(pid, Buffer.from("event_stream")),
+  buyQueue:       pda(pid, Buffer.from("buy_queue")),
+  backingVault:   pda(pid, Buffer.from("backing_vault")),
+  migration:      pda(pid, Buffer.from("migration")),
+  ledger:         (index = PRIMARY_VAULT) => pda(pid, Buffer.from("vault_ledger"), Buffer.from([index])),
+  solVaultShard:  (index: number) => pda(pid, Buffer.from("sol_vault"), Buffer.from([index])),
+  tokenVaultShard: (index: number) => pda(pid, Buffer.from("token_vault_account"), Buffer.from([index])),
+});
+export const P = PDAS(PROGRAM_ID);
+const LEGACY = PDAS(LEGACY_PROGRAM_ID);
+
+export type Sent = { logs: string[]; cu: number; events: { name: string; data: any }[] };
+export type VaultKind = "system" | "program" | "token";
+
+export type Sale = {
+  mint: PublicKey;
+  tokenVaultAccount: PublicKey;
+  saleEnd: bigint;
+  backingMint?: PublicKey; // token-backed sales only
+  backingTokenProgram?: PublicKey;
+  init: Sent; // the initialize transaction
+};
+
+/** Seeded legacy (v1) sale: its mint, vault token account and the holder's legacy account. */
+export type LegacySale = { mint: PublicKey; tokenVaultAccount: PublicKey; holderAccount: PublicKey };
+
+export type InitOpts = {
+  kind?: VaultKind;
+  saleDays?: number;
+  vaultCeiling?: bigint | null;
+  creatorAllocation?: { beneficiary: PublicKey; bps: number; cliffSecs: number; vestingSecs: number } | null;
+  referralRewards?: { rewardBps: number; maxRewardPerReferrer: bigint } | null;
+  distributionCrank?: PublicKey | null;
+  backingMint?: PublicKey; // kind "token": backing mint (default: a fresh 6-decimal SPL mint)
+  backingTokenProgram?: PublicKey;
+};
+
+/** Serialized Token Metadata `Metadata` with the update authority revoked (what `check_metadata_locked` wants). */
+function lockedMetadata(mint: PublicKey): Buffer {
+  const str = (s: string) => { const b = Buffer.from(s); const l = Buffer.alloc(4); l.writeUInt32LE(b.length); return Buffer.concat([l, b]); };
+  return Buffer.concat([
+    Buffer.from([4]),                 // Key::MetadataV1
+    Buffer.alloc(32),                 // update_authority = 11111111111111111111111111111111 (revoked)
+    mint.toBuffer(),
+    str("Fair"), str("FAIR"), str(""),
+    Buffer.from([0, 0]),              // seller_fee_basis_points
+    Buffer.from([0, 0, 0]),           // creators, primary_sale_happened, is_mutable
+    Buffer.from([0, 0, 0, 0, 0, 0]),  // edition_nonce .. programmable_config
+  ]);
+}
+
+export class Local {
+  private nonce = 0;
+  readonly events: anchor.EventParser;
+
+  private constructor(
+    readonly context: ProgramTestContext,
+    readonly client: BanksClient,
+    readonly provider: BankrunProvider,
+    readonly program: anchor.Program<any>,
+  ) {
+    this.events = new anchor.EventParser(PROGRAM_ID, new anchor.BorshCoder(IDL));
+  }
+
+  /** Fresh bank with fair_token (and, with `legacy`, the legacy program at `LEGACY_PROGRAM_ID`). */
+  static async start(opts: { legacy?: boolean } = {}): Promise<Local> {
+    const programs = [{ name: "fair_token", programId: PROGRAM_ID }];
+    if (opts.legacy) programs.push({ name: "legacy_fair_token", programId: LEGACY_PROGRAM_ID });
+    const context = await start(programs, [{
+      address: ADMIN.publicKey,
+      info: { lamports: 1_000 * LAMPORTS_PER_SOL, data: Buffer.alloc(0), owner: SystemProgram.programId, executable: false },
+    }]);
+    const provider = new BankrunProvider(context);
+    return new Local(context, context.banksClient, provider, new anchor.Program(IDL, provider));
+  }
+
+  get payer(): Keypair { return this.context.payer; }
+  get methods(): any { return this.program.methods; }
+
+  // ---- transactions ----
+
+  private async process(ixs: TransactionInstruction[], signers: Keypair[], feePayer = this.payer) {
+    // A distinct compute limit per transaction keeps otherwise identical transactions apart
+    const tx = new Transaction().add(
+      ComputeBudgetProgram.setComputeUnitLimit({ units: 1_400_000 - (this.nonce++ % 100_000) }),
+      ...ixs,
+    );
+    tx.recentBlockhash = this.context.lastBlockhash;
+    tx.feePayer = feePayer.publicKey;
+    tx.sign(feePayer, ...signers.filter(s => !s.publicKey.equals(feePayer.publicKey)));
+    const res = await this.client.tryProcessTransaction(tx);
+    const logs = res.meta?.logMessages ?? [];
+    return { err: res.result, logs };
+  }
+
+  /** Send and require success. `cu` is what the fair_token top-level instructions consumed. */
+  async send(ixs: TransactionInstruction | TransactionInstruction[], signers: Keypair[] = [], feePayer = this.payer): Promise<Sent> {
+    const { err, logs } = await this.process([ixs].flat(), signers, feePayer);
+    if (err) throw new Error(`transaction failed: ${err}\n${logs.join("\n")}`);
+    const stack: string[] = [];
+    let cu = 0;
+    for (const l of logs) {
+      let m = l.match(/^Program (\w+) invoke \[(\d+)\]/);
+      if (m) { stack.length = Number(m[2]) - 1; stack.push(m[1]); continue; }
+      m = l.match(/^Program (\w+) consumed (\d+) of/);
+      if (m && stack.length === 1 && m[1] === PROGRAM_ID.toBase58()) cu += Number(m[2]);
+      if (/^Program \w+ (success|failed)/.test(l)) stack.pop();
+    }
+    return { logs, cu, events: [...this.events.parseLogs(logs)] };
+  }
+
+  /** Send and require failure with `code` (an `ErrorCode` name, or any text in the logs). */
+  async sendErr(ixs: TransactionInstruction | TransactionInstruction[], signers: Keypair[], code: string): Promise<string[]> {
+    const { err, logs } = await this.process([ixs].flat(), signers);
+    assert.ok(err, `expected ${code}, transaction succeeded`);
+    const hit = logs.some(l => l.includes(`Error Code: ${code}.`) || l.includes(code)) || String(err).includes(code);
+    assert.ok(hit, `expected ${code}, got ${err}\n${logs.join("\n")}`);
+    return logs;
+  }
+
+  // ---- clock / accounts ----
+
+  async now(): Promise<bigint> { return (await this.client.getClock()).unixTimestamp; }
+
+  async warp(secs: number): Promise<void> {
+    const c = await this.client.getClock();
+    this.context.setClock(new Clock(c.slot + 1n, c.epochStartTimestamp, c.epoch, c.leaderScheduleEpoch, c.unixTimestamp + BigInt(secs)));
+  }
+
+  async lamports(key: PublicKey): Promise<bigint> { return BigInt((await this.client.getAccount(key))?.lamports ?? 0); }
+
+  async exists(key: PublicKey): Promise<boolean> { return (await this.client.getAccount(key)) !== null; }
+
+  async rent(space: number): Promise<bigint> { return (await this.client.getRent()).minimumBalance(BigInt(space)); }
+
+  setAccount(address: PublicKey, lamports: bigint | number, data: Buffer, owner: PublicKey) {
+    this.context.setAccount(address, { lamports: Number(lamports), data, owner, executable: false });
+  }
+
+  async user(sol = 100n): Promise<Keypair> {
+    const kp = Keypair.generate();
+    this.setAccount(kp.publicKey, sol * SOL, Buffer.alloc(0), SystemProgram.programId);
+    return kp;
+  }
+
+  fetch(name: string, key: PublicKey): Promise<any> { return (this.program.account as any)[name].fetch(key); }
+
+  // ---- SPL ----
+
+  async tokenAccount(key: PublicKey) {
+    const acc = await this.client.getAccount(key);
+    assert.ok(acc, `token account ${key.toBase58()} missing`);
+    return AccountLayout.decode(Buffer.from(acc.data));
+  }
+
+  async balance(key: PublicKey): Promise<bigint> { return (await this.client.getAccount(key)) ? (await this.tokenAccount(key)).amount : 0n; }
+
+  async supply(mint: PublicKey): Promise<bigint> {
+    return MintLayout.decode(Buffer.from((await this.client.getAccount(mint))!.data)).supply;
+  }
+
+  /** SPL mint with no freeze authority and `authority` as mint authority, plus locked metadata. */
+  async createMint(authority: Keypair = ADMIN, decimals = 9, programId = TOKEN_PROGRAM_ID): Promise<PublicKey> {
+    const mint = Keypair.generate();
+    await this.send([
+      SystemProgram.createAccount({
+        fromPubkey: this.payer.publicKey, newAccountPubkey: mint.publicKey,
+        lamports: Number(await this.rent(MINT_SIZE)), space: MINT_SIZE, programId,
+      }),
+      createInitializeMint2Instruction(mint.publicKey, decimals, authority.publicKey, null, programId),
+    ], [mint]);
+    const data = lockedMetadata(mint.publicKey);
+    this.setAccount(metadataPda(mint.publicKey), await this.rent(data.length), data, TOKEN_METADATA_ID);
+    return mint.publicKey;
+  }
+
+  async ata(mint: PublicKey, owner: PublicKey, programId = TOKEN_PROGRAM_ID): Promise<PublicKey> {
+    const ata = getAssociatedTokenAddressSync(mint, owner, true, programId);
+    await this.send(createAssociatedTokenAccountIdempotentInstruction(this.payer.publicKey, ata, owner, mint, programId));
+    return ata;
+  }
+
+  /** Mint `amount` of an ADMIN-authority mint (e.g. a backing mint) to `to`. */
+  async mintTo(mint: PublicKey, to: PublicKey, amount: bigint, programId = TOKEN_PROGRAM_ID) {
+    await this.send(createMintToInstruction(mint, to, ADMIN.publicKey, amount, [], programId), [ADMIN]);
+  }
+
+  /** Overwrite a token account's state (e.g. freeze it; only possible through setAccount here). */
+  async setTokenState(key: PublicKey, state: AccountState) {
+    const acc = (await this.client.getAccount(key))!;
+    const decoded = AccountLayout.decode(Buffer.from(acc.data));
+    const data = Buffer.alloc(ACCOUNT_SIZE);
+    AccountLayout.encode({ ...decoded, state }, data);
+    this.setAccount(key, acc.lamports, data, TOKEN_PROGRAM_ID);
+  }
+
+  // ---- fair_token ----
+
+  /** `initialize` (system-owned SOL vault), `initialize_program_vault` or `initialize_token_backed`. */
+  async initialize(opts: InitOpts = {}): Promise<Sale> {
+    const mint = await this.createMint();
+    const tokenVaultAccount = Keypair.generate();
+    const saleEnd = (await this.now()) + BigInt((opts.saleDays ?? 60) * DAY);
+    const creator = opts.creatorAllocation
+      ? { ...opts.creatorAllocation, cliffSecs: bn(opts.creatorAllocation.cliffSecs), vestingSecs: bn(opts.creatorAllocation.vestingSecs) }
+      : null;
+    const referral = opts.referralRewards
+      ? { rewardBps: opts.referralRewards.rewardBps, maxRewardPerReferrer: bn(opts.referralRewards.maxRewardPerReferrer) }
+      : null;
+    const kind = opts.kind ?? "system";
+    const method = { system: "initialize", program: "initializeProgramVault", token: "initializeTokenBacked" }[kind];
+    const ceiling = opts.vaultCeiling == null ? null : bn(opts.vaultCeiling);
+    const backingTokenProgram = opts.backingTokenProgram ?? TOKEN_PROGRAM_ID;
+    const backing = kind === "token"
+      ? { backingMint: opts.backingMint ?? await this.createMint(ADMIN, 6), backingVault: P.backingVault, backingTokenProgram }
+      : { solVault: P.solVault };
+    const init = await this.send(
+      await this.methods[method](bn(saleEnd), ceiling, creator, referral, opts.distributionCrank ?? null)
+        .accountsPartial({
+          admin: ADMIN.publicKey, mint, mintAuthority: P.mintAuthority, config: P.config, ...backing,
+          tokenVault: P.tokenVault, tokenVaultAccount: tokenVaultAccount.publicKey,
+          eventStream: P.eventStream, ledger: P.ledger(),
+          tokenMetadataProgram: TOKEN_METADATA_ID, metadata: metadataPda(mint),
+        })
+        .instruction(),
+      [ADMIN, tokenVaultAccount],
+    );
+    return {
+      mint, tokenVaultAccount: tokenVaultAccount.publicKey, saleEnd, init,
+      ...(kind === "token" ? { backingMint: (backing as any).backingMint, backingTokenProgram } : {}),
+    };
+  }
+
+  /** Accounts shared by the primary-vault buy/redeem instructions. */
+  vaultAccounts(sale: Sale) {
+    return {
+      mint: sale.mint, mintAuthority: P.mintAuthority, config: P.config, ledger: P.ledger(),
+      buyQueue: P.buyQueue, solVault: P.solVault, tokenVault: P.tokenVault, tokenVaultAccount: sale.tokenVaultAccount,
+    };
+  }
+
+  buyIx(sale: Sale, buyer: Keypair, userTokenAccount: PublicKey, lamports: bigint, referral: PublicKey | null = null): Promise<TransactionInstruction> {
+    return this.methods.buyFairToken(bn(lamports))
+      .accountsPartial({ buyer: buyer.publicKey, ...this.vaultAccounts(sale), userTokenAccount, referral })
+      .instruction();
+  }
+
+  redeemIx(sale: Sale, redeemer: Keypair, userTokenAccount: PublicKey, amount: bigint, remaining: AccountMeta[] = []): Promise<TransactionInstruction> {
+    return this.methods.redeemFairToken(bn(amount))
+      .accountsPartial({ redeemer: redeemer.publicKey, ...this.vaultAccounts(sale), userTokenAccount })
+      .remainingAccounts(remaining)
+      .instruction();
+  }
+
+  /** New funded user with an ATA holding `amount` bought during the sale. */
+  async holder(sale: Sale, amount: bigint, sol = 100n): Promise<{ kp: Keypair; ata: PublicKey }> {
+    const kp = await this.user(sol);
+    const ata = await this.ata(sale.mint, kp.publicKey);
+    if (amount > 0n) await this.send(await this.buyIx(sale, kp, ata, amount), [kp]);
+    return { kp, ata };
+  }
+
+  /** Warp past `sale_end` and run `finalize` (no creator allocation). */
+  async finalize(sale: Sale, extra: Record<string, PublicKey | null> = {}): Promise<Sent> {
+    const now = await this.now();
+    if (now <= sale.saleEnd) await this.warp(Number(sale.saleEnd - now) + 1);
+    return this.send(await this.methods.finalize()
+      .accountsPartial({
+        config: P.config, mint: sale.mint, mintAuthority: P.mintAuthority, tokenVaultAccount: sale.tokenVaultAccount,
+        creatorVesting: null, creatorEscrow: null, ...extra,
+      })
+      .instruction());
+  }
+
+  /**
+   * Legacy (v1) state mid-sale at `LEGACY_PROGRAM_ID`: `supply` minted to `holder`, fully
+   * backed by the legacy SOL vault. Seeded directly (its `initialize` is gated to the
+   * production ADMIN); needs `Local.start({ legacy: true })`.
+   */
+  async seedLegacy(holder: PublicKey, supply: bigint): Promise<LegacySale> {
+    const mint = Keypair.generate().publicKey;
+    const tokenVaultAccount = Keypair.generate().publicKey;
+    const holderAccount = getAssociatedTokenAddressSync(mint, holder);
+
+    const mintData = packMint(LEGACY.mintAuthority, supply);
+    this.setAccount(mint, await this.rent(mintData.length), mintData, TOKEN_PROGRAM_ID);
+    for (const [key, owner, amount] of [
+      [tokenVaultAccount, LEGACY.tokenVault, 0n],
+      [holderAccount, holder, supply],
+    ] as [PublicKey, PublicKey, bigint][]) {
+      const data = packTokenAccount(mint, owner, amount);
+      this.setAccount(key, await this.rent(data.length), data, TOKEN_PROGRAM_ID);
+    }
+
+    // v1 `Config` (Borsh): initialized, finalized, mint, sol_vault, token_vault,
+    // token_vault_account, sale_end, min_supply_base_units, total_burned
+    const saleEnd = Buffer.alloc(8);
+    saleEnd.writeBigInt64LE((await this.now()) + BigInt(60 * DAY));
+    const config = Buffer.concat([
+      accountDiscriminator("Config"), Buffer.from([1, 0]),
+      mint.toBuffer(), LEGACY.solVault.toBuffer(), LEGACY.tokenVault.toBuffer(), tokenVaultAccount.toBuffer(),
+      saleEnd, Buffer.alloc(16),
+    ]);
+    this.setAccount(LEGACY.config, await this.rent(config.length), config, LEGACY_PROGRAM_ID);
+    const tokenVault = Buffer.concat([accountDiscriminator("TokenVault"), Buffer.from([0])]);
+    this.setAccount(LEGACY.tokenVault, await this.rent(tokenVault.length), tokenVault, LEGACY_PROGRAM_ID);
+    this.setAccount(LEGACY.solVault, (await this.rent(0)) + supply, Buffer.alloc(0), SystemProgram.programId);
+    return { mint, tokenVaultAccount, holderAccount };
+  }
+
+  migrateIx(
+    sale: Sale, legacy: LegacySale, holder: Keypair, userTokenAccount: PublicKey, amount: bigint,
+    overrides: Record<string, PublicKey> = {},
+  ): Promise<TransactionInstruction> {
+    return this.methods.migrateFromLegacy(bn(amount))
+      .accountsPartial({
+        holder: holder.publicKey, ...this.vaultAccounts(sale), userTokenAccount,
+        migrationAuthority: P.migration,
+        legacyMint: legacy.mint,
+        holderLegacyAccount: legacy.holderAccount,
+        migrationLegacyAccount: getAssociatedTokenAddressSync(legacy.mint, P.migration, true),
+        legacyMintAuthority: LEGACY.mintAuthority,
+        legacyConfig: LEGACY.config,
+        legacySolVault: LEGACY.solVault,
+        legacyTokenVault: LEGACY.tokenVault,
+        legacyTokenVaultAccount: legacy.tokenVaultAccount,
+        legacyProgram: LEGACY_PROGRAM_ID,
+        ...overrides,
+      })
+      .instruction();
+  }
+
+  config(): Promise<any> { return this.fetch("config", P.config); }
+  ledger(index = PRIMARY_VAULT): Promise<any> { return this.fetch("vaultLedger", P.ledger(index)); }
+  /** Next `seq` of the config-wide event stream (config-level events). */
+  async seq(): Promise<bigint> { return big((await this.fetch("eventStream", P.eventStream)).seq); }
+  /** Next `seq` of vault `index`'s events (`VaultLedger.event_seq`). */
+  async vaultSeq(index = PRIMARY_VAULT): Promise<bigint> { return big((await this.ledger(index)).eventSeq); }
+}
+
+export const metadataPda = (mint: PublicKey) =>
+  pda(TOKEN_METADATA_ID, Buffer.from("metadata"), TOKEN_METADATA_ID.toBuffer(), mint.toBuffer());
+
+export const big = (v: { toString(): string }) => BigInt(v.toString());
+
+/** Anchor account discriminator of `name` (for seeding foreign program state). */
+export const accountDiscriminator = (name: string) =>
+  crypto.createHash("sha256").update(`account:${name}`).digest().subarray(0, 8);
+
+export function packMint(mintAuthority: PublicKey | null, supply: bigint, decimals = 9): Buffer {
+  const data = Buffer.alloc(MINT_SIZE);
+  MintLayout.encode({
+    mintAuthorityOption: mintAuthority ? 1 : 0, mintAuthority: mintAuthority ?? PublicKey.default,
+    supply, decimals, isInitialized: true, freezeAuthorityOption: 0, freezeAuthority: PublicKey.default,
+  }, data);
+  return data;
+}
+
+export function packTokenAccount(mint: PublicKey, owner: PublicKey, amount: bigint): Buffer {
+  const data = Buffer.alloc(ACCOUNT_SIZE);
+  AccountLayout.encode({
+    mint, owner, amount, delegateOption: 0, delegate: PublicKey.default, state: AccountState.Initialized,
+    isNativeOption: 0, isNative: 0n, delegatedAmount: 0n, closeAuthorityOption: 0, closeAuthority: PublicKey.default,
+  }, data);
+  return data;
+}
+
+/** The single event named `name` in a sent transaction. */
+export function eventOf(sent: Sent, name: string): any {
+  const hits = sent.events.filter(e => e.name === name);
+  assert.equal(hits.length, 1, `expected one ${name}, got ${sent.events.map(e => e.name).join(", ") || "none"}`);
+  return hits[0].data;
+}
+
+/** `ix` with `key` passed writable (e.g. the mint, read-only in the shard IDL, when it mints or burns). */
+export function writable(ix: TransactionInstruction, key: PublicKey): TransactionInstruction {
+  for (const meta of ix.keys) if (meta.pubkey.equals(key)) meta.isWritable = true;
+  return ix;
+}
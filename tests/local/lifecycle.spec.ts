@@ -0,0 +1,101 @@
+// tests/local/lifecycle.spec.ts
+// Initializer-created accounts (primary ledger, event stream), the per-vault and
+// config-wide event sequences, and the window between `sale_end` and `finalize`.
+import assert from "node:assert/strict";
+import { Keypair, PublicKey } from "@solana/web3.js";
+import { Local, Sale, P, PRIMARY_VAULT, SOL, bn, big, eventOf } from "./harness";
+
+describe("initializers", () => {
+  for (const kind of ["system", "program"] as const) {
+    it(`create the primary ledger and the event stream (${kind} vault)`, async () => {
+      const local = await Local.start();
+      await local.initialize({ kind });
+      const ledger = await local.ledger();
+      assert.equal(ledger.index, PRIMARY_VAULT);
+      assert.equal(big(ledger.lamportsIn), 0n);
+      assert.equal(await local.seq(), 1n); // InitializedEvent took seq 0
+      assert.equal(big(ledger.eventSeq), 0n);
+    });
+  }
+
+  it("open_vault_ledger no longer opens the primary ledger", async () => {
+    const local = await Local.start();
+    await local.initialize();
+    await local.sendErr(
+      await local.methods.openVaultLedger(PRIMARY_VAULT)
+        .accountsPartial({ payer: local.payer.publicKey, config: P.config, ledger: P.ledger() })
+        .instruction(),
+      [], "InvalidShard",
+    );
+  });
+});
+
+describe("event sequence", () => {
+  let local: Local;
+  let sale: Sale;
+  let holder: { kp: Keypair; ata: PublicKey };
+
+  before(async () => {
+    local = await Local.start();
+    sale = await local.initialize();
+    holder = await local.holder(sale, 0n);
+  });
+
+  it("numbers vault events per vault and config-level events on the event stream", async () => {
+    const vaultSeq0 = await local.vaultSeq();
+    const seq0 = await local.seq();
+    const buy = await local.send(await local.buyIx(sale, holder.kp, holder.ata, SOL), [holder.kp]);
+    const donate = await local.send(
+      await local.methods.donate(bn(1_000n))
+        .accountsPartial({ donor: holder.kp.publicKey, config: P.config, solVault: P.solVault })
+        .instruction(),
+      [holder.kp],
+    );
+    const redeem = await local.send(await local.redeemIx(sale, holder.kp, holder.ata, SOL / 2n), [holder.kp]);
+
+    const bought = eventOf(buy, "BuyMintEvent").state;
+    const redeemed = eventOf(redeem, "RedeemBurnEvent").state;
+    assert.deepEqual([bought.vault, redeemed.vault], [PRIMARY_VAULT, PRIMARY_VAULT]);
+    assert.equal(big(bought.seq), vaultSeq0);
+    assert.equal(big(redeemed.seq), vaultSeq0 + 1n);
+    assert.equal(await local.vaultSeq(), vaultSeq0 + 2n);
+    // buys and redeems leave the config-wide stream alone
+    assert.equal(big(eventOf(donate, "DonationEvent").seq), seq0);
+    assert.equal(await local.seq(), seq0 + 1n);
+  });
+});
+
+describe("after sale_end, before finalize", () => {
+  let local: Local;
+  let sale: Sale;
+  let holder: { kp: Keypair; ata: PublicKey };
+
+  before(async () => {
+    local = await Local.start();
+    sale = await local.initialize();
+    holder = await local.holder(sale, 2n * SOL);
+    await local.warp(Number(sale.saleEnd - (await local.now())) + 1);
+  });
+
+  it("rejects buys with FinalizationPending", async () => {
+    await local.sendErr(await local.buyIx(sale, holder.kp, holder.ata, SOL), [holder.kp], "FinalizationPending");
+  });
+
+  it("still redeems, on the burn path", async () => {
+    const supply0 = await local.supply(sale.mint);
+    const vault0 = await local.lamports(P.solVault);
+    const sent = await local.send(await local.redeemIx(sale, holder.kp, holder.ata, SOL), [holder.kp]);
+    const ev = eventOf(sent, "RedeemBurnEvent");
+    assert.equal(big(ev.amount), SOL);
+    assert.equal(await local.supply(sale.mint), supply0 - SOL);
+    assert.equal(await local.lamports(P.solVault), vault0 - SOL);
+    assert.equal(await local.balance(holder.ata), SOL);
+    assert.equal(big((await local.ledger()).totalRedeemedBurn), SOL);
+  });
+
+  it("finalize then reopens buys from the vault", async () => {
+    await local.finalize(sale);
+    const sent = await local.send(await local.buyIx(sale, holder.kp, holder.ata, SOL / 4n), [holder.kp]);
+    eventOf(sent, "BuyVaultEvent");
+  });
+});
@@ -0,0 +1,86 @@
+// tests/local/migration.spec.ts
+// `migrate_from_legacy` end to end: a holder of the legacy (audited v1) token at
+// LEGACY_PROGRAM_ID moves to this program, with both programs deployed side by side.
+// The legacy sale state is seeded directly (`Local.seedLegacy`).
+import assert from "node:assert/strict";
+import { Keypair, PublicKey } from "@solana/web3.js";
+import { Local, Sale, LegacySale, P, PDAS, LEGACY_PROGRAM_ID, PROGRAM_ID, SOL, big, eventOf } from "./harness";
+
+const L = PDAS(LEGACY_PROGRAM_ID);
+const LEGACY_SUPPLY = 5n * SOL;
+
+describe("migrate_from_legacy", () => {
+  let local: Local;
+  let sale: Sale;
+  let holder: Keypair;
+  let legacy: LegacySale;
+  let holderAta: PublicKey;
+
+  const migrateIx = (amount: bigint, overrides: Record<string, PublicKey> = {}) =>
+    local.migrateIx(sale, legacy, holder, holderAta, amount, overrides);
+
+  before(async () => {
+    local = await Local.start({ legacy: true });
+    sale = await local.initialize();
+    holder = await local.user();
+    holderAta = await local.ata(sale.mint, holder.publicKey);
+    legacy = await local.seedLegacy(holder.publicKey, LEGACY_SUPPLY);
+  });
+
+  it("is deployed under a different program ID than the legacy program", () => {
+    assert.ok(!PROGRAM_ID.equals(LEGACY_PROGRAM_ID));
+  });
+
+  it("migrates an amount below the rent-exempt minimum (holder funds the PDA reserve once)", async () => {
+    const amount = 1_000n;
+    const rent = await local.rent(0);
+    assert.ok(amount < rent);
+    assert.equal(await local.lamports(P.migration), 0n);
+    const vault0 = await local.lamports(P.solVault);
+    const legacyVault0 = await local.lamports(L.solVault);
+
+    const sent = await local.send(await migrateIx(amount), [holder]);
+
+    assert.equal(await local.lamports(P.migration), rent);
+    assert.equal(await local.lamports(P.solVault), vault0 + amount);
+    assert.equal(await local.lamports(L.solVault), legacyVault0 - amount);
+    assert.equal(await local.balance(holderAta), amount);
+    assert.equal(await local.balance(legacy.holderAccount), LEGACY_SUPPLY - amount);
+    const ev = eventOf(sent, "LegacyMigratedEvent");
+    assert.ok(ev.holder.equals(holder.publicKey));
+    assert.ok(ev.legacyMint.equals(legacy.mint));
+    assert.equal(big(ev.amount), amount);
+    assert.equal(ev.finalized, false);
+    assert.equal(big(ev.state.userBalance), amount);
+  });
+
+  it("migrates the rest 1:1: legacy tokens burned, SOL moved vault to vault, successor tokens minted", async () => {
+    const amount = LEGACY_SUPPLY - 1_000n;
+    const rent = await local.rent(0);
+    const holderSol0 = await local.lamports(holder.publicKey);
+    const vault0 = await local.lamports(P.solVault);
+    const supply0 = await local.supply(sale.mint);
+    const lamportsIn0 = big((await local.ledger()).lamportsIn);
+
+    await local.send(await migrateIx(amount), [holder]);
+
+    assert.equal(await local.balance(legacy.holderAccount), 0n);
+    assert.equal(await local.supply(legacy.mint), 0n);
+    assert.equal(await local.lamports(L.solVault), rent);
+    assert.equal(await local.lamports(P.solVault), vault0 + amount);
+    assert.equal(await local.supply(sale.mint), supply0 + amount);
+    assert.equal(await local.balance(holderAta), LEGACY_SUPPLY);
+    assert.equal(big((await local.ledger()).lamportsIn), lamportsIn0 + amount);
+    // reserve already funded: the holder pays no top-up (fee payer is the harness payer)
+    assert.equal(await local.lamports(holder.publicKey), holderSol0);
+    assert.equal(await local.lamports(P.migration), rent);
+  });
+
+  it("rejects any legacy program other than LEGACY_PROGRAM_ID", async () => {
+    await local.sendErr(await migrateIx(1n, { legacyProgram: PROGRAM_ID }), [holder], "InvalidLegacyProgram");
+  });
+
+  it("rejects the successor mint as the legacy mint", async () => {
+    await local.sendErr(await migrateIx(1n, { legacyMint: sale.mint, holderLegacyAccount: holderAta }), [holder], "WrongMint");
+  });
+});
@@ -0,0 +1,82 @@
+// tests/local/price_feed.spec.ts
+// `refresh_price`: the price feed PDA is created on first use, refreshed from the vaults,
+// and readable at the documented byte offsets without the IDL.
+import assert from "node:assert/strict";
+import { Keypair, PublicKey } from "@solana/web3.js";
+import { Local, Sale, P, PROGRAM_ID, SOL, big } from "./harness";
+
+const U64_MAX = 2n ** 64n - 1n;
+
+describe("price feed", () => {
+  let local: Local;
+  let sale: Sale;
+  let holder: { kp: Keypair; ata: PublicKey };
+
+  const refresh = (reserveVault = P.solVault) => local.methods.refreshPrice()
+    .accountsPartial({
+      payer: local.payer.publicKey, config: P.config, mint: sale.mint,
+      tokenVaultAccount: sale.tokenVaultAccount, reserveVault, priceFeed: P.priceFeed,
+    })
+    .instruction();
+  /** The documented layout, read straight from the account bytes. */
+  const raw = async () => {
+    const acc = (await local.client.getAccount(P.priceFeed))!;
+    const d = Buffer.from(acc.data);
+    return {
+      owner: new PublicKey(acc.owner),
+      version: d.readUInt8(8),
+      mint: new PublicKey(d.subarray(9, 41)),
+      quoteMint: new PublicKey(d.subarray(41, 73)),
+      price: d.readBigUInt64LE(73),
+      priceDecimals: d.readUInt8(81),
+      tokenDecimals: d.readUInt8(82),
+      backingRatioBps: d.readBigUInt64LE(83),
+      backing: d.readBigUInt64LE(91),
+      circulating: d.readBigUInt64LE(99),
+      lastUpdatedSlot: d.readBigUInt64LE(107),
+      lastUpdatedTs: d.readBigInt64LE(115),
+    };
+  };
+
+  before(async () => {
+    local = await Local.start();
+    sale = await local.initialize();
+    holder = await local.holder(sale, 0n);
+  });
+
+  it("creates the feed on first use, with nothing circulating", async () => {
+    assert.equal(await local.exists(P.priceFeed), false);
+    await local.send(await refresh());
+    const f = await raw();
+    assert.ok(f.owner.equals(PROGRAM_ID));
+    assert.equal(f.version, 1);
+    assert.ok(f.mint.equals(sale.mint));
+    assert.ok(f.quoteMint.equals(PublicKey.default));
+    assert.equal(f.price, SOL);
+    assert.equal(f.priceDecimals, 9);
+    assert.equal(f.tokenDecimals, 9);
+    assert.equal(f.circulating, 0n);
+    assert.equal(f.backingRatioBps, U64_MAX);
+  });
+
+  it("refreshes backing, circulating and the update time at the documented offsets", async () => {
+    await local.send(await local.buyIx(sale, holder.kp, holder.ata, 4n * SOL), [holder.kp]);
+    await local.warp(30);
+    const clock = await local.client.getClock();
+
+    await local.send(await refresh());
+
+    const f = await raw();
+    assert.equal(f.circulating, 4n * SOL);
+    assert.equal(f.backing, await local.lamports(P.solVault));
+    assert.equal(f.lastUpdatedSlot, clock.slot);
+    assert.equal(f.lastUpdatedTs, clock.unixTimestamp);
+    // the IDL view agrees with the byte layout
+    const feed = await local.fetch("priceFeed", P.priceFeed);
+    assert.equal(big(feed.backingRatioBps), f.backingRatioBps);
+  });
+
+  it("rejects any reserve vault other than the config's", async () => {
+    await local.sendErr(await refresh(P.backingVault), [], "InvalidVault");
+  });
+});
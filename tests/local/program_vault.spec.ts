@@ -0,0 +1,64 @@
+// tests/local/program_vault.spec.ts
+// `initialize_program_vault`: the SOL vault is program-owned with a `SolVault` header whose
+// `backing_lamports` is the exact backing; buys add to it, redeems debit the vault directly.
+import assert from "node:assert/strict";
+import { Keypair, PublicKey, SystemProgram } from "@solana/web3.js";
+import { Local, Sale, P, PROGRAM_ID, SOL, big, eventOf } from "./harness";
+
+describe("initialize_program_vault", () => {
+  let local: Local;
+  let sale: Sale;
+  let holder: { kp: Keypair; ata: PublicKey };
+  let rent: bigint;
+
+  const backing = async () => big((await local.fetch("solVault", P.solVault)).backingLamports);
+
+  before(async () => {
+    local = await Local.start();
+    sale = await local.initialize({ kind: "program" });
+    holder = await local.holder(sale, 0n);
+    rent = await local.rent(8 + 8);
+  });
+
+  it("creates a program-owned SOL vault holding only its rent", async () => {
+    const vault = await local.client.getAccount(P.solVault);
+    assert.ok(vault);
+    assert.ok(vault.owner.equals(PROGRAM_ID));
+    assert.equal(await backing(), 0n);
+    assert.equal(BigInt(vault.lamports), rent);
+    const config = await local.config();
+    assert.equal(config.solVaultProgramOwned, 1);
+    assert.ok(config.solVault.equals(P.solVault));
+    assert.ok(config.backingMint.equals(PublicKey.default));
+  });
+
+  it("records buys in backing_lamports", async () => {
+    const sent = await local.send(await local.buyIx(sale, holder.kp, holder.ata, 3n * SOL), [holder.kp]);
+    assert.equal(await backing(), 3n * SOL);
+    assert.equal(await local.lamports(P.solVault), rent + 3n * SOL);
+    assert.equal(big(eventOf(sent, "BuyMintEvent").state.vaultLamports), 3n * SOL);
+  });
+
+  it("pays redeems by direct debit and reduces backing_lamports", async () => {
+    const sol0 = await local.lamports(holder.kp.publicKey);
+    const sent = await local.send(await local.redeemIx(sale, holder.kp, holder.ata, SOL), [holder.kp]);
+    assert.equal(await backing(), 2n * SOL);
+    assert.equal(await local.lamports(P.solVault), rent + 2n * SOL);
+    assert.equal(await local.lamports(holder.kp.publicKey), sol0 + SOL);
+    assert.equal(big(eventOf(sent, "RedeemBurnEvent").state.vaultLamports), 2n * SOL);
+  });
+
+  it("drains the backing to zero without touching the rent", async () => {
+    await local.send(await local.redeemIx(sale, holder.kp, holder.ata, 2n * SOL), [holder.kp]);
+    assert.equal(await backing(), 0n);
+    assert.equal(await local.lamports(P.solVault), rent);
+    await local.sendErr(await local.redeemIx(sale, holder.kp, holder.ata, 1n), [holder.kp], "InsufficientTokens");
+  });
+
+  it("does not count plain transfers to the vault as backing", async () => {
+    const donor = await local.user();
+    await local.send(SystemProgram.transfer({ fromPubkey: donor.publicKey, toPubkey: P.solVault, lamports: 1_000 }), [donor]);
+    assert.equal(await backing(), 0n);
+    assert.equal(await local.lamports(P.solVault), rent + 1_000n);
+  });
+});
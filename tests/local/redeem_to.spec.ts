@@ -0,0 +1,47 @@
+// tests/local/redeem_to.spec.ts
+// `redeem_to`: the redeemer's tokens, SOL paid to another account; the event records it.
+import assert from "node:assert/strict";
+import { Keypair, PublicKey } from "@solana/web3.js";
+import { Local, Sale, P, SOL, bn, big, eventOf } from "./harness";
+
+describe("redeem_to", () => {
+  let local: Local;
+  let sale: Sale;
+  let holder: { kp: Keypair; ata: PublicKey };
+  let recipient: PublicKey;
+
+  const redeemTo = (redeemer: Keypair, userTokenAccount: PublicKey, amount: bigint) => local.methods.redeemTo(bn(amount))
+    .accountsPartial({ redeemer: redeemer.publicKey, recipient, ...local.vaultAccounts(sale), userTokenAccount })
+    .instruction();
+
+  before(async () => {
+    local = await Local.start();
+    sale = await local.initialize();
+    holder = await local.holder(sale, 5n * SOL);
+    recipient = Keypair.generate().publicKey;
+  });
+
+  it("pays the SOL to the recipient and records it in the event", async () => {
+    const holderSol0 = await local.lamports(holder.kp.publicKey);
+    const vault0 = await local.lamports(P.solVault);
+
+    const ev = eventOf(await local.send(await redeemTo(holder.kp, holder.ata, 2n * SOL), [holder.kp]), "RedeemBurnEvent");
+
+    assert.ok(ev.redeemer.equals(holder.kp.publicKey));
+    assert.ok(ev.recipient.equals(recipient));
+    assert.equal(big(ev.amount), 2n * SOL);
+    assert.equal(await local.lamports(recipient), 2n * SOL);
+    assert.equal(await local.lamports(holder.kp.publicKey), holderSol0);
+    assert.equal(await local.lamports(P.solVault), vault0 - 2n * SOL);
+    assert.equal(await local.balance(holder.ata), 3n * SOL);
+  });
+
+  it("rejects a zero redeem", async () => {
+    await local.sendErr(await redeemTo(holder.kp, holder.ata, 0n), [holder.kp], "ZeroTokenRedeem");
+  });
+
+  it("rejects redeeming tokens the signer does not own", async () => {
+    const thief = await local.user();
+    await local.sendErr(await redeemTo(thief, holder.ata, SOL), [thief], "ConstraintRaw");
+  });
+});
@@ -0,0 +1,104 @@
+// tests/local/referral.spec.ts
+// Referral rewards: accrual on referred buys, the per-referrer cap, and claims against a
+// reward pool that can run dry (what it cannot pay stays claimable).
+import assert from "node:assert/strict";
+import { Keypair, PublicKey, SystemProgram } from "@solana/web3.js";
+import { Local, Sale, P, PROGRAM_ID, SOL, big, pda, eventOf } from "./harness";
+
+const REWARD_BPS = 100; // 1%
+const CAP = SOL / 20n; // 0.05 SOL per referrer
+
+describe("referral rewards", () => {
+  let local: Local;
+  let sale: Sale;
+  let referrer: Keypair;
+  let buyer: { kp: Keypair; ata: PublicKey };
+  let poolRent: bigint;
+
+  const pool = pda(PROGRAM_ID, Buffer.from("referral_pool"));
+  const referralPda = (key: PublicKey) => pda(PROGRAM_ID, Buffer.from("referral"), key.toBuffer());
+  const referral = () => local.fetch("referral", referralPda(referrer.publicKey));
+
+  const referredBuy = async (who: { kp: Keypair; ata: PublicKey }, lamports: bigint) => eventOf(
+    await local.send(await local.buyIx(sale, who.kp, who.ata, lamports, referralPda(referrer.publicKey)), [who.kp]),
+    "BuyMintEvent",
+  );
+  const claim = () => local.methods.claimReferralRewards()
+    .accountsPartial({
+      referrer: referrer.publicKey, config: P.config, eventStream: P.eventStream,
+      referral: referralPda(referrer.publicKey), referralPool: pool,
+    })
+    .instruction();
+  const fundPool = (lamports: bigint) => local.send(
+    SystemProgram.transfer({ fromPubkey: local.payer.publicKey, toPubkey: pool, lamports: Number(lamports) }),
+  );
+
+  before(async () => {
+    local = await Local.start();
+    sale = await local.initialize({ referralRewards: { rewardBps: REWARD_BPS, maxRewardPerReferrer: CAP } });
+    referrer = await local.user();
+    buyer = await local.holder(sale, 0n);
+    poolRent = await local.rent(0);
+    await local.send(
+      await local.methods.openReferral()
+        .accountsPartial({ payer: local.payer.publicKey, referrer: referrer.publicKey, referral: referralPda(referrer.publicKey) })
+        .instruction(),
+    );
+  });
+
+  it("accrues the reward on a referred buy and reports it in the buy event", async () => {
+    const ev = await referredBuy(buyer, 2n * SOL);
+    assert.ok(ev.referrer.equals(referrer.publicKey));
+    assert.equal(big(ev.referralReward), 2n * SOL / 100n);
+    const r = await referral();
+    assert.equal(big(r.buys), 1n);
+    assert.equal(big(r.lamports), 2n * SOL);
+    assert.equal(big(r.rewardsAccrued), 2n * SOL / 100n);
+  });
+
+  it("rejects referring oneself with the same key", async () => {
+    const self = { kp: referrer, ata: await local.ata(sale.mint, referrer.publicKey) };
+    await local.sendErr(
+      await local.buyIx(sale, self.kp, self.ata, SOL, referralPda(referrer.publicKey)),
+      [referrer], "SelfReferral",
+    );
+  });
+
+  it("stops accruing at the per-referrer cap", async () => {
+    // 1% of 5 SOL is 0.05 SOL, but only 0.03 SOL of the cap is left
+    assert.equal(big((await referredBuy(buyer, 5n * SOL)).referralReward), CAP - 2n * SOL / 100n);
+    // further referred buys are still attributed, without reward
+    assert.equal(big((await referredBuy(buyer, SOL)).referralReward), 0n);
+    const r = await referral();
+    assert.equal(big(r.buys), 3n);
+    assert.equal(big(r.rewardsAccrued), CAP);
+  });
+
+  it("fails while the pool holds nothing above its rent minimum", async () => {
+    await fundPool(poolRent);
+    await local.sendErr(await claim(), [referrer], "NoReferralRewards");
+  });
+
+  it("pays what an underfunded pool covers and keeps the rest claimable", async () => {
+    await fundPool(SOL / 100n);
+    const sol0 = await local.lamports(referrer.publicKey);
+
+    const ev = eventOf(await local.send(await claim(), [referrer]), "ReferralRewardClaimedEvent");
+
+    assert.equal(big(ev.amount), SOL / 100n);
+    assert.equal(big(ev.rewardsPaid), SOL / 100n);
+    assert.equal(big(ev.rewardsAccrued), CAP);
+    assert.equal(await local.lamports(referrer.publicKey), sol0 + SOL / 100n);
+    assert.equal(await local.lamports(pool), poolRent);
+    // exhausted: nothing more until the pool is refunded
+    await local.sendErr(await claim(), [referrer], "NoReferralRewards");
+  });
+
+  it("pays the remainder once the pool is refunded, and nothing beyond the accrual", async () => {
+    await fundPool(SOL);
+    const ev = eventOf(await local.send(await claim(), [referrer]), "ReferralRewardClaimedEvent");
+    assert.equal(big(ev.amount), CAP - SOL / 100n);
+    assert.equal(big(ev.rewardsPaid), CAP);
+    await local.sendErr(await claim(), [referrer], "NoReferralRewards");
+  });
+});
@@ -0,0 +1,67 @@
+// tests/local/return_data.spec.ts
+// Buy and redeem return `BuyResult` / `RedeemResult` as instruction return data: the
+// path taken, amounts and the post-state.
+import assert from "node:assert/strict";
+import { Keypair, PublicKey } from "@solana/web3.js";
+import { Local, Sale, PROGRAM_ID, SOL, bn, big } from "./harness";
+
+describe("buy / redeem return data", () => {
+  let local: Local;
+  let sale: Sale;
+  let holder: { kp: Keypair; ata: PublicKey };
+
+  /** Decodes the `Program return:` log of this program as IDL type `name`. */
+  const returned = (logs: string[], name: string) => {
+    const prefix = `Program return: ${PROGRAM_ID.toBase58()} `;
+    const line = logs.find(l => l.startsWith(prefix));
+    assert.ok(line, "no return data");
+    return local.program.coder.types.decode(name, Buffer.from(line.slice(prefix.length), "base64"));
+  };
+  const redeem = (amount: bigint, close = false) =>
+    (close ? local.methods.redeemFairTokenAndClose(bn(amount)) : local.methods.redeemFairToken(bn(amount)))
+      .accountsPartial({ redeemer: holder.kp.publicKey, ...local.vaultAccounts(sale), userTokenAccount: holder.ata })
+      .instruction();
+
+  before(async () => {
+    local = await Local.start();
+    sale = await local.initialize();
+    holder = await local.holder(sale, 0n);
+  });
+
+  it("buy before finalization: mint path", async () => {
+    const sent = await local.send(await local.buyIx(sale, holder.kp, holder.ata, 3n * SOL), [holder.kp]);
+    const r = returned(sent.logs, "BuyResult");
+    assert.deepEqual(r.path, { mint: {} });
+    assert.equal(big(r.amount), 3n * SOL);
+    assert.equal(big(r.lamports), 3n * SOL);
+    assert.equal(big(r.state.userBalance), 3n * SOL);
+  });
+
+  it("redeem before finalization: burn path", async () => {
+    const sent = await local.send(await redeem(SOL), [holder.kp]);
+    const r = returned(sent.logs, "RedeemResult");
+    assert.deepEqual(r.path, { burn: {} });
+    assert.equal(big(r.amount), SOL);
+    assert.equal(big(r.queueFilled), 0n);
+    assert.equal(r.closed, false);
+    assert.equal(big(r.state.mintSupply), await local.supply(sale.mint));
+  });
+
+  it("returns nothing when the instruction fails", async () => {
+    const logs = await local.sendErr(await redeem(10n * SOL), [holder.kp], "InsufficientTokens");
+    assert.equal(logs.some(l => l.startsWith("Program return:")), false);
+  });
+
+  it("after finalization: vault and restock paths, closed account reported", async () => {
+    await local.finalize(sale);
+    const buy = returned((await local.send(await local.buyIx(sale, holder.kp, holder.ata, SOL), [holder.kp])).logs, "BuyResult");
+    assert.deepEqual(buy.path, { vault: {} });
+    assert.equal(big(buy.state.tokenVaultAmount), await local.balance(sale.tokenVaultAccount));
+
+    const r = returned((await local.send(await redeem(3n * SOL, true), [holder.kp])).logs, "RedeemResult");
+    assert.deepEqual(r.path, { restock: {} });
+    assert.equal(big(r.lamports), 3n * SOL);
+    assert.equal(r.closed, true);
+    assert.equal(big(r.state.userBalance), 0n);
+  });
+});
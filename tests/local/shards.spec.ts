@@ -0,0 +1,134 @@
+// tests/local/shards.spec.ts
+// Vault shards: `open_vault_shard` funds the shard SOL vault with a rent reserve that no
+// redeem, rebalance or solvency figure counts, `vault_ceiling` caps each vault token
+// account on its own, and shard traffic numbers its events on the shard ledger with the
+// mint write-locked only when it mints or burns.
+import assert from "node:assert/strict";
+import { AccountMeta, Keypair, PublicKey } from "@solana/web3.js";
+import { Local, Sale, P, PRIMARY_VAULT, SOL, bn, big, eventOf, writable } from "./harness";
+
+const CEILING = 2n * SOL;
+
+describe("vault shards", () => {
+  let local: Local;
+  let sale: Sale;
+  let holder: { kp: Keypair; ata: PublicKey };
+  let reserve: bigint;
+
+  const shardAccounts = (index: number) => ({
+    mint: sale.mint, mintAuthority: P.mintAuthority, config: P.config,
+    ledger: P.ledger(index), buyQueue: P.buyQueue, solVaultShard: P.solVaultShard(index),
+    tokenVault: P.tokenVault, shardTokenAccount: P.tokenVaultShard(index),
+  });
+  const shardPairs = (): AccountMeta[] => [
+    { pubkey: P.solVaultShard(0), isWritable: false, isSigner: false },
+    { pubkey: P.tokenVaultShard(0), isWritable: false, isSigner: false },
+  ];
+  /** `mintWritable`: needed whenever the redemption burns. */
+  const redeemSharded = async (amount: bigint, mintWritable = true) => {
+    const ix = await local.methods.redeemSharded(0, bn(amount))
+      .accountsPartial({ redeemer: holder.kp.publicKey, ...shardAccounts(0), userTokenAccount: holder.ata })
+      .instruction();
+    return mintWritable ? writable(ix, sale.mint) : ix;
+  };
+  const buySharded = async (lamports: bigint, mintWritable = true) => {
+    const ix = await local.methods.buySharded(0, bn(lamports))
+      .accountsPartial({ buyer: holder.kp.publicKey, ...shardAccounts(0), userTokenAccount: holder.ata })
+      .instruction();
+    return mintWritable ? writable(ix, sale.mint) : ix;
+  };
+  const vaultIndex = (index: number) => index === PRIMARY_VAULT
+    ? { sol: P.solVault, tokens: sale.tokenVaultAccount }
+    : { sol: P.solVaultShard(index), tokens: P.tokenVaultShard(index) };
+  const rebalance = (from: number, to: number, lamports: bigint) => local.methods.rebalanceVaultShards(from, to, bn(lamports), bn(0))
+    .accountsPartial({
+      config: P.config, eventStream: P.eventStream, tokenVault: P.tokenVault,
+      fromSolVault: vaultIndex(from).sol, toSolVault: vaultIndex(to).sol,
+      fromTokenAccount: vaultIndex(from).tokens, toTokenAccount: vaultIndex(to).tokens,
+    })
+    .instruction();
+
+  before(async () => {
+    local = await Local.start();
+    sale = await local.initialize({ vaultCeiling: CEILING });
+    holder = await local.holder(sale, 10n * SOL);
+    reserve = await local.rent(0);
+    await local.send([
+      await local.methods.openVaultShard(0)
+        .accountsPartial({
+          payer: local.payer.publicKey, config: P.config, eventStream: P.eventStream, mint: sale.mint,
+          tokenVault: P.tokenVault, solVaultShard: P.solVaultShard(0), shardTokenAccount: P.tokenVaultShard(0),
+        })
+        .instruction(),
+      await local.methods.openVaultLedger(0)
+        .accountsPartial({ payer: local.payer.publicKey, config: P.config, ledger: P.ledger(0) })
+        .instruction(),
+    ]);
+  });
+
+  it("open_vault_shard funds the shard SOL vault with its rent reserve", async () => {
+    assert.equal(await local.lamports(P.solVaultShard(0)), reserve);
+  });
+
+  it("buy_sharded needs a writable mint to mint", async () => {
+    await local.sendErr(await buySharded(SOL, false), [holder.kp], "MintNotWritable");
+  });
+
+  it("buy_sharded reports the shard backing without the reserve, numbered on the shard ledger", async () => {
+    const seq0 = await local.seq();
+    const sent = await local.send(await buySharded(3n * SOL), [holder.kp]);
+    const state = eventOf(sent, "BuyShardEvent").state;
+    assert.equal(big(state.vaultLamports), 3n * SOL);
+    assert.equal(await local.lamports(P.solVaultShard(0)), reserve + 3n * SOL);
+    assert.equal(state.vault, 0);
+    assert.equal(big(state.seq), 0n);
+    assert.equal(await local.vaultSeq(0), 1n);
+    assert.equal(await local.seq(), seq0);
+  });
+
+  it("check_solvency leaves the reserve out of the vault backing", async () => {
+    const sent = await local.send(
+      await local.methods.checkSolvency()
+        .accountsPartial({ config: P.config, mint: sale.mint, reserveVault: P.solVault, tokenVaultAccount: sale.tokenVaultAccount })
+        .remainingAccounts(shardPairs())
+        .instruction(),
+    );
+    const ev = eventOf(sent, "SolvencyCheckedEvent");
+    assert.equal(big(ev.vaultLamports), (await local.lamports(P.solVault)) + 3n * SOL);
+    assert.equal(big(ev.circulating), 13n * SOL);
+  });
+
+  it("rebalance cannot move the reserve", async () => {
+    await local.sendErr(await rebalance(0, PRIMARY_VAULT, 3n * SOL + 1n), [], "VaultSOLInsufficient");
+  });
+
+  it("redeem_sharded drains the backing down to the reserve, never into it", async () => {
+    const sent = await local.send(await redeemSharded(3n * SOL), [holder.kp]);
+    assert.equal(big(eventOf(sent, "RedeemShardEvent").state.vaultLamports), 0n);
+    assert.equal(await local.lamports(P.solVaultShard(0)), reserve);
+    await local.sendErr(await redeemSharded(1n), [holder.kp], "VaultSOLInsufficient");
+  });
+
+  it("vault_ceiling caps each vault token account on its own", async () => {
+    await local.finalize(sale);
+    await local.send(await rebalance(PRIMARY_VAULT, 0, 3n * SOL));
+    const primary0 = await local.balance(sale.tokenVaultAccount);
+
+    // restocking below the ceiling only reads the mint
+    await local.send(await redeemSharded(SOL, false), [holder.kp]);
+    await local.sendErr(await redeemSharded(2n * SOL, false), [holder.kp], "MintNotWritable");
+    const sent = await local.send(await redeemSharded(2n * SOL), [holder.kp]);
+
+    const contracted = eventOf(sent, "SupplyContractedEvent");
+    assert.equal(contracted.vault, 0);
+    assert.equal(big(contracted.burned), SOL);
+    assert.equal(await local.balance(P.tokenVaultShard(0)), CEILING);
+    // the primary account keeps its own inventory, untouched by the shard's cap
+    assert.equal(await local.balance(sale.tokenVaultAccount), primary0);
+  });
+
+  it("post-finalization shard buys only read the mint", async () => {
+    const sent = await local.send(await buySharded(SOL, false), [holder.kp]);
+    assert.equal(big(eventOf(sent, "BuyShardEvent").state.tokenVaultAmount), CEILING - SOL);
+  });
+});
@@ -0,0 +1,147 @@
+// tests/local/surplus.spec.ts
+// `donate` records surplus in `Config.donated_lamports`; `refresh_price` and
+// `check_solvency` report the backing ratio above 1.0, for SOL and token backing alike.
+import assert from "node:assert/strict";
+import { Keypair, PublicKey } from "@solana/web3.js";
+import { Local, Sale, P, SOL, bn, big, eventOf } from "./harness";
+
+const USDC = 1_000_000n; // one backing token (6 decimals)
+const FAIR_PER_BACKING_UNIT = 1_000n; // 10^(9 - 6)
+
+function surplusViews(get: () => { local: Local; sale: Sale; reserveVault: PublicKey }) {
+  return {
+    refreshPrice: async () => {
+      const { local, sale, reserveVault } = get();
+      await local.send(
+        await local.methods.refreshPrice()
+          .accountsPartial({
+            payer: local.payer.publicKey, config: P.config, mint: sale.mint,
+            tokenVaultAccount: sale.tokenVaultAccount, reserveVault, priceFeed: P.priceFeed,
+          })
+          .instruction(),
+      );
+      return local.fetch("priceFeed", P.priceFeed);
+    },
+    checkSolvency: async () => {
+      const { local, sale, reserveVault } = get();
+      const sent = await local.send(
+        await local.methods.checkSolvency()
+          .accountsPartial({ config: P.config, mint: sale.mint, reserveVault, tokenVaultAccount: sale.tokenVaultAccount })
+          .instruction(),
+      );
+      return eventOf(sent, "SolvencyCheckedEvent");
+    },
+  };
+}
+
+describe("donate (SOL backing)", () => {
+  let local: Local;
+  let sale: Sale;
+  let donor: Keypair;
+  const views = surplusViews(() => ({ local, sale, reserveVault: P.solVault }));
+
+  const donate = async (lamports: bigint) => local.send(
+    await local.methods.donate(bn(lamports))
+      .accountsPartial({ donor: donor.publicKey, config: P.config, eventStream: P.eventStream, solVault: P.solVault })
+      .instruction(),
+    [donor],
+  );
+
+  before(async () => {
+    // program-owned vault: `backing_lamports` is exact, so the ratios are too
+    local = await Local.start();
+    sale = await local.initialize({ kind: "program" });
+    await local.holder(sale, 4n * SOL);
+    donor = await local.user();
+  });
+
+  it("reports exactly 1.0 before any donation", async () => {
+    assert.equal(big((await views.refreshPrice()).backingRatioBps), 10_000n);
+    assert.equal(big((await views.checkSolvency()).surplusLamports), 0n);
+  });
+
+  it("counts donations in donated_lamports and the event", async () => {
+    const first = eventOf(await donate(SOL), "DonationEvent");
+    assert.equal(big(first.lamports), SOL);
+    assert.equal(big(first.donatedLamports), SOL);
+    assert.equal(big(first.vaultLamports), 5n * SOL);
+
+    const second = eventOf(await donate(SOL / 2n), "DonationEvent");
+    assert.equal(big(second.donatedLamports), 3n * SOL / 2n);
+    assert.equal(big((await local.config()).donatedLamports), 3n * SOL / 2n);
+  });
+
+  it("refresh_price reports the ratio above 1.0", async () => {
+    const feed = await views.refreshPrice();
+    assert.equal(big(feed.backing), 11n * SOL / 2n);
+    assert.equal(big(feed.circulating), 4n * SOL);
+    assert.equal(big(feed.backingRatioBps), 13_750n); // 5.5 / 4
+  });
+
+  it("check_solvency reports the surplus and the donated part of it", async () => {
+    const ev = await views.checkSolvency();
+    assert.equal(big(ev.surplusLamports), 3n * SOL / 2n);
+    assert.equal(big(ev.donatedLamports), 3n * SOL / 2n);
+    assert.equal(big(ev.backingRatioBps), 13_750n);
+  });
+
+  it("rejects a zero donation", async () => {
+    await local.sendErr(
+      await local.methods.donate(bn(0))
+        .accountsPartial({ donor: donor.publicKey, config: P.config, eventStream: P.eventStream, solVault: P.solVault })
+        .instruction(),
+      [donor], "NoSOLSent",
+    );
+  });
+});
+
+describe("surplus (token backing)", () => {
+  let local: Local;
+  let sale: Sale;
+  let user: Keypair;
+  const views = surplusViews(() => ({ local, sale, reserveVault: P.backingVault }));
+
+  before(async () => {
+    local = await Local.start();
+    sale = await local.initialize({ kind: "token" });
+    user = await local.user();
+    const ata = await local.ata(sale.mint, user.publicKey);
+    const backingAta = await local.ata(sale.backingMint!, user.publicKey);
+    await local.mintTo(sale.backingMint!, backingAta, 4n * USDC);
+    await local.send(
+      await local.methods.buyWithBacking(bn(4n * USDC))
+        .accountsPartial({
+          buyer: user.publicKey, mint: sale.mint, mintAuthority: P.mintAuthority, config: P.config,
+          ledger: P.ledger(), tokenVault: P.tokenVault, tokenVaultAccount: sale.tokenVaultAccount,
+          userTokenAccount: ata, backingMint: sale.backingMint!, backingVault: P.backingVault,
+          userBackingAccount: backingAta, backingTokenProgram: sale.backingTokenProgram!,
+        })
+        .instruction(),
+      [user],
+    );
+  });
+
+  it("rejects donate: donations are SOL only", async () => {
+    await local.sendErr(
+      await local.methods.donate(bn(SOL))
+        .accountsPartial({ donor: user.publicKey, config: P.config, eventStream: P.eventStream, solVault: P.solVault })
+        .instruction(),
+      [user], "DonationRequiresSolBacking",
+    );
+  });
+
+  it("values backing-vault surplus in fair base units", async () => {
+    await local.mintTo(sale.backingMint!, P.backingVault, USDC); // unrecorded surplus
+    const feed = await views.refreshPrice();
+    assert.ok(feed.quoteMint.equals(sale.backingMint!));
+    assert.equal(big(feed.price), USDC);
+    assert.equal(big(feed.backing), 5n * USDC * FAIR_PER_BACKING_UNIT);
+    assert.equal(big(feed.circulating), 4n * SOL);
+    assert.equal(big(feed.backingRatioBps), 12_500n);
+
+    const ev = await views.checkSolvency();
+    assert.equal(big(ev.surplusLamports), USDC * FAIR_PER_BACKING_UNIT);
+    assert.equal(big(ev.donatedLamports), 0n);
+    assert.equal(big(ev.backingRatioBps), 12_500n);
+  });
+});
@@ -0,0 +1,119 @@
+// tests/local/token_backed.spec.ts
+// `initialize_token_backed` with an SPL backing mint (6 decimals, like USDC): backing mint
+// admission, the ledger's backing counters, and `check_solvency` against the backing vault.
+import assert from "node:assert/strict";
+import { Keypair, PublicKey, SystemProgram } from "@solana/web3.js";
+import {
+  TOKEN_2022_PROGRAM_ID, ExtensionType, getMintLen,
+  createInitializeMintCloseAuthorityInstruction, createInitializeMint2Instruction,
+} from "@solana/spl-token";
+import { Local, Sale, P, ADMIN, SOL, bn, big, eventOf, packTokenAccount } from "./harness";
+
+const USDC = 1_000_000n; // one backing token (6 decimals)
+const FAIR_PER_BACKING_UNIT = 1_000n; // 10^(9 - 6)
+
+describe("token-backed sale", () => {
+  describe("backing mint admission", () => {
+    it("rejects a Token-2022 backing mint with extensions", async () => {
+      const local = await Local.start();
+      const mint = Keypair.generate();
+      const space = getMintLen([ExtensionType.MintCloseAuthority]);
+      await local.send([
+        SystemProgram.createAccount({
+          fromPubkey: local.payer.publicKey, newAccountPubkey: mint.publicKey,
+          lamports: Number(await local.rent(space)), space, programId: TOKEN_2022_PROGRAM_ID,
+        }),
+        createInitializeMintCloseAuthorityInstruction(mint.publicKey, ADMIN.publicKey, TOKEN_2022_PROGRAM_ID),
+        createInitializeMint2Instruction(mint.publicKey, 6, ADMIN.publicKey, null, TOKEN_2022_PROGRAM_ID),
+      ], [mint]);
+      await assert.rejects(
+        local.initialize({ kind: "token", backingMint: mint.publicKey, backingTokenProgram: TOKEN_2022_PROGRAM_ID }),
+        /BackingMintExtensionsUnsupported/,
+      );
+    });
+
+    it("accepts a Token-2022 backing mint without extensions", async () => {
+      const local = await Local.start();
+      const backingMint = await local.createMint(ADMIN, 6, TOKEN_2022_PROGRAM_ID);
+      const sale = await local.initialize({ kind: "token", backingMint, backingTokenProgram: TOKEN_2022_PROGRAM_ID });
+      assert.ok((await local.config()).backingMint.equals(sale.backingMint!));
+    });
+  });
+
+  describe("buy / redeem / solvency", () => {
+    let local: Local;
+    let sale: Sale;
+    let user: Keypair;
+    let ata: PublicKey;
+    let backingAta: PublicKey;
+
+    const accounts = () => ({
+      mint: sale.mint, mintAuthority: P.mintAuthority, config: P.config, ledger: P.ledger(),
+      tokenVault: P.tokenVault, tokenVaultAccount: sale.tokenVaultAccount, userTokenAccount: ata,
+      backingMint: sale.backingMint!, backingVault: P.backingVault, userBackingAccount: backingAta,
+      backingTokenProgram: sale.backingTokenProgram!,
+    });
+    const checkSolvency = async () => local.methods.checkSolvency()
+      .accountsPartial({ config: P.config, mint: sale.mint, reserveVault: P.backingVault, tokenVaultAccount: sale.tokenVaultAccount })
+      .instruction();
+
+    before(async () => {
+      local = await Local.start();
+      sale = await local.initialize({ kind: "token" });
+      user = await local.user();
+      ata = await local.ata(sale.mint, user.publicKey);
+      backingAta = await local.ata(sale.backingMint!, user.publicKey);
+      await local.mintTo(sale.backingMint!, backingAta, 100n * USDC);
+    });
+
+    it("books buys in backing_in, not lamports_in", async () => {
+      const sent = await local.send(
+        await local.methods.buyWithBacking(bn(5n * USDC)).accountsPartial({ buyer: user.publicKey, ...accounts() }).instruction(),
+        [user],
+      );
+      assert.equal(await local.balance(ata), 5n * SOL);
+      assert.equal(await local.balance(P.backingVault), 5n * USDC);
+      const ledger = await local.ledger();
+      assert.equal(big(ledger.backingIn), 5n * USDC);
+      assert.equal(big(ledger.lamportsIn), 0n);
+      const ev = eventOf(sent, "BuyBackedEvent");
+      assert.equal(big(ev.amount), 5n * USDC * FAIR_PER_BACKING_UNIT);
+      assert.equal(big(ev.backingAmount), 5n * USDC);
+    });
+
+    it("books redeems in backing_out, not lamports_out", async () => {
+      await local.send(
+        await local.methods.redeemForBacking(bn(2n * SOL)).accountsPartial({ redeemer: user.publicKey, ...accounts() }).instruction(),
+        [user],
+      );
+      assert.equal(await local.balance(backingAta), 97n * USDC);
+      const ledger = await local.ledger();
+      assert.equal(big(ledger.backingOut), 2n * USDC);
+      assert.equal(big(ledger.lamportsOut), 0n);
+    });
+
+    it("check_solvency measures the backing vault in fair base units", async () => {
+      const ev = eventOf(await local.send(await checkSolvency()), "SolvencyCheckedEvent");
+      assert.equal(big(ev.circulating), 3n * SOL);
+      assert.equal(big(ev.vaultLamports), 3n * USDC * FAIR_PER_BACKING_UNIT);
+      assert.equal(big(ev.surplusLamports), 0n);
+      assert.equal(big(ev.backingRatioBps), 10_000n);
+    });
+
+    it("check_solvency rejects the SOL vault for a token-backed config", async () => {
+      await local.sendErr(
+        await local.methods.checkSolvency()
+          .accountsPartial({ config: P.config, mint: sale.mint, reserveVault: P.solVault, tokenVaultAccount: sale.tokenVaultAccount })
+          .instruction(),
+        [], "InvalidVault",
+      );
+    });
+
+    it("check_solvency fails once the backing vault holds less than the circulating supply", async () => {
+      const vault = (await local.client.getAccount(P.backingVault))!;
+      const data = packTokenAccount(sale.backingMint!, P.tokenVault, 3n * USDC - 1n);
+      local.setAccount(P.backingVault, vault.lamports, data, vault.owner);
+      await local.sendErr(await checkSolvency(), [], "Insolvent");
+    });
+  });
+});
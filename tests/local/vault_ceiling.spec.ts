@@ -0,0 +1,59 @@
+// tests/local/vault_ceiling.spec.ts
+// `vault_ceiling`: post-finalization restocks that would lift the primary vault above the
+// ceiling burn the excess from the vault, counted in `total_burned`.
+import assert from "node:assert/strict";
+import { Keypair, PublicKey } from "@solana/web3.js";
+import { Local, Sale, PRIMARY_VAULT, SOL, big, eventOf } from "./harness";
+
+const MIN_SUPPLY = 100_000n * SOL; // MIN_SUPPLY_TOKENS in base units
+const CEILING = 2n * SOL;
+
+describe("vault_ceiling", () => {
+  let local: Local;
+  let sale: Sale;
+  let whale: { kp: Keypair; ata: PublicKey };
+
+  const redeem = (amount: bigint) =>
+    local.redeemIx(sale, whale.kp, whale.ata, amount).then(ix => local.send(ix, [whale.kp]));
+
+  before(async () => {
+    // the sale reaches min_supply, so finalize mints no shortfall and the vault starts empty
+    local = await Local.start();
+    sale = await local.initialize({ vaultCeiling: CEILING });
+    whale = await local.holder(sale, MIN_SUPPLY, 100_100n);
+  });
+
+  it("burns nothing before finalization beyond the redemption itself", async () => {
+    const sent = await redeem(SOL);
+    assert.equal(sent.events.some(e => e.name === "SupplyContractedEvent"), false);
+    assert.equal(big((await local.ledger()).totalBurned), SOL);
+  });
+
+  it("restocks below the ceiling in full", async () => {
+    await local.finalize(sale);
+    const sent = await redeem(SOL);
+    assert.equal(sent.events.some(e => e.name === "SupplyContractedEvent"), false);
+    assert.equal(await local.balance(sale.tokenVaultAccount), SOL);
+  });
+
+  it("burns the part of a restock above the ceiling", async () => {
+    const supply0 = await local.supply(sale.mint);
+
+    const contracted = eventOf(await redeem(3n * SOL), "SupplyContractedEvent");
+
+    assert.equal(contracted.vault, PRIMARY_VAULT);
+    assert.equal(big(contracted.burned), 2n * SOL);
+    assert.equal(big(contracted.vaultAmount), CEILING);
+    assert.equal(await local.balance(sale.tokenVaultAccount), CEILING);
+    assert.equal(await local.supply(sale.mint), supply0 - 2n * SOL);
+    const ledger = await local.ledger();
+    assert.equal(big(ledger.totalBurned), 3n * SOL);
+    assert.equal(big(ledger.totalRedeemedToVault), 4n * SOL);
+  });
+
+  it("burns a whole restock once the vault sits at the ceiling", async () => {
+    const contracted = eventOf(await redeem(SOL), "SupplyContractedEvent");
+    assert.equal(big(contracted.burned), SOL);
+    assert.equal(await local.balance(sale.tokenVaultAccount), CEILING);
+  });
+});
@@ -0,0 +1,94 @@
+// tests/local/wsol.spec.ts
+// `buy_fair_token_wsol`: the SOL vault advances the scratch wSOL account's rent and gets it
+// back when the scratch is closed into it; the buyer pays exactly `lamports_sent` in wSOL
+// and no native lamports.
+import assert from "node:assert/strict";
+import { Keypair, PublicKey, SystemProgram } from "@solana/web3.js";
+import { NATIVE_MINT, ACCOUNT_SIZE, createSyncNativeInstruction } from "@solana/spl-token";
+import { Local, Sale, P, SOL, bn, big, pda, PROGRAM_ID, eventOf } from "./harness";
+
+const FEE = 5_000n; // one signature
+
+describe("buy_fair_token_wsol", () => {
+  let local: Local;
+  let sale: Sale;
+  let buyer: Keypair;
+  let ata: PublicKey;
+  let wsol: PublicKey;
+  let scratchRent: bigint;
+
+  const scratch = () => pda(PROGRAM_ID, Buffer.from("wsol_scratch"), buyer.publicKey.toBuffer());
+  const buyIx = (lamports: bigint) => local.methods.buyFairTokenWsol(bn(lamports))
+    .accountsPartial({
+      buyer: buyer.publicKey, ...local.vaultAccounts(sale), userTokenAccount: ata,
+      userWsolAccount: wsol, wsolMint: NATIVE_MINT, wsolScratch: scratch(),
+    })
+    .instruction();
+
+  before(async () => {
+    local = await Local.start();
+    sale = await local.initialize();
+    buyer = await local.user();
+    ata = await local.ata(sale.mint, buyer.publicKey);
+    wsol = await local.ata(NATIVE_MINT, buyer.publicKey);
+    await local.send([
+      SystemProgram.transfer({ fromPubkey: buyer.publicKey, toPubkey: wsol, lamports: Number(10n * SOL) }),
+      createSyncNativeInstruction(wsol),
+    ], [buyer]);
+    scratchRent = await local.rent(ACCOUNT_SIZE);
+  });
+
+  it("needs a SOL vault holding at least the scratch rent to advance it", async () => {
+    await local.sendErr(await buyIx(SOL), [buyer], "VaultSOLInsufficient");
+    await local.holder(sale, SOL); // a native buy funds the vault
+  });
+
+  it("closes the scratch into the SOL vault: the vault gets exactly lamports_sent, paid in wSOL only", async () => {
+    const amount = 2n * SOL;
+    const vault0 = await local.lamports(P.solVault);
+    const wsol0 = await local.balance(wsol);
+    const native0 = await local.lamports(buyer.publicKey);
+
+    const sent = await local.send(await buyIx(amount), [buyer]);
+
+    assert.equal(await local.lamports(P.solVault), vault0 + amount);
+    assert.equal(await local.exists(scratch()), false);
+    assert.equal(await local.balance(ata), amount);
+    // no native lamports from the buyer (fees are paid by the harness payer)
+    assert.equal(await local.lamports(buyer.publicKey), native0);
+    assert.equal(await local.balance(wsol), wsol0 - amount);
+    assert.equal(big((await local.ledger()).lamportsIn), amount);
+    assert.equal(big(eventOf(sent, "BuyMintEvent").state.vaultLamports), vault0 + amount);
+  });
+
+  it("works for a buyer holding no native SOL beyond the transaction fee", async () => {
+    const poor = Keypair.generate();
+    const poorAta = await local.ata(sale.mint, poor.publicKey);
+    const poorWsol = await local.ata(NATIVE_MINT, poor.publicKey);
+    await local.send([
+      SystemProgram.transfer({ fromPubkey: local.payer.publicKey, toPubkey: poorWsol, lamports: Number(SOL) }),
+      createSyncNativeInstruction(poorWsol),
+    ]);
+    local.setAccount(poor.publicKey, FEE, Buffer.alloc(0), SystemProgram.programId);
+    const vault0 = await local.lamports(P.solVault);
+
+    // a buy smaller than the scratch rent is fine too: the rent is not part of the price
+    const amount = scratchRent - 1n;
+    await local.send(
+      await local.methods.buyFairTokenWsol(bn(amount))
+        .accountsPartial({
+          buyer: poor.publicKey, ...local.vaultAccounts(sale), userTokenAccount: poorAta,
+          userWsolAccount: poorWsol, wsolMint: NATIVE_MINT,
+          wsolScratch: pda(PROGRAM_ID, Buffer.from("wsol_scratch"), poor.publicKey.toBuffer()),
+        })
+        .instruction(),
+      [poor],
+      poor,
+    );
+
+    assert.equal(await local.lamports(poor.publicKey), 0n);
+    assert.equal(await local.balance(poorWsol), SOL - amount);
+    assert.equal(await local.balance(poorAta), amount);
+    assert.equal(await local.lamports(P.solVault), vault0 + amount);
+  });
+});